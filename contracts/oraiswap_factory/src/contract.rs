@@ -4,28 +4,41 @@ use std::convert::TryFrom;
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    Reply, Response, StdError, StdResult, SubMsg, WasmMsg,
+    coin, to_json_binary, Addr, Api, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Decimal,
+    Deps, DepsMut, Env, Event, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage,
+    SubMsg, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
+use cw_storage_plus::Bound;
 use oraiswap::error::ContractError;
 use oraiswap::querier::query_pair_info_from_pair;
 use oraiswap::response::MsgInstantiateContractResponse;
 
 use crate::state::{
-    read_pairs, Config, Creator, RestrictedAssets, CONFIG, CREATOR, PAIRS, RESTRICTED_ASSETS,
+    index_pair, pool_key, read_asset_pools, read_failed_pair_migrations,
+    read_pair_by_liquidity_token, read_pair_infos_raw, read_pair_type_config, read_pairs,
+    read_pairs_by_asset, read_restriction_rules, unindex_pair, Config, Creator, CONFIG,
+    CREATION_FEE, CREATOR, DEFAULT_LIMIT, FAILED_PAIR_MIGRATIONS, GUARDIAN, INDEX_PAIRS_PROGRESS,
+    MAX_LIMIT, MIGRATE_PAIRS_PROGRESS, MIGRATING_PAIRS, PAIRS, PAIRS_BY_TYPE, PAIR_TYPE_CONFIGS,
+    RESTRICTED_ASSETS, RESTRICTION_RULES,
 };
 
-use oraiswap::asset::{pair_key, Asset, AssetInfo, PairInfo, PairInfoRaw};
+use oraiswap::asset::{Asset, AssetInfo, PairInfo, PairInfoRaw, PairStatus, PairType};
 use oraiswap::factory::{
-    ConfigResponse, CreatorsResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, PairsResponse,
-    ProvideLiquidityParams, QueryMsg, RestrictedAssetResponse,
+    AssetRestriction, ConfigResponse, CreationFee, CreatorsResponse, ExecuteMsg,
+    FailedPairMigrationsResponse, InstantiateMsg, MigrateMsg, MigratePairsProgress, PairTypeConfig,
+    PairTypesResponse, PairsResponse, ProvideLiquidityParams, QueryMsg, RestrictedAsset,
+    RestrictedAssetResponse,
 };
 use oraiswap::pair::{
-    InstantiateMsg as PairInstantiateMsg, DEFAULT_COMMISSION_RATE, DEFAULT_OPERATOR_FEE,
+    ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg, StableSwapParams,
+    DEFAULT_COMMISSION_RATE, DEFAULT_OPERATOR_FEE,
 };
+use std::str::FromStr;
 
 const INSTANTIATE_REPLY_ID: u64 = 1;
+/// Reply ids of a `MigratePairs` page start here, offset by the pair index in the page
+pub const MIGRATE_PAIR_REPLY_ID: u64 = 1000;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -71,6 +84,9 @@ pub fn execute(
             pair_admin,
             operator,
             provide_liquidity,
+            pair_type,
+            stable_swap,
+            native_lp,
         } => execute_create_pair(
             deps,
             env,
@@ -79,6 +95,9 @@ pub fn execute(
             pair_admin,
             operator,
             provide_liquidity,
+            pair_type.unwrap_or_default(),
+            stable_swap,
+            native_lp.unwrap_or_default(),
         ),
         ExecuteMsg::AddPair { pair_info } => execute_add_pair_manually(deps, env, info, pair_info),
         ExecuteMsg::MigrateContract {
@@ -86,12 +105,71 @@ pub fn execute(
             new_code_id,
             msg,
         } => migrate_pair(deps, env, info, contract_addr, new_code_id, msg),
-        ExecuteMsg::ProvideLiquidity { assets, receiver } => {
-            execute_provide_liquidity(deps, env, info, assets, receiver)
-        }
+        ExecuteMsg::ProvideLiquidity {
+            assets,
+            receiver,
+            pair_type,
+        } => execute_provide_liquidity(
+            deps,
+            env,
+            info,
+            assets,
+            receiver,
+            pair_type.unwrap_or_default(),
+        ),
         ExecuteMsg::RestrictAsset { prefix } => execute_restrict_asset(deps, info, prefix),
+        ExecuteMsg::RestrictAssetRule {
+            rule,
+            exempt_creators,
+        } => execute_restrict_asset_rule(deps, info, rule, exempt_creators),
+        ExecuteMsg::UnrestrictAsset { rule } => execute_unrestrict_asset(deps, info, rule),
         ExecuteMsg::AddCreator { address } => add_creator(deps, info, address),
         ExecuteMsg::RemoveCreator { address } => remove_creator(deps, info, address),
+        ExecuteMsg::UpdatePairTypeConfig { config } => {
+            execute_update_pair_type_config(deps, info, config)
+        }
+        ExecuteMsg::UpdateGuardian { guardian } => execute_update_guardian(deps, info, guardian),
+        ExecuteMsg::PausePair {
+            pair_addr,
+            swap,
+            provide,
+            withdraw,
+        } => execute_pause_pair(deps, info, pair_addr, swap, provide, withdraw),
+        ExecuteMsg::MigratePairs {
+            new_code_id,
+            msg,
+            start_after,
+            start_after_pair_type,
+            limit,
+        } => execute_migrate_pairs(
+            deps,
+            info,
+            new_code_id,
+            msg,
+            start_after,
+            start_after_pair_type,
+            limit,
+        ),
+        ExecuteMsg::DeprecatePair {
+            asset_infos,
+            pair_type,
+            successor,
+            successor_pair_type,
+        } => execute_deprecate_pair(
+            deps,
+            info,
+            asset_infos,
+            pair_type.unwrap_or_default(),
+            successor.map(|successor| (successor, successor_pair_type.unwrap_or_default())),
+        ),
+        ExecuteMsg::RemovePair {
+            asset_infos,
+            pair_type,
+        } => execute_remove_pair(deps, info, asset_infos, pair_type.unwrap_or_default()),
+        ExecuteMsg::IndexPairs { limit } => execute_index_pairs(deps, info, limit),
+        ExecuteMsg::UpdateCreationFee { creation_fee } => {
+            execute_update_creation_fee(deps, info, creation_fee)
+        }
     }
 }
 
@@ -154,6 +232,325 @@ pub fn remove_creator(
     Ok(res)
 }
 
+pub fn execute_update_guardian(
+    deps: DepsMut,
+    info: MessageInfo,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match &guardian {
+        Some(guardian) => GUARDIAN.save(deps.storage, &deps.api.addr_validate(guardian)?)?,
+        None => GUARDIAN.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_guardian")
+        .add_attribute("guardian", guardian.unwrap_or_default()))
+}
+
+/// Forward the pause flags to the pair, which only trusts its admin and its factory
+pub fn execute_pause_pair(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_addr: String,
+    swap: Option<bool>,
+    provide: Option<bool>,
+    withdraw: Option<bool>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner
+        && GUARDIAN.may_load(deps.storage)?.as_ref() != Some(&info.sender)
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+    Ok(Response::new()
+        .add_attribute("action", "pause_pair")
+        .add_attribute("pair_addr", pair_addr.as_str())
+        .add_message(WasmMsg::Execute {
+            contract_addr: pair_addr.to_string(),
+            msg: to_json_binary(&PairExecuteMsg::UpdatePauseStatus {
+                swap,
+                provide,
+                withdraw,
+            })?,
+            funds: vec![],
+        }))
+}
+
+pub fn execute_update_creation_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    creation_fee: Option<CreationFee>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match &creation_fee {
+        Some(creation_fee) => {
+            deps.api.addr_validate(creation_fee.collector.as_str())?;
+            CREATION_FEE.save(deps.storage, creation_fee)?;
+        }
+        None => CREATION_FEE.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_creation_fee"),
+        (
+            "creation_fee",
+            &creation_fee
+                .map(|creation_fee| creation_fee.fee.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Charges the creation fee to senders other than the owner and the creators,
+/// returns the fee transfer and the funds left for the initial liquidity
+fn charge_creation_fee(
+    deps: Deps,
+    info: &MessageInfo,
+    config: &Config,
+    creators: &Creator,
+) -> Result<(Option<CosmosMsg>, Vec<Coin>), ContractError> {
+    let mut funds = info.funds.clone();
+    let creation_fee = match CREATION_FEE.may_load(deps.storage)? {
+        Some(creation_fee) if !creation_fee.fee.amount.is_zero() => creation_fee,
+        _ => return Ok((None, funds)),
+    };
+    if deps.api.addr_canonicalize(info.sender.as_str())? == config.owner
+        || creators.whitelist_addresses.contains(&info.sender)
+    {
+        return Ok((None, funds));
+    }
+
+    let fee_msg: CosmosMsg = match &creation_fee.fee.info {
+        AssetInfo::NativeToken { denom } => {
+            let paid = funds
+                .iter_mut()
+                .find(|coin| coin.denom.eq(denom) && coin.amount >= creation_fee.fee.amount)
+                .ok_or(ContractError::CreationFeeRequired {
+                    fee: creation_fee.fee.to_string(),
+                })?;
+            paid.amount -= creation_fee.fee.amount;
+            funds.retain(|coin| !coin.amount.is_zero());
+
+            BankMsg::Send {
+                to_address: creation_fee.collector.to_string(),
+                amount: vec![coin(creation_fee.fee.amount.u128(), denom)],
+            }
+            .into()
+        }
+        AssetInfo::Token { contract_addr } => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: creation_fee.collector.to_string(),
+                amount: creation_fee.fee.amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    };
+
+    Ok((Some(fee_msg), funds))
+}
+
+/// A pool of the same assets and pair type can not be registered again unless it was removed
+fn assert_pair_unregistered(storage: &dyn Storage, pool_key: &[u8]) -> Result<(), ContractError> {
+    match PAIRS.may_load(storage, pool_key)? {
+        Some(registered) if registered.status != PairStatus::Removed => {
+            Err(ContractError::PairExisted {})
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Drops the removed registration a new pair replaces from the lookups
+fn unindex_removed_pair(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    pair_key: &[u8],
+) -> StdResult<()> {
+    match PAIRS.may_load(storage, pair_key)? {
+        Some(removed) => unindex_pair(storage, api, pair_key, &removed),
+        None => Ok(()),
+    }
+}
+
+/// Loads a deployed pair that has not been removed
+fn load_registered_pair(
+    deps: Deps,
+    asset_infos: &[AssetInfo; 2],
+    pair_type: &PairType,
+) -> Result<(Vec<u8>, PairInfoRaw), ContractError> {
+    let pair_key = pool_key(
+        &[
+            asset_infos[0].to_raw(deps.api)?,
+            asset_infos[1].to_raw(deps.api)?,
+        ],
+        pair_type,
+    );
+    let pair_info = PAIRS.load(deps.storage, &pair_key)?;
+    if pair_info.contract_addr.is_empty() || pair_info.status == PairStatus::Removed {
+        return Err(ContractError::PairNotActive {});
+    }
+
+    Ok((pair_key, pair_info))
+}
+
+pub fn execute_deprecate_pair(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    pair_type: PairType,
+    successor: Option<([AssetInfo; 2], PairType)>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (pair_key, mut pair_info) = load_registered_pair(deps.as_ref(), &asset_infos, &pair_type)?;
+    let successor = match successor {
+        Some((successor, successor_pair_type)) => {
+            let (_, successor) =
+                load_registered_pair(deps.as_ref(), &successor, &successor_pair_type)?;
+            // the successor must stay active once this pair is deprecated
+            if successor.status != PairStatus::Active
+                || successor.contract_addr == pair_info.contract_addr
+            {
+                return Err(ContractError::PairNotActive {});
+            }
+            Some(successor.contract_addr)
+        }
+        None => None,
+    };
+
+    unindex_pair(deps.storage, deps.api, &pair_key, &pair_info)?;
+    pair_info.status = PairStatus::Deprecated;
+    pair_info.successor = successor;
+    PAIRS.save(deps.storage, &pair_key, &pair_info)?;
+    index_pair(deps.storage, deps.api, &pair_key, &pair_info)?;
+
+    // the pair mirrors its registry status
+    let pair_info = pair_info.to_normal(deps.api)?;
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: pair_info.contract_addr.to_string(),
+            msg: to_json_binary(&PairExecuteMsg::UpdatePairStatus {
+                status: PairStatus::Deprecated,
+                successor: pair_info.successor.clone(),
+            })?,
+            funds: vec![],
+        })
+        .add_attributes(vec![
+            ("action", "deprecate_pair"),
+            ("pair_contract_address", pair_info.contract_addr.as_str()),
+            (
+                "successor",
+                pair_info
+                    .successor
+                    .as_ref()
+                    .map(|successor| successor.as_str())
+                    .unwrap_or_default(),
+            ),
+        ]))
+}
+
+pub fn execute_remove_pair(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    pair_type: PairType,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (pair_key, mut pair_info) = load_registered_pair(deps.as_ref(), &asset_infos, &pair_type)?;
+    unindex_pair(deps.storage, deps.api, &pair_key, &pair_info)?;
+    pair_info.status = PairStatus::Removed;
+    PAIRS.save(deps.storage, &pair_key, &pair_info)?;
+    index_pair(deps.storage, deps.api, &pair_key, &pair_info)?;
+
+    let pair_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: pair_addr.to_string(),
+            msg: to_json_binary(&PairExecuteMsg::UpdatePairStatus {
+                status: PairStatus::Removed,
+                successor: None,
+            })?,
+            funds: vec![],
+        })
+        .add_attributes(vec![
+            ("action", "remove_pair"),
+            ("pair_contract_address", pair_addr.as_str()),
+        ]))
+}
+
+/// Indexes a page of the pairs registered before the reverse lookups
+pub fn execute_index_pairs(
+    deps: DepsMut,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let last_key = INDEX_PAIRS_PROGRESS
+        .may_load(deps.storage)?
+        .filter(|last_key| !last_key.is_empty());
+    let pairs = PAIRS
+        .range(
+            deps.storage,
+            last_key.as_deref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let done = pairs.len() < limit;
+
+    let mut indexed = 0u32;
+    for (pair_key, pair_info) in pairs.iter() {
+        if !pair_info.contract_addr.is_empty() {
+            index_pair(deps.storage, deps.api, pair_key, pair_info)?;
+            indexed += 1;
+        }
+    }
+
+    match pairs.last() {
+        Some((pair_key, _)) if !done => INDEX_PAIRS_PROGRESS.save(deps.storage, pair_key)?,
+        _ => INDEX_PAIRS_PROGRESS.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "index_pairs"),
+        ("indexed", &indexed.to_string()),
+        ("done", &done.to_string()),
+    ]))
+}
+
 pub fn migrate_pair(
     deps: DepsMut,
     _env: Env,
@@ -179,6 +576,95 @@ pub fn migrate_pair(
         .add_message(wasm_msg))
 }
 
+/// Migrates a page of pairs, each migration is reported in the reply and a failed one
+/// does not revert the others
+pub fn execute_migrate_pairs(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_code_id: u64,
+    msg: Binary,
+    start_after: Option<[AssetInfo; 2]>,
+    start_after_pair_type: Option<PairType>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // resume the unfinished sweep of the same code id, otherwise start a new one
+    let mut progress = match MIGRATE_PAIRS_PROGRESS.may_load(deps.storage)? {
+        Some(progress) if progress.new_code_id == new_code_id && !progress.done => progress,
+        _ => MigratePairsProgress {
+            new_code_id,
+            last_pair: None,
+            last_pair_type: None,
+            migrated: 0,
+            failed: 0,
+            done: false,
+        },
+    };
+    let (start_after, start_after_pair_type) = match start_after {
+        Some(start_after) => (Some(start_after), start_after_pair_type),
+        None => (progress.last_pair.clone(), progress.last_pair_type.clone()),
+    };
+    let start_after = match start_after {
+        Some(asset_infos) => Some([
+            asset_infos[0].to_raw(deps.api)?,
+            asset_infos[1].to_raw(deps.api)?,
+        ]),
+        None => None,
+    };
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let pairs = read_pair_infos_raw(
+        deps.storage,
+        start_after,
+        start_after_pair_type,
+        Some(limit),
+    )?;
+    progress.done = pairs.len() < limit as usize;
+
+    let mut migrating_pairs: Vec<Addr> = vec![];
+    let mut sub_msgs: Vec<SubMsg> = vec![];
+    for pair_info in pairs.iter() {
+        progress.last_pair = Some([
+            pair_info.asset_infos[0].to_normal(deps.api)?,
+            pair_info.asset_infos[1].to_normal(deps.api)?,
+        ]);
+        progress.last_pair_type = Some(pair_info.pair_type.clone());
+        // pairs still being created have no contract yet
+        if pair_info.contract_addr.is_empty() {
+            continue;
+        }
+
+        let pair_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+        sub_msgs.push(SubMsg::reply_always(
+            WasmMsg::Migrate {
+                contract_addr: pair_addr.to_string(),
+                new_code_id,
+                msg: msg.clone(),
+            },
+            MIGRATE_PAIR_REPLY_ID + migrating_pairs.len() as u64,
+        ));
+        migrating_pairs.push(pair_addr);
+    }
+
+    MIGRATE_PAIRS_PROGRESS.save(deps.storage, &progress)?;
+    MIGRATING_PAIRS.save(deps.storage, &migrating_pairs)?;
+
+    Ok(Response::new()
+        .add_submessages(sub_msgs)
+        .add_attributes(vec![
+            ("action", "migrate_pairs"),
+            ("new_code_id", &new_code_id.to_string()),
+            ("migrating", &migrating_pairs.len().to_string()),
+            ("done", &progress.done.to_string()),
+        ]))
+}
+
 // Only owner can execute it
 pub fn execute_update_config(
     deps: DepsMut,
@@ -212,7 +698,38 @@ pub fn execute_update_config(
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+// Only owner can execute it
+pub fn execute_update_pair_type_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_type_config: PairTypeConfig,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let total_fee = Decimal::from_str(&pair_type_config.commission_rate)?
+        + Decimal::from_str(&pair_type_config.operator_fee)?;
+    if total_fee >= Decimal::one() {
+        return Err(StdError::generic_err("Total fee must be less than 1").into());
+    }
+
+    let pair_type = pair_type_config.pair_type.to_string();
+    PAIR_TYPE_CONFIGS.save(deps.storage, &pair_type, &pair_type_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_pair_type_config"),
+        ("pair_type", &pair_type),
+        ("code_id", &pair_type_config.code_id.to_string()),
+        ("is_disabled", &pair_type_config.is_disabled.to_string()),
+    ]))
+}
+
 // Anyone can execute it to create swap pair
+#[allow(clippy::too_many_arguments)]
 pub fn execute_create_pair(
     deps: DepsMut,
     env: Env,
@@ -221,58 +738,87 @@ pub fn execute_create_pair(
     pair_admin: Option<String>,
     _operator: Option<String>,
     provide_liquidity: Option<ProvideLiquidityParams>,
+    pair_type: PairType,
+    stable_swap: Option<StableSwapParams>,
+    native_lp: bool,
 ) -> Result<Response, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
+    let pair_type_config = read_pair_type_config(deps.storage, &config, &pair_type)?.ok_or(
+        ContractError::PairTypeNotFound {
+            pair_type: pair_type.to_string(),
+        },
+    )?;
+    if pair_type_config.is_disabled {
+        return Err(ContractError::PairTypeDisabled {
+            pair_type: pair_type.to_string(),
+        });
+    }
+
+    let stable_swap = match pair_type {
+        PairType::Xyk {} => None,
+        PairType::Stable {} => Some(stable_swap.ok_or(ContractError::MissingStableSwapParams {})?),
+        PairType::Custom(_) => stable_swap,
+    };
+
     let raw_infos: [oraiswap::asset::AssetInfoRaw; 2] = [
         asset_infos[0].to_raw(deps.api)?,
         asset_infos[1].to_raw(deps.api)?,
     ];
 
-    let restricted_list = RESTRICTED_ASSETS
-        .may_load(deps.storage)?
-        .unwrap_or(RestrictedAssets { assets: Vec::new() });
+    let restriction_rules = RESTRICTION_RULES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, v)| v))
+        .collect::<StdResult<Vec<RestrictedAsset>>>()?;
 
     let creators = CREATOR.may_load(deps.storage)?.unwrap_or(Creator {
         whitelist_addresses: vec![],
     });
 
     for asset in asset_infos.as_ref().into_iter() {
-        if let AssetInfo::NativeToken { denom, .. } = &asset {
-            if denom.contains("factory/orai1") {
-                let parts: Vec<&str> = denom.split('/').collect();
-                if parts.len() > 2 && restricted_list.assets.contains(&parts[0..2].join("/")) {
-                    // permission check
-                    if !creators.whitelist_addresses.contains(&info.sender) {
-                        return Err(ContractError::Unauthorized {});
-                    }
-                }
+        for restricted in restriction_rules
+            .iter()
+            .filter(|restricted| restricted.rule.matches(asset))
+        {
+            // permission check
+            if !creators.whitelist_addresses.contains(&info.sender)
+                && !restricted.exempt_creators.contains(&info.sender)
+            {
+                return Err(ContractError::Unauthorized {});
             }
         }
     }
 
-    let pair_key = pair_key(&raw_infos);
+    let pair_key = pool_key(&raw_infos, &pair_type);
+    assert_pair_unregistered(deps.storage, &pair_key)?;
 
-    // can not update pair once updated
-    if let Ok(Some(_)) = PAIRS.may_load(deps.storage, &pair_key) {
-        return Err(ContractError::PairExisted {});
+    // senders outside the creators list pay for the listing, the rest goes to the liquidity
+    let (fee_msg, funds) = charge_creation_fee(deps.as_ref(), &info, &config, &creators)?;
+    if provide_liquidity.is_none() && !funds.is_empty() {
+        return Err(ContractError::UnexpectedCreationFunds {});
     }
 
+    unindex_removed_pair(deps.storage, deps.api, &pair_key)?;
+
     PAIRS.save(
         deps.storage,
         &pair_key,
         &PairInfoRaw {
             oracle_addr: config.oracle_addr.clone(),
             liquidity_token: CanonicalAddr::from(vec![]),
+            liquidity_denom: None,
             contract_addr: CanonicalAddr::from(vec![]),
             asset_infos: raw_infos,
-            commission_rate: config.commission_rate.clone(),
-            operator_fee: config.operator_fee.clone(),
+            commission_rate: pair_type_config.commission_rate.clone(),
+            operator_fee: pair_type_config.operator_fee.clone(),
+            pair_type: pair_type.clone(),
+            status: PairStatus::Active,
+            successor: None,
         },
     )?;
     let pair_admin = pair_admin.unwrap_or(env.contract.address.to_string());
 
     // if provide_liquidity is not None, transfer all cw20 tokens to this contract
-    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut messages: Vec<CosmosMsg> = fee_msg.into_iter().collect();
 
     if let Some(ProvideLiquidityParams { assets, receiver }) = provide_liquidity {
         let receiver = receiver.unwrap_or(info.sender.clone());
@@ -293,15 +839,19 @@ pub fn execute_create_pair(
 
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: env.contract.address.to_string(),
-            msg: to_json_binary(&ExecuteMsg::ProvideLiquidity { assets, receiver })?,
-            funds: info.funds,
+            msg: to_json_binary(&ExecuteMsg::ProvideLiquidity {
+                assets,
+                receiver,
+                pair_type: Some(pair_type.clone()),
+            })?,
+            funds,
         }));
     }
 
     Ok(Response::new()
         .add_submessage(SubMsg::reply_on_success(
             WasmMsg::Instantiate {
-                code_id: config.pair_code_id,
+                code_id: pair_type_config.code_id,
                 funds: vec![],
                 admin: Some(pair_admin.clone()),
                 label: "pair".to_string(),
@@ -309,10 +859,14 @@ pub fn execute_create_pair(
                     oracle_addr: deps.api.addr_humanize(&config.oracle_addr)?,
                     asset_infos: asset_infos.clone(),
                     token_code_id: config.token_code_id,
-                    commission_rate: Some(config.commission_rate),
+                    commission_rate: Some(pair_type_config.commission_rate),
                     admin: Some(deps.api.addr_validate(&pair_admin)?),
-                    operator_fee: Some(config.operator_fee),
+                    operator_fee: Some(pair_type_config.operator_fee),
                     operator: Some(deps.api.addr_humanize(&config.operator)?),
+                    stable_swap,
+                    pair_type: Some(pair_type.clone()),
+                    native_lp,
+                    factory: Some(env.contract.address.clone()),
                 })?,
             },
             INSTANTIATE_REPLY_ID,
@@ -320,6 +874,7 @@ pub fn execute_create_pair(
         .add_attributes(vec![
             ("action", "create_pair"),
             ("pair", &format!("{}-{}", asset_infos[0], asset_infos[1])),
+            ("pair_type", &pair_type.to_string()),
         ])
         .add_messages(messages))
 }
@@ -342,29 +897,28 @@ pub fn execute_add_pair_manually(
         pair_info.asset_infos[1].to_raw(deps.api)?,
     ];
 
-    let pair_key = pair_key(&raw_infos);
+    let pair_key = pool_key(&raw_infos, &pair_info.pair_type);
 
-    // can not update pair once updated
-    if let Ok(Some(_)) = PAIRS.may_load(deps.storage, &pair_key) {
-        return Err(ContractError::PairExisted {});
-    }
+    assert_pair_unregistered(deps.storage, &pair_key)?;
+    unindex_removed_pair(deps.storage, deps.api, &pair_key)?;
 
-    PAIRS.save(
-        deps.storage,
-        &pair_key,
-        &PairInfoRaw {
-            oracle_addr: deps.api.addr_canonicalize(pair_info.oracle_addr.as_str())?,
-            liquidity_token: deps
-                .api
-                .addr_canonicalize(pair_info.liquidity_token.as_str())?,
-            contract_addr: deps
-                .api
-                .addr_canonicalize(pair_info.contract_addr.as_str())?,
-            asset_infos: raw_infos,
-            commission_rate: pair_info.commission_rate.clone(),
-            operator_fee: pair_info.operator_fee,
-        },
-    )?;
+    let (liquidity_token, liquidity_denom) = pair_info.liquidity_token_raw(deps.api)?;
+    let pair_info_raw = PairInfoRaw {
+        oracle_addr: deps.api.addr_canonicalize(pair_info.oracle_addr.as_str())?,
+        liquidity_token,
+        liquidity_denom,
+        contract_addr: deps
+            .api
+            .addr_canonicalize(pair_info.contract_addr.as_str())?,
+        asset_infos: raw_infos,
+        commission_rate: pair_info.commission_rate.clone(),
+        operator_fee: pair_info.operator_fee,
+        pair_type: pair_info.pair_type,
+        status: PairStatus::Active,
+        successor: None,
+    };
+    PAIRS.save(deps.storage, &pair_key, &pair_info_raw)?;
+    index_pair(deps.storage, deps.api, &pair_key, &pair_info_raw)?;
 
     Ok(Response::new().add_attributes(vec![
         ("action", "add_pair"),
@@ -381,13 +935,17 @@ pub fn execute_provide_liquidity(
     info: MessageInfo,
     assets: [Asset; 2],
     receiver: Addr,
+    pair_type: PairType,
 ) -> Result<Response, ContractError> {
     if info.sender != env.contract.address {
         return Err(ContractError::Unauthorized {});
     }
 
     let asset_infos = [assets[0].info.clone(), assets[1].info.clone()];
-    let pair_key = pair_key(&asset_infos.map(|a| a.to_raw(deps.api).unwrap()));
+    let pair_key = pool_key(
+        &asset_infos.map(|a| a.to_raw(deps.api).unwrap()),
+        &pair_type,
+    );
     let pair_raw = PAIRS.load(deps.storage, &pair_key)?;
     let pair_contract = deps.api.addr_humanize(&pair_raw.contract_addr)?;
 
@@ -423,6 +981,7 @@ pub fn execute_provide_liquidity(
             assets,
             slippage_tolerance: None,
             receiver: Some(receiver),
+            deadline: None,
         })?,
         funds,
     });
@@ -444,15 +1003,21 @@ pub fn execute_restrict_asset(
         return Err(ContractError::Unauthorized {});
     }
 
-    let mut restrict_list = RESTRICTED_ASSETS
-        .may_load(deps.storage)?
-        .unwrap_or(RestrictedAssets { assets: vec![] });
-    if restrict_list.assets.contains(&prefix) {
+    let rule = AssetRestriction::Prefix {
+        prefix: prefix.clone(),
+    };
+    if RESTRICTION_RULES.has(deps.storage, &rule.key()) {
         return Err(ContractError::RestrictPrefixExisted {});
     }
-    restrict_list.assets.push(prefix.clone());
-
-    RESTRICTED_ASSETS.save(deps.storage, &restrict_list)?;
+    RESTRICTION_RULES.save(
+        deps.storage,
+        &rule.key(),
+        &RestrictedAsset {
+            rule: rule.clone(),
+            added_by: info.sender,
+            exempt_creators: vec![],
+        },
+    )?;
 
     let res = Response::new()
         .add_attribute("method", "restrict_asset")
@@ -461,9 +1026,116 @@ pub fn execute_restrict_asset(
     Ok(res)
 }
 
-/// This just stores the result for future query
+pub fn execute_restrict_asset_rule(
+    deps: DepsMut,
+    info: MessageInfo,
+    rule: AssetRestriction,
+    exempt_creators: Vec<Addr>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for creator in exempt_creators.iter() {
+        deps.api.addr_validate(creator.as_str())?;
+    }
+    let key = rule.key();
+    if RESTRICTION_RULES.has(deps.storage, &key) {
+        return Err(ContractError::RestrictRuleExisted {});
+    }
+    RESTRICTION_RULES.save(
+        deps.storage,
+        &key,
+        &RestrictedAsset {
+            rule,
+            added_by: info.sender,
+            exempt_creators,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "restrict_asset_rule")
+        .add_attribute("restrict_asset", key))
+}
+
+pub fn execute_unrestrict_asset(
+    deps: DepsMut,
+    info: MessageInfo,
+    rule: AssetRestriction,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let key = rule.key();
+    if !RESTRICTION_RULES.has(deps.storage, &key) {
+        return Err(ContractError::RestrictionNotFound {});
+    }
+    RESTRICTION_RULES.remove(deps.storage, &key);
+
+    Ok(Response::new()
+        .add_attribute("method", "unrestrict_asset")
+        .add_attribute("unrestrict_asset", key))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_REPLY_ID => create_pair_reply(deps, msg),
+        id if (MIGRATE_PAIR_REPLY_ID..MIGRATE_PAIR_REPLY_ID + MAX_LIMIT as u64).contains(&id) => {
+            migrate_pair_reply(deps, msg)
+        }
+        _ => Err(StdError::generic_err(format!("Unknown reply id {}", msg.id)).into()),
+    }
+}
+
+/// Records the outcome of a pair migration of a `MigratePairs` page
+fn migrate_pair_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let pair_addr = msg
+        .id
+        .checked_sub(MIGRATE_PAIR_REPLY_ID)
+        .and_then(|index| {
+            MIGRATING_PAIRS
+                .load(deps.storage)
+                .ok()?
+                .get(index as usize)
+                .cloned()
+        })
+        .ok_or_else(|| StdError::not_found("migrating pair"))?;
+
+    let mut progress = MIGRATE_PAIRS_PROGRESS.load(deps.storage)?;
+    let event = match msg.result.into_result() {
+        Ok(_) => {
+            progress.migrated += 1;
+            // a pair retried by a later sweep of the same code id
+            FAILED_PAIR_MIGRATIONS.remove(deps.storage, (progress.new_code_id, &pair_addr));
+            Event::new("migrate_pair").add_attribute("pair", pair_addr.as_str())
+        }
+        Err(error) => {
+            progress.failed += 1;
+            FAILED_PAIR_MIGRATIONS.save(
+                deps.storage,
+                (progress.new_code_id, &pair_addr),
+                &error,
+            )?;
+            Event::new("migrate_pair_failed")
+                .add_attribute("pair", pair_addr.as_str())
+                .add_attribute("error", error)
+        }
+    };
+    MIGRATE_PAIRS_PROGRESS.save(deps.storage, &progress)?;
+
+    Ok(Response::new().add_event(event))
+}
+
+/// This just stores the result for future query
+fn create_pair_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
     let data = msg.result.unwrap().data.unwrap();
     let res = MsgInstantiateContractResponse::try_from(data.as_slice()).map_err(|_| {
         StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
@@ -471,7 +1143,10 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 
     let pair_contract = Addr::unchecked(res.address);
     let pair_info = query_pair_info_from_pair(&deps.querier, pair_contract.clone())?;
-    let pair_key = pair_key(&pair_info.asset_infos.map(|a| a.to_raw(deps.api).unwrap()));
+    let pair_key = pool_key(
+        &pair_info.asset_infos.map(|a| a.to_raw(deps.api).unwrap()),
+        &pair_info.pair_type,
+    );
 
     // get pair info raw from state
     let mut pair_info_raw = PAIRS.load(deps.storage, &pair_key)?;
@@ -482,16 +1157,19 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
     }
 
     // the contract must follow the standard interface
-    pair_info_raw.liquidity_token = deps
-        .api
-        .addr_canonicalize(pair_info.liquidity_token.as_str())?;
+    (pair_info_raw.liquidity_token, pair_info_raw.liquidity_denom) =
+        pair_info.liquidity_token_raw(deps.api)?;
     pair_info_raw.contract_addr = deps.api.addr_canonicalize(pair_contract.as_str())?;
 
     PAIRS.save(deps.storage, &pair_key, &pair_info_raw)?;
+    index_pair(deps.storage, deps.api, &pair_key, &pair_info_raw)?;
 
     Ok(Response::new().add_attributes(vec![
         ("pair_contract_address", pair_contract.as_str()),
-        ("liquidity_token_addr", pair_info.liquidity_token.as_str()),
+        (
+            "liquidity_token_addr",
+            &pair_info.liquidity_token_info()?.to_string(),
+        ),
     ]))
 }
 
@@ -499,12 +1177,52 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
-        QueryMsg::Pair { asset_infos } => to_json_binary(&query_pair(deps, asset_infos)?),
-        QueryMsg::Pairs { start_after, limit } => {
-            to_json_binary(&query_pairs(deps, start_after, limit)?)
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type,
+        } => to_json_binary(&query_pair(deps, asset_infos, pair_type)?),
+        QueryMsg::Pairs {
+            start_after,
+            start_after_pair_type,
+            limit,
+            pair_type,
+            include_inactive,
+        } => to_json_binary(&query_pairs(
+            deps,
+            start_after,
+            start_after_pair_type,
+            limit,
+            pair_type,
+            include_inactive.unwrap_or_default(),
+        )?),
+        QueryMsg::RestrictedAssets { start_after, limit } => {
+            to_json_binary(&query_restricted_assets(deps, start_after, limit)?)
         }
-        QueryMsg::RestrictedAssets {} => to_json_binary(&query_restricted_assets(deps)?),
         QueryMsg::GetCreators {} => to_json_binary(&get_creators(deps)?),
+        QueryMsg::PairTypes {} => to_json_binary(&query_pair_types(deps)?),
+        QueryMsg::Guardian {} => to_json_binary(&GUARDIAN.may_load(deps.storage)?),
+        QueryMsg::MigratePairsProgress {} => {
+            to_json_binary(&MIGRATE_PAIRS_PROGRESS.may_load(deps.storage)?)
+        }
+        QueryMsg::FailedPairMigrations { start_after, limit } => {
+            to_json_binary(&query_failed_pair_migrations(deps, start_after, limit)?)
+        }
+        QueryMsg::PairByLiquidityToken { liquidity_token } => {
+            to_json_binary(&query_pair_by_liquidity_token(deps, liquidity_token)?)
+        }
+        QueryMsg::PairsByAsset {
+            asset_info,
+            start_after,
+            start_after_pair_type,
+            limit,
+        } => to_json_binary(&query_pairs_by_asset(
+            deps,
+            asset_info,
+            start_after,
+            start_after_pair_type,
+            limit,
+        )?),
+        QueryMsg::CreationFee {} => to_json_binary(&CREATION_FEE.may_load(deps.storage)?),
     }
 }
 
@@ -523,19 +1241,41 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     Ok(resp)
 }
 
-pub fn query_pair(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<PairInfo> {
-    let pair_key = pair_key(&[
+pub fn query_pair(
+    deps: Deps,
+    asset_infos: [AssetInfo; 2],
+    pair_type: Option<PairType>,
+) -> StdResult<PairInfo> {
+    let raw_infos = [
         asset_infos[0].to_raw(deps.api)?,
         asset_infos[1].to_raw(deps.api)?,
-    ]);
-    let pair_info: PairInfoRaw = PAIRS.load(deps.storage, &pair_key)?;
+    ];
+    let pair_info = match pair_type {
+        Some(pair_type) => PAIRS.load(deps.storage, &pool_key(&raw_infos, &pair_type))?,
+        None => {
+            let pools = read_asset_pools(deps.storage, &raw_infos)?;
+            pools
+                .iter()
+                .find(|pool| pool.status == PairStatus::Active)
+                .or_else(|| pools.iter().find(|pool| pool.status != PairStatus::Removed))
+                .cloned()
+                .ok_or_else(|| StdError::not_found("PairInfo"))?
+        }
+    };
+    // a removed pair is no longer registered
+    if pair_info.status == PairStatus::Removed {
+        return Err(StdError::not_found("PairInfo"));
+    }
     pair_info.to_normal(deps.api)
 }
 
 pub fn query_pairs(
     deps: Deps,
     start_after: Option<[AssetInfo; 2]>,
+    start_after_pair_type: Option<PairType>,
     limit: Option<u32>,
+    pair_type: Option<PairType>,
+    include_inactive: bool,
 ) -> StdResult<PairsResponse> {
     let start_after = if let Some(start_after) = start_after {
         Some([
@@ -546,20 +1286,102 @@ pub fn query_pairs(
         None
     };
 
-    let pairs: Vec<PairInfo> = read_pairs(deps.storage, deps.api, start_after, limit)?;
+    let pairs: Vec<PairInfo> = read_pairs(
+        deps.storage,
+        deps.api,
+        start_after,
+        start_after_pair_type,
+        limit,
+        pair_type,
+        include_inactive,
+    )?;
     let resp = PairsResponse { pairs };
 
     Ok(resp)
 }
 
-pub fn query_restricted_assets(deps: Deps) -> StdResult<RestrictedAssetResponse> {
-    let restricted_list = RESTRICTED_ASSETS
-        .may_load(deps.storage)?
-        .unwrap_or(RestrictedAssets { assets: Vec::new() });
+pub fn query_pair_by_liquidity_token(deps: Deps, liquidity_token: String) -> StdResult<PairInfo> {
+    read_pair_by_liquidity_token(deps.storage, deps.api, &liquidity_token)
+}
 
-    Ok(RestrictedAssetResponse {
-        prefixes: restricted_list.assets,
-    })
+pub fn query_pairs_by_asset(
+    deps: Deps,
+    asset_info: AssetInfo,
+    start_after: Option<[AssetInfo; 2]>,
+    start_after_pair_type: Option<PairType>,
+    limit: Option<u32>,
+) -> StdResult<PairsResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some([
+            start_after[0].to_raw(deps.api)?,
+            start_after[1].to_raw(deps.api)?,
+        ])
+    } else {
+        None
+    };
+
+    let pairs = read_pairs_by_asset(
+        deps.storage,
+        deps.api,
+        &asset_info.to_raw(deps.api)?,
+        start_after,
+        start_after_pair_type,
+        limit,
+    )?;
+
+    Ok(PairsResponse { pairs })
+}
+
+pub fn query_restricted_assets(
+    deps: Deps,
+    start_after: Option<AssetRestriction>,
+    limit: Option<u32>,
+) -> StdResult<RestrictedAssetResponse> {
+    let rules = read_restriction_rules(deps.storage, start_after, limit)?;
+    let prefixes = rules
+        .iter()
+        .filter_map(|restricted| match &restricted.rule {
+            AssetRestriction::Prefix { prefix } => Some(prefix.clone()),
+            _ => None,
+        })
+        .collect();
+
+    Ok(RestrictedAssetResponse { prefixes, rules })
+}
+
+pub fn query_failed_pair_migrations(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<FailedPairMigrationsResponse> {
+    let failed = match MIGRATE_PAIRS_PROGRESS.may_load(deps.storage)? {
+        Some(progress) => {
+            read_failed_pair_migrations(deps.storage, progress.new_code_id, start_after, limit)?
+        }
+        None => vec![],
+    };
+
+    Ok(FailedPairMigrationsResponse { failed })
+}
+
+pub fn query_pair_types(deps: Deps) -> StdResult<PairTypesResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let mut pair_types = PAIR_TYPE_CONFIGS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, v)| v))
+        .collect::<StdResult<Vec<PairTypeConfig>>>()?;
+
+    // constant product pairs are always available through the legacy config
+    if !pair_types
+        .iter()
+        .any(|pair_type_config| matches!(pair_type_config.pair_type, PairType::Xyk {}))
+    {
+        if let Some(xyk_config) = read_pair_type_config(deps.storage, &config, &PairType::Xyk {})? {
+            pair_types.insert(0, xyk_config);
+        }
+    }
+
+    Ok(PairTypesResponse { pair_types })
 }
 
 fn get_creators(deps: Deps) -> StdResult<CreatorsResponse> {
@@ -586,5 +1408,36 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response>
     };
 
     CONFIG.save(deps.storage, &config)?;
+
+    // move the legacy prefix list into the restriction rules
+    if let Some(restricted_list) = RESTRICTED_ASSETS.may_load(deps.storage)? {
+        for prefix in restricted_list.assets {
+            let rule = AssetRestriction::Prefix { prefix };
+            RESTRICTION_RULES.save(
+                deps.storage,
+                &rule.key(),
+                &RestrictedAsset {
+                    rule: rule.clone(),
+                    added_by: msg.owner.clone(),
+                    exempt_creators: vec![],
+                },
+            )?;
+        }
+        RESTRICTED_ASSETS.remove(deps.storage);
+    }
+
+    // the reverse lookups of existing pairs are filled in pages by `IndexPairs`, pairs
+    // are listed from the registry until then
+    if PAIRS_BY_TYPE
+        .keys_raw(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_none()
+        && PAIRS
+            .keys_raw(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some()
+    {
+        INDEX_PAIRS_PROGRESS.save(deps.storage, &vec![])?;
+    }
     Ok(Response::default())
 }
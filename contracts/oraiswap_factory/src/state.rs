@@ -1,8 +1,12 @@
 use cosmwasm_schema::cw_serde;
 
-use cosmwasm_std::{Addr, Api, CanonicalAddr, Order, StdResult, Storage};
+use cosmwasm_std::{Addr, Api, CanonicalAddr, Empty, Order, StdError, StdResult, Storage};
 use cw_storage_plus::{Bound, Item, Map};
-use oraiswap::asset::{AssetInfoRaw, PairInfo, PairInfoRaw};
+use oraiswap::asset::{pair_key, AssetInfoRaw, PairInfo, PairInfoRaw, PairStatus, PairType};
+use oraiswap::factory::{
+    AssetRestriction, CreationFee, FailedPairMigration, MigratePairsProgress, PairTypeConfig,
+    RestrictedAsset,
+};
 
 #[cw_serde]
 pub struct Config {
@@ -29,46 +33,340 @@ pub struct Creator {
 // put the length bytes at the first for compatibility with legacy singleton store
 pub const CONFIG: Item<Config> = Item::new("\u{0}\u{6}config");
 
-// store temporary pair info while waiting for deployment
+// store temporary pair info while waiting for deployment, keyed by `pool_key` so an
+// asset pair holds one pool per pair type
 pub const PAIRS: Map<&[u8], PairInfoRaw> = Map::new("pairs");
 
+// reverse lookups of deployed pairs, by liquidity token and by (asset, pool key),
+// the asset lookup only holds active pairs
+pub const PAIRS_BY_LIQUIDITY_TOKEN: Map<&str, Vec<u8>> = Map::new("pairs_by_liquidity_token");
+pub const PAIRS_BY_ASSET: Map<(&[u8], &[u8]), Empty> = Map::new("pairs_by_asset");
+// deployed pairs by (pair type, pool key), and the active ones, so filtered listings
+// never scan past the page
+pub const PAIRS_BY_TYPE: Map<(&str, &[u8]), Empty> = Map::new("pairs_by_type");
+pub const ACTIVE_PAIRS: Map<&[u8], Empty> = Map::new("active_pairs");
+pub const ACTIVE_PAIRS_BY_TYPE: Map<(&str, &[u8]), Empty> = Map::new("active_pairs_by_type");
+// last pair key of the previous `IndexPairs` page, empty until the first page, removed
+// once the sweep is done. Listings filter PAIRS directly while it is set
+pub const INDEX_PAIRS_PROGRESS: Item<Vec<u8>> = Item::new("index_pairs_progress");
+
+// legacy prefix list, moved into RESTRICTION_RULES on migration
 pub const RESTRICTED_ASSETS: Item<RestrictedAssets> = Item::new("restricted_assets");
+// restriction rules keyed by `AssetRestriction::key`
+pub const RESTRICTION_RULES: Map<&str, RestrictedAsset> = Map::new("restriction_rules");
 pub const CREATOR: Item<Creator> = Item::new("creator");
+// fee charged to senders outside the creators list for creating a pair
+pub const CREATION_FEE: Item<CreationFee> = Item::new("creation_fee");
+// allowed to pause pairs besides the owner
+pub const GUARDIAN: Item<Addr> = Item::new("guardian");
+
+// progress of the pair migration sweep, and the pairs of the page being migrated
+pub const MIGRATE_PAIRS_PROGRESS: Item<MigratePairsProgress> = Item::new("migrate_pairs_progress");
+pub const MIGRATING_PAIRS: Item<Vec<Addr>> = Item::new("migrating_pairs");
+// errors of the failed pair migrations keyed by (new code id, pair)
+pub const FAILED_PAIR_MIGRATIONS: Map<(u64, &Addr), String> = Map::new("failed_pair_migrations");
+
+// registered pair types, keyed by the pair type name
+pub const PAIR_TYPE_CONFIGS: Map<&str, PairTypeConfig> = Map::new("pair_type_configs");
+
+/// Constant product pairs fall back to the legacy code id and fees in `Config` until registered
+pub fn read_pair_type_config(
+    storage: &dyn Storage,
+    config: &Config,
+    pair_type: &PairType,
+) -> StdResult<Option<PairTypeConfig>> {
+    if let Some(pair_type_config) = PAIR_TYPE_CONFIGS.may_load(storage, &pair_type.to_string())? {
+        return Ok(Some(pair_type_config));
+    }
+
+    Ok(match pair_type {
+        PairType::Xyk {} => Some(PairTypeConfig {
+            pair_type: PairType::Xyk {},
+            code_id: config.pair_code_id,
+            commission_rate: config.commission_rate.clone(),
+            operator_fee: config.operator_fee.clone(),
+            is_disabled: false,
+        }),
+        _ => None,
+    })
+}
 
 // settings for pagination
-const MAX_LIMIT: u32 = 30;
-const DEFAULT_LIMIT: u32 = 10;
-pub fn read_pairs(
+pub const MAX_LIMIT: u32 = 30;
+pub const DEFAULT_LIMIT: u32 = 10;
+
+/// Registry key of a pool, the pair key of its assets followed by its pair type.
+/// Constant product pools keep the bare pair key of the legacy registry
+pub fn pool_key(asset_infos: &[AssetInfoRaw; 2], pair_type: &PairType) -> Vec<u8> {
+    let mut key = pair_key(asset_infos);
+    if !matches!(pair_type, PairType::Xyk {}) {
+        key.push(0);
+        key.extend_from_slice(pair_type.to_string().as_bytes());
+    }
+    key
+}
+
+/// Pools registered for the assets in key order, constant product first
+pub fn read_asset_pools(
     storage: &dyn Storage,
+    asset_infos: &[AssetInfoRaw; 2],
+) -> StdResult<Vec<PairInfoRaw>> {
+    let key = pair_key(asset_infos);
+    PAIRS
+        .range(
+            storage,
+            Some(Bound::InclusiveRaw(key.clone())),
+            calc_range_start(Some(asset_infos.clone()), None).map(Bound::ExclusiveRaw),
+            Order::Ascending,
+        )
+        .map(|item| item.map(|(_, v)| v))
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |pair_info| pair_key(&pair_info.asset_infos) == key)
+        })
+        .collect()
+}
+
+/// The reverse lookups are complete once no `IndexPairs` sweep is pending
+pub fn pairs_indexed(storage: &dyn Storage) -> bool {
+    !INDEX_PAIRS_PROGRESS.exists(storage)
+}
+
+/// Adds a deployed pair to the reverse lookups matching its status, a removed pair is
+/// only listed by type
+pub fn index_pair(
+    storage: &mut dyn Storage,
     api: &dyn Api,
+    pair_key: &[u8],
+    pair_info: &PairInfoRaw,
+) -> StdResult<()> {
+    let pair_type = pair_info.pair_type.to_string();
+    PAIRS_BY_TYPE.save(storage, (&pair_type, pair_key), &Empty {})?;
+    if pair_info.status == PairStatus::Removed {
+        return Ok(());
+    }
+
+    let liquidity_token = pair_info
+        .to_normal(api)?
+        .liquidity_token_info()?
+        .to_string();
+    PAIRS_BY_LIQUIDITY_TOKEN.save(storage, &liquidity_token, &pair_key.to_vec())?;
+    if pair_info.status == PairStatus::Active {
+        ACTIVE_PAIRS.save(storage, pair_key, &Empty {})?;
+        ACTIVE_PAIRS_BY_TYPE.save(storage, (&pair_type, pair_key), &Empty {})?;
+        for asset_info in pair_info.asset_infos.iter() {
+            PAIRS_BY_ASSET.save(storage, (asset_info.as_bytes(), pair_key), &Empty {})?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Drops a pair from every reverse lookup, before its status changes
+pub fn unindex_pair(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    pair_key: &[u8],
+    pair_info: &PairInfoRaw,
+) -> StdResult<()> {
+    let pair_type = pair_info.pair_type.to_string();
+    PAIRS_BY_TYPE.remove(storage, (&pair_type, pair_key));
+    ACTIVE_PAIRS.remove(storage, pair_key);
+    ACTIVE_PAIRS_BY_TYPE.remove(storage, (&pair_type, pair_key));
+
+    let liquidity_token = pair_info
+        .to_normal(api)?
+        .liquidity_token_info()?
+        .to_string();
+    PAIRS_BY_LIQUIDITY_TOKEN.remove(storage, &liquidity_token);
+    for asset_info in pair_info.asset_infos.iter() {
+        PAIRS_BY_ASSET.remove(storage, (asset_info.as_bytes(), pair_key));
+    }
+
+    Ok(())
+}
+
+pub fn read_pairs_by_asset(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    asset_info: &AssetInfoRaw,
     start_after: Option<[AssetInfoRaw; 2]>,
+    start_after_pair_type: Option<PairType>,
     limit: Option<u32>,
 ) -> StdResult<Vec<PairInfo>> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = calc_range_start(start_after).map(Bound::ExclusiveRaw);
+    let start = calc_range_start(start_after, start_after_pair_type).map(Bound::ExclusiveRaw);
+
+    if !pairs_indexed(storage) {
+        return PAIRS
+            .range(storage, start, None, Order::Ascending)
+            .filter(|item| {
+                item.as_ref().map_or(true, |(_, pair_info)| {
+                    pair_info.status == PairStatus::Active
+                        && pair_info.asset_infos.contains(asset_info)
+                })
+            })
+            .take(limit)
+            .map(|item| item?.1.to_normal(api))
+            .collect();
+    }
+
+    PAIRS_BY_ASSET
+        .prefix(asset_info.as_bytes())
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|key| PAIRS.load(storage, &key?)?.to_normal(api))
+        .collect()
+}
+
+pub fn read_restriction_rules(
+    storage: &dyn Storage,
+    start_after: Option<AssetRestriction>,
+    limit: Option<u32>,
+) -> StdResult<Vec<RestrictedAsset>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_key = start_after.map(|rule| rule.key());
+    let start = start_key.as_deref().map(Bound::exclusive);
+
+    RESTRICTION_RULES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, v)| v))
+        .collect()
+}
+
+pub fn read_failed_pair_migrations(
+    storage: &dyn Storage,
+    new_code_id: u64,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<FailedPairMigration>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    FAILED_PAIR_MIGRATIONS
+        .prefix(new_code_id)
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(pair, error)| FailedPairMigration { pair, error }))
+        .collect()
+}
+
+/// Registered pairs after `start_after` in key order, including pairs still being created
+pub fn read_pair_infos_raw(
+    storage: &dyn Storage,
+    start_after: Option<[AssetInfoRaw; 2]>,
+    start_after_pair_type: Option<PairType>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PairInfoRaw>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after, start_after_pair_type).map(Bound::ExclusiveRaw);
 
     PAIRS
         .range(storage, start, None, Order::Ascending)
         .take(limit)
-        .map(|item| {
-            let (_, v) = item?;
-            v.to_normal(api)
-        })
-        .collect::<StdResult<Vec<PairInfo>>>()
+        .map(|item| item.map(|(_, v)| v))
+        .collect()
 }
 
-// this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start(start_after: Option<[AssetInfoRaw; 2]>) -> Option<Vec<u8>> {
-    start_after.map(|asset_infos| {
-        let mut asset_infos = asset_infos.to_vec();
-        asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+/// Pairs after `start_after` in key order, filtered listings walk the matching lookup
+pub fn read_pairs(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    start_after: Option<[AssetInfoRaw; 2]>,
+    start_after_pair_type: Option<PairType>,
+    limit: Option<u32>,
+    pair_type: Option<PairType>,
+    include_inactive: bool,
+) -> StdResult<Vec<PairInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after, start_after_pair_type.or(pair_type.clone()))
+        .map(Bound::ExclusiveRaw);
+
+    if !pairs_indexed(storage) {
+        return PAIRS
+            .range(storage, start, None, Order::Ascending)
+            .filter(|item| {
+                item.as_ref().map_or(true, |(_, pair_info)| {
+                    (include_inactive || pair_info.status == PairStatus::Active)
+                        && pair_type
+                            .as_ref()
+                            .map_or(true, |pair_type| &pair_info.pair_type == pair_type)
+                })
+            })
+            .take(limit)
+            .map(|item| item?.1.to_normal(api))
+            .collect();
+    }
+
+    let pair_type = pair_type.map(|pair_type| pair_type.to_string());
+    let pair_keys = match (pair_type.as_deref(), include_inactive) {
+        (None, true) => {
+            return PAIRS
+                .range(storage, start, None, Order::Ascending)
+                .take(limit)
+                .map(|item| {
+                    let (_, v) = item?;
+                    v.to_normal(api)
+                })
+                .collect::<StdResult<Vec<PairInfo>>>()
+        }
+        (None, false) => ACTIVE_PAIRS.keys(storage, start, None, Order::Ascending),
+        (Some(pair_type), true) => {
+            PAIRS_BY_TYPE
+                .prefix(pair_type)
+                .keys(storage, start, None, Order::Ascending)
+        }
+        (Some(pair_type), false) => {
+            ACTIVE_PAIRS_BY_TYPE
+                .prefix(pair_type)
+                .keys(storage, start, None, Order::Ascending)
+        }
+    };
 
-        let mut v = [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()]
-            .concat()
-            .as_slice()
-            .to_vec();
-        v.push(1);
-        v
+    pair_keys
+        .take(limit)
+        .map(|key| PAIRS.load(storage, &key?)?.to_normal(api))
+        .collect()
+}
+
+/// Registered pair of a liquidity token
+pub fn read_pair_by_liquidity_token(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    liquidity_token: &str,
+) -> StdResult<PairInfo> {
+    if pairs_indexed(storage) {
+        let pair_key = PAIRS_BY_LIQUIDITY_TOKEN.load(storage, liquidity_token)?;
+        return PAIRS.load(storage, &pair_key)?.to_normal(api);
+    }
+
+    for item in PAIRS.range(storage, None, None, Order::Ascending) {
+        let (_, pair_info) = item?;
+        if pair_info.status == PairStatus::Removed || pair_info.contract_addr.is_empty() {
+            continue;
+        }
+        let pair_info = pair_info.to_normal(api)?;
+        if pair_info.liquidity_token_info()?.to_string() == liquidity_token {
+            return Ok(pair_info);
+        }
+    }
+    Err(StdError::not_found("PairInfo"))
+}
+
+// this will set the first key after the provided pool, or after every pool of the
+// provided assets without a pair type by appending a 1 byte to their pair key
+fn calc_range_start(
+    start_after: Option<[AssetInfoRaw; 2]>,
+    pair_type: Option<PairType>,
+) -> Option<Vec<u8>> {
+    start_after.map(|asset_infos| match pair_type {
+        Some(pair_type) => pool_key(&asset_infos, &pair_type),
+        None => {
+            let mut v = pair_key(&asset_infos);
+            v.push(1);
+            v
+        }
     })
 }
 
@@ -81,7 +379,7 @@ mod test {
     use cosmwasm_storage::{
         bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket,
     };
-    use oraiswap::asset::pair_key;
+    use oraiswap::asset::{pair_key, PairStatus, PairType};
     use oraiswap::pair::{DEFAULT_COMMISSION_RATE, DEFAULT_OPERATOR_FEE};
     const KEY_CONFIG: &[u8] = b"config";
 
@@ -145,7 +443,7 @@ mod test {
     ) -> StdResult<Vec<PairInfo>> {
         let pair_bucket: ReadonlyBucket<PairInfoRaw> = bucket_read(storage, PREFIX_PAIRS);
         let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-        let start = calc_range_start(start_after);
+        let start = calc_range_start(start_after, None);
         pair_bucket
             .range(start.as_deref(), None, Order::Ascending)
             .take(limit)
@@ -171,8 +469,12 @@ mod test {
             ],
             contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
             liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+            liquidity_denom: None,
             commission_rate: DEFAULT_COMMISSION_RATE.to_string(),
             operator_fee: DEFAULT_OPERATOR_FEE.to_string(),
+            pair_type: PairType::Xyk {},
+            status: PairStatus::Active,
+            successor: None,
         };
 
         let pair_info2 = PairInfoRaw {
@@ -187,12 +489,25 @@ mod test {
             ],
             contract_addr: deps.api.addr_canonicalize("pair0001").unwrap(),
             liquidity_token: deps.api.addr_canonicalize("liquidity0001").unwrap(),
+            liquidity_denom: None,
             commission_rate: DEFAULT_COMMISSION_RATE.to_string(),
             operator_fee: DEFAULT_OPERATOR_FEE.to_string(),
+            pair_type: PairType::Xyk {},
+            status: PairStatus::Active,
+            successor: None,
         };
 
         store_pair(&mut deps.storage, &pair_info).unwrap();
         store_pair(&mut deps.storage, &pair_info2).unwrap();
+        for pair_info in [&pair_info, &pair_info2] {
+            index_pair(
+                &mut deps.storage,
+                &deps.api,
+                &pair_key(&pair_info.asset_infos),
+                pair_info,
+            )
+            .unwrap();
+        }
 
         assert_eq!(
             PAIRS
@@ -209,7 +524,7 @@ mod test {
         );
 
         assert_eq!(
-            read_pairs(&deps.storage, &deps.api, None, None),
+            read_pairs(&deps.storage, &deps.api, None, None, None, None, false),
             legacy_read_pairs(&deps.storage, &deps.api, None, None),
         );
     }
@@ -1,14 +1,29 @@
 use std::str::FromStr;
 
-use cosmwasm_std::{coin, to_json_binary, Addr, Coin};
-use oraiswap::asset::{AssetInfo, PairInfo};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{
+    attr, coin, from_json, to_json_binary, Addr, Api, Coin, CosmosMsg, Deps, Order, Reply,
+    StdResult, Storage, SubMsgResult, Uint128, WasmMsg,
+};
+use oraiswap::asset::{
+    pair_key, Asset, AssetInfo, AssetInfoRaw, PairInfo, PairInfoRaw, PairStatus, PairType,
+};
 
 use oraiswap::create_entry_points_testing;
-use oraiswap::factory::ConfigResponse;
-use oraiswap::pair::{PairResponse, DEFAULT_COMMISSION_RATE, DEFAULT_OPERATOR_FEE};
+use oraiswap::factory::{
+    AssetRestriction, ConfigResponse, CreationFee, FailedPairMigrationsResponse, InstantiateMsg,
+    MigrateMsg, MigratePairsProgress, PairTypeConfig, PairTypesResponse, PairsResponse, QueryMsg,
+    RestrictedAsset, RestrictedAssetResponse,
+};
+use oraiswap::pair::{
+    PairResponse, StableSwapParams, StatusResponse, DEFAULT_COMMISSION_RATE, DEFAULT_OPERATOR_FEE,
+};
 use oraiswap::querier::query_pair_info_from_pair;
 use oraiswap::testing::{MockApp, APP_OWNER};
 
+use crate::contract::{execute, instantiate, migrate, query, reply, MIGRATE_PAIR_REPLY_ID};
+use crate::state::{INDEX_PAIRS_PROGRESS, MAX_LIMIT, PAIRS, PAIRS_BY_LIQUIDITY_TOKEN};
+
 #[test]
 fn create_pair() {
     let mut app = MockApp::new(&[]);
@@ -76,10 +91,14 @@ fn create_pair() {
         PairInfo {
             oracle_addr: app.oracle_addr,
             liquidity_token: pair_info.liquidity_token,
+            liquidity_denom: None,
             contract_addr,
             asset_infos,
             commission_rate: DEFAULT_COMMISSION_RATE.into(),
-            operator_fee: DEFAULT_OPERATOR_FEE.to_string()
+            operator_fee: DEFAULT_OPERATOR_FEE.to_string(),
+            pair_type: PairType::Xyk {},
+            status: PairStatus::Active,
+            successor: None,
         }
     );
 }
@@ -167,10 +186,14 @@ fn create_pair_restricted() {
         PairInfo {
             oracle_addr: app.oracle_addr,
             liquidity_token: pair_info.liquidity_token,
+            liquidity_denom: None,
             contract_addr,
             asset_infos,
             commission_rate: DEFAULT_COMMISSION_RATE.into(),
-            operator_fee: DEFAULT_OPERATOR_FEE.to_string()
+            operator_fee: DEFAULT_OPERATOR_FEE.to_string(),
+            pair_type: PairType::Xyk {},
+            status: PairStatus::Active,
+            successor: None,
         }
     );
 }
@@ -203,11 +226,15 @@ fn add_pair() {
 
     let pair_info = PairInfo {
         oracle_addr: app.oracle_addr.clone(),
-        liquidity_token: Addr::unchecked("liquidity_token"),
+        liquidity_token: Some(Addr::unchecked("liquidity_token")),
+        liquidity_denom: None,
         contract_addr: Addr::unchecked("contract_addr"),
         asset_infos: asset_infos.clone(),
         commission_rate: DEFAULT_COMMISSION_RATE.into(),
         operator_fee: DEFAULT_OPERATOR_FEE.to_string(),
+        pair_type: PairType::Xyk {},
+        status: PairStatus::Active,
+        successor: None,
     };
 
     // add pair
@@ -216,3 +243,1382 @@ fn add_pair() {
     let pair_res = app.query_pair(asset_infos.clone()).unwrap();
     assert_eq!(pair_res, pair_info);
 }
+
+#[test]
+fn create_pair_with_pair_type() {
+    let mut app = MockApp::new(&[]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply)),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair)
+                .with_reply_empty(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let factory_config: ConfigResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Config {},
+        )
+        .unwrap();
+
+    let stable_config = PairTypeConfig {
+        pair_type: PairType::Stable {},
+        code_id: factory_config.pair_code_id,
+        commission_rate: "0.0005".to_string(),
+        operator_fee: "0.0001".to_string(),
+        is_disabled: false,
+    };
+
+    let contract_addr1 = app.create_token("assetE");
+    let contract_addr2 = app.create_token("assetF");
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: contract_addr1,
+        },
+        AssetInfo::Token {
+            contract_addr: contract_addr2,
+        },
+    ];
+    let create_msg = oraiswap::factory::ExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        pair_admin: Some("admin".to_string()),
+        operator: None,
+        provide_liquidity: None,
+        pair_type: Some(PairType::Stable {}),
+        stable_swap: Some(StableSwapParams {
+            amp: 100,
+            decimals: None,
+        }),
+        native_lp: None,
+    };
+
+    // stable type is not registered yet
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &create_msg,
+        &[],
+    )
+    .unwrap_err();
+
+    // only owner can register pair types
+    let register_msg = oraiswap::factory::ExecuteMsg::UpdatePairTypeConfig {
+        config: stable_config.clone(),
+    };
+    app.execute(
+        Addr::unchecked("addr0000"),
+        app.factory_addr.clone(),
+        &register_msg,
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &register_msg,
+        &[],
+    )
+    .unwrap();
+
+    let pair_types: PairTypesResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::PairTypes {},
+        )
+        .unwrap();
+    assert_eq!(pair_types.pair_types.len(), 2);
+    assert_eq!(pair_types.pair_types[0].pair_type, PairType::Xyk {});
+    assert_eq!(pair_types.pair_types[1], stable_config);
+
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &create_msg,
+        &[],
+    )
+    .unwrap();
+
+    // pair uses the registered fees
+    let pair_res = app.query_pair(asset_infos.clone()).unwrap();
+    assert_eq!(pair_res.pair_type, PairType::Stable {});
+    assert_eq!(pair_res.commission_rate, "0.0005".to_string());
+    assert_eq!(pair_res.operator_fee, "0.0001".to_string());
+
+    let pair_info =
+        query_pair_info_from_pair(&app.as_querier().into_empty(), pair_res.contract_addr).unwrap();
+    assert_eq!(pair_info.pair_type, PairType::Stable {});
+
+    // a pool of the same assets and type can not be created again
+    let error = app
+        .execute(
+            Addr::unchecked(APP_OWNER),
+            app.factory_addr.clone(),
+            &create_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("Pair already exists"));
+
+    // but the same assets can also hold a constant product pool
+    let res: StdResult<PairInfo> = app.query(
+        app.factory_addr.clone(),
+        &oraiswap::factory::QueryMsg::Pair {
+            asset_infos: asset_infos.clone(),
+            pair_type: Some(PairType::Xyk {}),
+        },
+    );
+    assert!(res.is_err());
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::CreatePair {
+            asset_infos: asset_infos.clone(),
+            pair_admin: None,
+            operator: None,
+            provide_liquidity: None,
+            pair_type: None,
+            stable_swap: None,
+            native_lp: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let xyk_pair: PairInfo = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: Some(PairType::Xyk {}),
+            },
+        )
+        .unwrap();
+    assert_eq!(xyk_pair.pair_type, PairType::Xyk {});
+    assert_ne!(xyk_pair.contract_addr, pair_res.contract_addr);
+    assert_ne!(xyk_pair.liquidity_token, pair_res.liquidity_token);
+
+    let stable_pair: PairInfo = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: Some(PairType::Stable {}),
+            },
+        )
+        .unwrap();
+    assert_eq!(stable_pair, pair_res);
+
+    // without a pair type the constant product pool comes first
+    assert_eq!(app.query_pair(asset_infos.clone()).unwrap(), xyk_pair);
+
+    // filter by pair type
+    let pairs: PairsResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Pairs {
+                start_after: None,
+                start_after_pair_type: None,
+                limit: None,
+                pair_type: Some(PairType::Stable {}),
+                include_inactive: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(pairs.pairs, vec![pair_res.clone()]);
+
+    // both pools are listed, paging through them by pair type
+    let pairs: PairsResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Pairs {
+                start_after: None,
+                start_after_pair_type: None,
+                limit: Some(1),
+                pair_type: None,
+                include_inactive: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(pairs.pairs, vec![xyk_pair.clone()]);
+    let pairs: PairsResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Pairs {
+                start_after: Some(asset_infos.clone()),
+                start_after_pair_type: Some(PairType::Xyk {}),
+                limit: None,
+                pair_type: None,
+                include_inactive: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(pairs.pairs, vec![pair_res.clone()]);
+    let pairs: PairsResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Pairs {
+                start_after: Some(asset_infos.clone()),
+                start_after_pair_type: None,
+                limit: None,
+                pair_type: None,
+                include_inactive: None,
+            },
+        )
+        .unwrap();
+    assert!(pairs.pairs.is_empty());
+
+    let pairs: PairsResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::PairsByAsset {
+                asset_info: asset_infos[0].clone(),
+                start_after: None,
+                start_after_pair_type: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(pairs.pairs, vec![xyk_pair, pair_res]);
+
+    // disabled pair types cannot be used anymore
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::UpdatePairTypeConfig {
+            config: PairTypeConfig {
+                is_disabled: true,
+                ..stable_config
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let contract_addr3 = app.create_token("assetG");
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::CreatePair {
+            asset_infos: [
+                asset_infos[0].clone(),
+                AssetInfo::Token {
+                    contract_addr: contract_addr3,
+                },
+            ],
+            pair_admin: None,
+            operator: None,
+            provide_liquidity: None,
+            pair_type: Some(PairType::Stable {}),
+            stable_swap: Some(StableSwapParams {
+                amp: 100,
+                decimals: None,
+            }),
+            native_lp: None,
+        },
+        &[],
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn pause_pair_by_guardian() {
+    let mut app = MockApp::new(&[]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply)),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair)
+                .with_reply_empty(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let contract_addr1 = app.create_token("assetH");
+    let contract_addr2 = app.create_token("assetI");
+    app.mint_token(contract_addr1.clone(), 1000000u128).unwrap();
+    app.mint_token(contract_addr2.clone(), 1000000u128).unwrap();
+    app.increase_allowance(contract_addr1.clone(), 1000000u128)
+        .unwrap();
+    app.increase_allowance(contract_addr2.clone(), 1000000u128)
+        .unwrap();
+
+    let pair_addr = app
+        .create_pair_add_add_liquidity([
+            AssetInfo::Token {
+                contract_addr: contract_addr1,
+            },
+            AssetInfo::Token {
+                contract_addr: contract_addr2,
+            },
+        ])
+        .unwrap();
+
+    let pause_msg = oraiswap::factory::ExecuteMsg::PausePair {
+        pair_addr: pair_addr.to_string(),
+        swap: Some(true),
+        provide: None,
+        withdraw: None,
+    };
+
+    // only the owner can set the guardian
+    app.execute(
+        Addr::unchecked("addr0000"),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::UpdateGuardian {
+            guardian: Some("guardian".to_string()),
+        },
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::UpdateGuardian {
+            guardian: Some("guardian".to_string()),
+        },
+        &[],
+    )
+    .unwrap();
+    let guardian: Option<Addr> = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Guardian {},
+        )
+        .unwrap();
+    assert_eq!(guardian, Some(Addr::unchecked("guardian")));
+
+    app.execute(
+        Addr::unchecked("addr0000"),
+        app.factory_addr.clone(),
+        &pause_msg,
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked("guardian"),
+        app.factory_addr.clone(),
+        &pause_msg,
+        &[],
+    )
+    .unwrap();
+
+    let status: StatusResponse = app
+        .query(pair_addr, &oraiswap::pair::QueryMsg::Status {})
+        .unwrap();
+    assert!(status.swap_paused);
+    assert!(!status.provide_paused);
+    assert!(!status.withdraw_paused);
+    assert_eq!(status.factory, Some(app.factory_addr.clone()));
+}
+
+#[test]
+fn migrate_pairs_in_pages() {
+    let mut app = MockApp::new(&[]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply)),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair)
+                .with_migrate_empty(oraiswap_pair::contract::migrate)
+                .with_reply_empty(oraiswap_pair::contract::reply),
+        ),
+    );
+    let config: ConfigResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Config {},
+        )
+        .unwrap();
+
+    // the factory can only migrate the pairs it administers
+    let mut pair_addrs = vec![];
+    for (denom, pair_admin) in [("uatom", None), ("uusdt", Some("admin")), ("uosmo", None)] {
+        let asset_infos = [
+            AssetInfo::NativeToken {
+                denom: "orai".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: denom.to_string(),
+            },
+        ];
+        app.execute(
+            Addr::unchecked(APP_OWNER),
+            app.factory_addr.clone(),
+            &oraiswap::factory::ExecuteMsg::CreatePair {
+                asset_infos: asset_infos.clone(),
+                pair_admin: pair_admin.map(|admin| admin.to_string()),
+                operator: None,
+                provide_liquidity: None,
+                pair_type: None,
+                stable_swap: None,
+                native_lp: None,
+            },
+            &[],
+        )
+        .unwrap();
+        pair_addrs.push(app.query_pair(asset_infos).unwrap().contract_addr);
+    }
+
+    let migrate_msg = oraiswap::factory::ExecuteMsg::MigratePairs {
+        new_code_id: config.pair_code_id,
+        msg: to_json_binary(&oraiswap::pair::MigrateMsg {
+            admin: None,
+            asset_infos: None,
+            factory: None,
+        })
+        .unwrap(),
+        start_after: None,
+        start_after_pair_type: None,
+        limit: Some(2),
+    };
+    app.execute(
+        Addr::unchecked("addr0000"),
+        app.factory_addr.clone(),
+        &migrate_msg,
+        &[],
+    )
+    .unwrap_err();
+
+    let progress = |app: &MockApp| -> Option<MigratePairsProgress> {
+        app.query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::MigratePairsProgress {},
+        )
+        .unwrap()
+    };
+
+    // the second page resumes after the first one
+    let mut events = app
+        .execute(
+            Addr::unchecked(APP_OWNER),
+            app.factory_addr.clone(),
+            &migrate_msg,
+            &[],
+        )
+        .unwrap()
+        .events;
+    assert!(!progress(&app).unwrap().done);
+    events.extend(
+        app.execute(
+            Addr::unchecked(APP_OWNER),
+            app.factory_addr.clone(),
+            &migrate_msg,
+            &[],
+        )
+        .unwrap()
+        .events,
+    );
+
+    let progress = progress(&app).unwrap();
+    assert!(progress.done);
+    assert_eq!(progress.migrated, 2);
+    assert_eq!(progress.failed, 1);
+
+    // only the pairs that migrated are reported as migrated
+    let mut migrated: Vec<String> = events
+        .iter()
+        .filter(|event| event.ty == "wasm-migrate_pair")
+        .flat_map(|event| event.attributes.iter())
+        .filter(|attr| attr.key == "pair")
+        .map(|attr| attr.value.clone())
+        .collect();
+    migrated.sort();
+    let mut expected = vec![pair_addrs[0].to_string(), pair_addrs[2].to_string()];
+    expected.sort();
+    assert_eq!(migrated, expected);
+
+    let res: FailedPairMigrationsResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::FailedPairMigrations {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.failed.len(), 1);
+    assert_eq!(res.failed[0].pair, pair_addrs[1]);
+}
+
+#[test]
+fn query_pairs_by_index() {
+    let mut app = MockApp::new(&[]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply)),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair)
+                .with_reply_empty(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let orai = AssetInfo::NativeToken {
+        denom: "orai".to_string(),
+    };
+    let mut pairs = vec![];
+    for (base, quote) in [("orai", "uatom"), ("uatom", "uusdt"), ("orai", "uusdt")] {
+        let asset_infos = [
+            AssetInfo::NativeToken {
+                denom: base.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: quote.to_string(),
+            },
+        ];
+        app.execute(
+            Addr::unchecked(APP_OWNER),
+            app.factory_addr.clone(),
+            &oraiswap::factory::ExecuteMsg::CreatePair {
+                asset_infos: asset_infos.clone(),
+                pair_admin: None,
+                operator: None,
+                provide_liquidity: None,
+                pair_type: None,
+                stable_swap: None,
+                native_lp: None,
+            },
+            &[],
+        )
+        .unwrap();
+        pairs.push(app.query_pair(asset_infos).unwrap());
+    }
+
+    for pair in pairs.iter() {
+        let res: PairInfo = app
+            .query(
+                app.factory_addr.clone(),
+                &oraiswap::factory::QueryMsg::PairByLiquidityToken {
+                    liquidity_token: pair.liquidity_token_info().unwrap().to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(&res, pair);
+    }
+
+    let res: PairsResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::PairsByAsset {
+                asset_info: orai.clone(),
+                start_after: None,
+                start_after_pair_type: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.pairs.len(), 1);
+
+    let next: PairsResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::PairsByAsset {
+                asset_info: orai,
+                start_after: Some(res.pairs[0].asset_infos.clone()),
+                start_after_pair_type: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(next.pairs.len(), 1);
+    assert_ne!(next.pairs[0], res.pairs[0]);
+    assert!(next.pairs.iter().chain(res.pairs.iter()).all(|pair| pair
+        .asset_infos
+        .iter()
+        .any(|asset_info| asset_info.to_string() == "orai")));
+}
+
+#[test]
+fn index_pairs_in_pages() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        InstantiateMsg {
+            pair_code_id: 1,
+            token_code_id: 1,
+            oracle_addr: Addr::unchecked("oracle"),
+            commission_rate: None,
+            operator_fee: None,
+            operator: Some("operator".to_string()),
+        },
+    )
+    .unwrap();
+
+    // pairs registered before the reverse lookups existed
+    for denom in ["uatom", "uosmo", "uusdt"] {
+        let asset_infos = [
+            AssetInfoRaw::NativeToken {
+                denom: "orai".to_string(),
+            },
+            AssetInfoRaw::NativeToken {
+                denom: denom.to_string(),
+            },
+        ];
+        PAIRS
+            .save(
+                &mut deps.storage,
+                &pair_key(&asset_infos),
+                &PairInfoRaw {
+                    oracle_addr: deps.api.addr_canonicalize("oracle").unwrap(),
+                    asset_infos,
+                    contract_addr: deps
+                        .api
+                        .addr_canonicalize(&format!("pair_{denom}"))
+                        .unwrap(),
+                    liquidity_token: deps.api.addr_canonicalize(&format!("lp_{denom}")).unwrap(),
+                    liquidity_denom: None,
+                    commission_rate: DEFAULT_COMMISSION_RATE.to_string(),
+                    operator_fee: DEFAULT_OPERATOR_FEE.to_string(),
+                    pair_type: PairType::Xyk {},
+                    status: PairStatus::Active,
+                    successor: None,
+                },
+            )
+            .unwrap();
+    }
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: Addr::unchecked("owner"),
+            pair_code_id: 1,
+            token_code_id: 1,
+            oracle_addr: Addr::unchecked("oracle"),
+            commission_rate: None,
+            operator_fee: None,
+            operator: "operator".to_string(),
+        },
+    )
+    .unwrap();
+
+    // pairs are listed from the registry until the lookups are filled
+    let assert_listed = |deps: Deps| {
+        let res: PairsResponse = from_json(
+            query(
+                deps,
+                mock_env(),
+                QueryMsg::Pairs {
+                    start_after: None,
+                    start_after_pair_type: None,
+                    limit: None,
+                    pair_type: None,
+                    include_inactive: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.pairs.len(), 3);
+        let res: PairsResponse = from_json(
+            query(
+                deps,
+                mock_env(),
+                QueryMsg::PairsByAsset {
+                    asset_info: AssetInfo::NativeToken {
+                        denom: "uatom".to_string(),
+                    },
+                    start_after: None,
+                    start_after_pair_type: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.pairs.len(), 1);
+        let res: PairInfo = from_json(
+            query(
+                deps,
+                mock_env(),
+                QueryMsg::PairByLiquidityToken {
+                    liquidity_token: "lp_uosmo".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.contract_addr, Addr::unchecked("pair_uosmo"));
+    };
+    assert_listed(deps.as_ref());
+
+    let index_msg = oraiswap::factory::ExecuteMsg::IndexPairs { limit: Some(2) };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        index_msg.clone(),
+    )
+    .unwrap_err();
+
+    let indexed = |storage: &dyn Storage| {
+        PAIRS_BY_LIQUIDITY_TOKEN
+            .keys(storage, None, None, Order::Ascending)
+            .count()
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        index_msg.clone(),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("done", "false")));
+    assert_eq!(indexed(deps.as_ref().storage), 2);
+    assert_listed(deps.as_ref());
+
+    // the next page resumes after the last indexed pair
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        index_msg,
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("done", "true")));
+    assert_eq!(indexed(deps.as_ref().storage), 3);
+    assert_eq!(
+        INDEX_PAIRS_PROGRESS
+            .may_load(deps.as_ref().storage)
+            .unwrap(),
+        None
+    );
+    let pair_key: Vec<u8> = PAIRS_BY_LIQUIDITY_TOKEN
+        .load(deps.as_ref().storage, "lp_uosmo")
+        .unwrap();
+    assert_eq!(
+        PAIRS
+            .load(deps.as_ref().storage, &pair_key)
+            .unwrap()
+            .contract_addr,
+        deps.api.addr_canonicalize("pair_uosmo").unwrap()
+    );
+    assert_listed(deps.as_ref());
+}
+
+#[test]
+fn deprecate_and_remove_pair() {
+    let mut app = MockApp::new(&[]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply)),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair)
+                .with_reply_empty(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let [old_infos, new_infos] = ["uatom", "uusdt"].map(|denom| {
+        [
+            AssetInfo::NativeToken {
+                denom: "orai".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: denom.to_string(),
+            },
+        ]
+    });
+    let create_pair = |app: &mut MockApp, asset_infos: &[AssetInfo; 2]| {
+        app.execute(
+            Addr::unchecked(APP_OWNER),
+            app.factory_addr.clone(),
+            &oraiswap::factory::ExecuteMsg::CreatePair {
+                asset_infos: asset_infos.clone(),
+                pair_admin: None,
+                operator: None,
+                provide_liquidity: None,
+                pair_type: None,
+                stable_swap: None,
+                native_lp: None,
+            },
+            &[],
+        )
+    };
+    create_pair(&mut app, &old_infos).unwrap();
+    create_pair(&mut app, &new_infos).unwrap();
+    let new_pair = app.query_pair(new_infos.clone()).unwrap();
+
+    let deprecate_msg = oraiswap::factory::ExecuteMsg::DeprecatePair {
+        asset_infos: old_infos.clone(),
+        pair_type: None,
+        successor: Some(new_infos.clone()),
+        successor_pair_type: None,
+    };
+    app.execute(
+        Addr::unchecked("addr0000"),
+        app.factory_addr.clone(),
+        &deprecate_msg,
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &deprecate_msg,
+        &[],
+    )
+    .unwrap();
+
+    let old_pair = app.query_pair(old_infos.clone()).unwrap();
+    assert_eq!(old_pair.status, PairStatus::Deprecated);
+    assert_eq!(old_pair.successor, Some(new_pair.contract_addr.clone()));
+
+    // the pair mirrors its registry status, which only the factory can set
+    let pair_info = query_pair_info_from_pair(
+        &app.as_querier().into_empty(),
+        old_pair.contract_addr.clone(),
+    )
+    .unwrap();
+    assert_eq!(pair_info.status, PairStatus::Deprecated);
+    assert_eq!(pair_info.successor, Some(new_pair.contract_addr.clone()));
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        old_pair.contract_addr.clone(),
+        &oraiswap::pair::ExecuteMsg::UpdatePairStatus {
+            status: PairStatus::Active,
+            successor: None,
+        },
+        &[],
+    )
+    .unwrap_err();
+
+    // deprecated pairs are only listed on request
+    let query_pairs = |app: &MockApp, include_inactive: Option<bool>| -> Vec<PairInfo> {
+        let res: PairsResponse = app
+            .query(
+                app.factory_addr.clone(),
+                &oraiswap::factory::QueryMsg::Pairs {
+                    start_after: None,
+                    start_after_pair_type: None,
+                    limit: None,
+                    pair_type: None,
+                    include_inactive,
+                },
+            )
+            .unwrap();
+        res.pairs
+    };
+    assert_eq!(query_pairs(&app, None), vec![new_pair.clone()]);
+    assert_eq!(query_pairs(&app, Some(true)).len(), 2);
+
+    // the per type and per asset lookups follow the status
+    let res: PairsResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Pairs {
+                start_after: None,
+                start_after_pair_type: None,
+                limit: None,
+                pair_type: Some(PairType::Xyk {}),
+                include_inactive: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.pairs, vec![new_pair.clone()]);
+    let res: PairsResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Pairs {
+                start_after: None,
+                start_after_pair_type: None,
+                limit: None,
+                pair_type: Some(PairType::Xyk {}),
+                include_inactive: Some(true),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.pairs.len(), 2);
+    let res: PairsResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::PairsByAsset {
+                asset_info: old_infos[1].clone(),
+                start_after: None,
+                start_after_pair_type: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(res.pairs.is_empty());
+
+    // a deprecated pair can not be a successor
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::DeprecatePair {
+            asset_infos: new_infos.clone(),
+            pair_type: None,
+            successor: Some(old_infos.clone()),
+            successor_pair_type: None,
+        },
+        &[],
+    )
+    .unwrap_err();
+
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::RemovePair {
+            asset_infos: old_infos.clone(),
+            pair_type: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(app.query_pair(old_infos.clone()).is_err());
+    let pair_info = query_pair_info_from_pair(
+        &app.as_querier().into_empty(),
+        old_pair.contract_addr.clone(),
+    )
+    .unwrap();
+    assert_eq!(pair_info.status, PairStatus::Removed);
+    assert_eq!(pair_info.successor, None);
+    let pairs = query_pairs(&app, Some(true));
+    assert_eq!(pairs.len(), 2);
+    assert!(pairs
+        .iter()
+        .any(|pair| pair.contract_addr == old_pair.contract_addr
+            && pair.status == PairStatus::Removed));
+    let res: StdResult<PairInfo> = app.query(
+        app.factory_addr.clone(),
+        &oraiswap::factory::QueryMsg::PairByLiquidityToken {
+            liquidity_token: old_pair.liquidity_token_info().unwrap().to_string(),
+        },
+    );
+    assert!(res.is_err());
+
+    // removed pairs can be created again
+    create_pair(&mut app, &old_infos).unwrap();
+    let pair = app.query_pair(old_infos.clone()).unwrap();
+    assert_eq!(pair.status, PairStatus::Active);
+    assert_ne!(pair.contract_addr, old_pair.contract_addr);
+
+    // a pair can not succeed itself
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::DeprecatePair {
+            asset_infos: old_infos.clone(),
+            pair_type: None,
+            successor: Some(old_infos.clone()),
+            successor_pair_type: None,
+        },
+        &[],
+    )
+    .unwrap_err();
+
+    // the successor can be a pool of another type for the same assets
+    let factory_config: ConfigResponse = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Config {},
+        )
+        .unwrap();
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::UpdatePairTypeConfig {
+            config: PairTypeConfig {
+                pair_type: PairType::Stable {},
+                code_id: factory_config.pair_code_id,
+                commission_rate: "0.0005".to_string(),
+                operator_fee: "0.0001".to_string(),
+                is_disabled: false,
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::CreatePair {
+            asset_infos: old_infos.clone(),
+            pair_admin: None,
+            operator: None,
+            provide_liquidity: None,
+            pair_type: Some(PairType::Stable {}),
+            stable_swap: Some(StableSwapParams {
+                amp: 100,
+                decimals: None,
+            }),
+            native_lp: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::DeprecatePair {
+            asset_infos: old_infos.clone(),
+            pair_type: None,
+            successor: Some(old_infos.clone()),
+            successor_pair_type: Some(PairType::Stable {}),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let stable_pair: PairInfo = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Pair {
+                asset_infos: old_infos.clone(),
+                pair_type: Some(PairType::Stable {}),
+            },
+        )
+        .unwrap();
+    let xyk_pair: PairInfo = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::Pair {
+                asset_infos: old_infos.clone(),
+                pair_type: Some(PairType::Xyk {}),
+            },
+        )
+        .unwrap();
+    assert_eq!(xyk_pair.contract_addr, pair.contract_addr);
+    assert_eq!(xyk_pair.status, PairStatus::Deprecated);
+    assert_eq!(xyk_pair.successor, Some(stable_pair.contract_addr.clone()));
+
+    // the active pool is preferred when no pair type is given
+    assert_eq!(app.query_pair(old_infos).unwrap(), stable_pair);
+}
+
+#[test]
+fn create_pair_with_creation_fee() {
+    let init_balance: &[(&str, &[Coin])] = &[(
+        "addr0000",
+        &[coin(1000u128, "orai"), coin(1000u128, "uusdt")],
+    )];
+    let mut app = MockApp::new(init_balance);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply)),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair)
+                .with_reply_empty(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let creation_fee = CreationFee {
+        fee: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "orai".to_string(),
+            },
+            amount: Uint128::from(100u128),
+        },
+        collector: Addr::unchecked("collector"),
+    };
+    let update_msg = oraiswap::factory::ExecuteMsg::UpdateCreationFee {
+        creation_fee: Some(creation_fee.clone()),
+    };
+    app.execute(
+        Addr::unchecked("addr0000"),
+        app.factory_addr.clone(),
+        &update_msg,
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &update_msg,
+        &[],
+    )
+    .unwrap();
+    let res: Option<CreationFee> = app
+        .query(
+            app.factory_addr.clone(),
+            &oraiswap::factory::QueryMsg::CreationFee {},
+        )
+        .unwrap();
+    assert_eq!(res, Some(creation_fee));
+
+    let create_pair = |denom: &str| oraiswap::factory::ExecuteMsg::CreatePair {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "orai".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: denom.to_string(),
+            },
+        ],
+        pair_admin: None,
+        operator: None,
+        provide_liquidity: None,
+        pair_type: None,
+        stable_swap: None,
+        native_lp: None,
+    };
+
+    // anyone can list a pair by paying the exact fee
+    for funds in [
+        vec![coin(99u128, "orai")],
+        vec![coin(101u128, "orai")],
+        vec![coin(100u128, "uusdt")],
+        vec![coin(100u128, "orai"), coin(1u128, "uusdt")],
+    ] {
+        app.execute(
+            Addr::unchecked("addr0000"),
+            app.factory_addr.clone(),
+            &create_pair("uatom"),
+            &funds,
+        )
+        .unwrap_err();
+    }
+    app.execute(
+        Addr::unchecked("addr0000"),
+        app.factory_addr.clone(),
+        &create_pair("uatom"),
+        &[coin(100u128, "orai")],
+    )
+    .unwrap();
+    assert_eq!(
+        app.query_balance(Addr::unchecked("collector"), "orai".to_string())
+            .unwrap(),
+        Uint128::from(100u128)
+    );
+
+    // an existing pair fails before the fee is charged
+    let error = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            app.factory_addr.clone(),
+            &create_pair("uatom"),
+            &[],
+        )
+        .unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("Pair already exists"));
+
+    // the owner lists for free
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &create_pair("uusdt"),
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn restriction_rules() {
+    let mut app = MockApp::new(&[]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply)),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair)
+                .with_reply_empty(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let denom_rule = AssetRestriction::Denom {
+        denom: "uatom".to_string(),
+    };
+    let prefix_rule = AssetRestriction::Prefix {
+        prefix: "factory/orai1token".to_string(),
+    };
+    let restrict_msg = oraiswap::factory::ExecuteMsg::RestrictAssetRule {
+        rule: denom_rule.clone(),
+        exempt_creators: vec![Addr::unchecked("exempt")],
+    };
+    app.execute(
+        Addr::unchecked("user1"),
+        app.factory_addr.clone(),
+        &restrict_msg,
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &restrict_msg,
+        &[],
+    )
+    .unwrap();
+    app.restrict_asset("factory/orai1token".to_string())
+        .unwrap();
+
+    // an existing rule is never overwritten
+    for rule in [denom_rule.clone(), prefix_rule.clone()] {
+        let error = app
+            .execute(
+                Addr::unchecked(APP_OWNER),
+                app.factory_addr.clone(),
+                &oraiswap::factory::ExecuteMsg::RestrictAssetRule {
+                    rule,
+                    exempt_creators: vec![],
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(error
+            .root_cause()
+            .to_string()
+            .contains("Restriction rule existed"));
+    }
+
+    // rules are listed in key order, page by page
+    let query_rules = |app: &MockApp, start_after: Option<AssetRestriction>| {
+        let res: RestrictedAssetResponse = app
+            .query(
+                app.factory_addr.clone(),
+                &oraiswap::factory::QueryMsg::RestrictedAssets {
+                    start_after,
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        res
+    };
+    let res = query_rules(&app, None);
+    assert_eq!(
+        res.rules,
+        vec![RestrictedAsset {
+            rule: denom_rule.clone(),
+            added_by: Addr::unchecked(APP_OWNER),
+            exempt_creators: vec![Addr::unchecked("exempt")],
+        }]
+    );
+    assert!(res.prefixes.is_empty());
+    let res = query_rules(&app, Some(denom_rule.clone()));
+    assert_eq!(res.prefixes, vec!["factory/orai1token".to_string()]);
+    assert!(query_rules(&app, Some(prefix_rule.clone()))
+        .rules
+        .is_empty());
+
+    let create_pair = |app: &mut MockApp, sender: &str, denom: &str| {
+        app.create_pair_by(
+            [
+                AssetInfo::NativeToken {
+                    denom: "orai".to_string(),
+                },
+                AssetInfo::NativeToken {
+                    denom: denom.to_string(),
+                },
+            ],
+            sender.to_string(),
+        )
+    };
+
+    // only the exempted creator can pair the restricted denom
+    create_pair(&mut app, "user1", "uatom").unwrap_err();
+    create_pair(&mut app, "exempt", "uatom").unwrap();
+    create_pair(&mut app, "exempt", "factory/orai1token/token1").unwrap_err();
+    // the prefix only covers its own path
+    create_pair(&mut app, "user1", "factory/orai1token2/token1").unwrap();
+
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::UnrestrictAsset {
+            rule: prefix_rule.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    create_pair(&mut app, "user1", "factory/orai1token/token1").unwrap();
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.factory_addr.clone(),
+        &oraiswap::factory::ExecuteMsg::UnrestrictAsset { rule: prefix_rule },
+        &[],
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn reject_unknown_reply_id() {
+    let mut deps = mock_dependencies();
+    for id in [
+        0,
+        MIGRATE_PAIR_REPLY_ID - 1,
+        MIGRATE_PAIR_REPLY_ID + MAX_LIMIT as u64,
+    ] {
+        let err = reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id,
+                result: SubMsgResult::Err("failed".to_string()),
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Unknown reply id"));
+    }
+}
+
+#[test]
+fn create_pair_with_native_lp() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        InstantiateMsg {
+            pair_code_id: 1,
+            token_code_id: 1,
+            oracle_addr: Addr::unchecked("oracle"),
+            commission_rate: None,
+            operator_fee: None,
+            operator: Some("operator".to_string()),
+        },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        oraiswap::factory::ExecuteMsg::CreatePair {
+            asset_infos: [
+                AssetInfo::NativeToken {
+                    denom: "orai".to_string(),
+                },
+                AssetInfo::NativeToken {
+                    denom: "uatom".to_string(),
+                },
+            ],
+            pair_admin: None,
+            operator: None,
+            provide_liquidity: None,
+            pair_type: None,
+            stable_swap: None,
+            native_lp: Some(true),
+        },
+    )
+    .unwrap();
+
+    // the pair is asked to mint its LP share as a token factory denom
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate { msg, .. }) => {
+            let msg: oraiswap::pair::InstantiateMsg = from_json(msg).unwrap();
+            assert!(msg.native_lp);
+            assert_eq!(msg.factory, Some(mock_env().contract.address));
+        }
+        msg => panic!("unexpected message {msg:?}"),
+    }
+}
@@ -22,7 +22,7 @@ use oraiswap::mixed_router::{
     SimulateSwapOperationsResponse, SwapOperation,
 };
 use oraiswap::oracle::OracleContract;
-use oraiswap::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
+use oraiswap::pair::{assert_deadline, QueryMsg as PairQueryMsg, SimulationResponse};
 use oraiswap::querier::{query_pair_config, query_pair_info};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -64,15 +64,19 @@ pub fn execute(
             minimum_receive,
             to,
             affiliates,
-        } => execute_swap_operations(
-            deps,
-            env,
-            info.sender,
-            operations,
-            minimum_receive,
-            to,
-            affiliates.unwrap_or_default(),
-        ),
+            deadline,
+        } => {
+            assert_deadline(env.block.time, deadline)?;
+            execute_swap_operations(
+                deps,
+                env,
+                info.sender,
+                operations,
+                minimum_receive,
+                to,
+                affiliates.unwrap_or_default(),
+            )
+        }
         ExecuteMsg::ExecuteSwapOperation {
             operation,
             to,
@@ -157,7 +161,9 @@ pub fn receive_cw20(
             minimum_receive,
             to,
             affiliates,
+            deadline,
         } => {
+            assert_deadline(env.block.time, deadline)?;
             let receiver = to.and_then(|addr| deps.api.addr_validate(addr.as_str()).ok());
             execute_swap_operations(
                 deps,
@@ -255,6 +255,7 @@ fn asset_into_swap_msg(
                     belief_price: None,
                     max_spread,
                     to,
+                    deadline: None,
                 })?,
             }))
         }
@@ -268,6 +269,7 @@ fn asset_into_swap_msg(
                     belief_price: None,
                     max_spread,
                     to,
+                    deadline: None,
                 })?,
             })?,
         })),
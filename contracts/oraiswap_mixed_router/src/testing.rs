@@ -19,11 +19,11 @@ fn simulate_swap_operations_test() {
         &[
             Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(1000u128),
+                amount: Uint128::from(100000u128),
             },
             Coin {
                 denom: ATOM_DENOM.to_string(),
-                amount: Uint128::from(1000u128),
+                amount: Uint128::from(100000u128),
             },
         ],
     )]);
@@ -72,17 +72,18 @@ fn simulate_swap_operations_test() {
                 info: AssetInfo::NativeToken {
                     denom: ATOM_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
@@ -93,11 +94,11 @@ fn simulate_swap_operations_test() {
             &[
                 Coin {
                     denom: ORAI_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
                 Coin {
                     denom: ATOM_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
             ],
         )
@@ -117,7 +118,7 @@ fn simulate_swap_operations_test() {
         .unwrap();
 
     let msg = QueryMsg::SimulateSwapOperations {
-        offer_amount: Uint128::from(100u128),
+        offer_amount: Uint128::from(10000u128),
         operations: vec![SwapOperation::OraiSwap {
             offer_asset_info: AssetInfo::NativeToken {
                 denom: ORAI_DENOM.to_string(),
@@ -139,11 +140,11 @@ fn execute_swap_operations() {
         &[
             Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(1000u128),
+                amount: Uint128::from(100000u128),
             },
             Coin {
                 denom: ATOM_DENOM.to_string(),
-                amount: Uint128::from(1000u128),
+                amount: Uint128::from(100000u128),
             },
         ],
     )]);
@@ -205,17 +206,18 @@ fn execute_swap_operations() {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::Token {
                     contract_addr: asset_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     // set allowance
@@ -224,7 +226,7 @@ fn execute_swap_operations() {
         asset_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: pair_addr1.to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(10000u128),
             expires: None,
         },
         &[],
@@ -238,7 +240,7 @@ fn execute_swap_operations() {
             &msg,
             &[Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             }],
         )
         .unwrap();
@@ -249,17 +251,18 @@ fn execute_swap_operations() {
                 info: AssetInfo::NativeToken {
                     denom: ATOM_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::Token {
                     contract_addr: asset_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     // set allowance
@@ -268,7 +271,7 @@ fn execute_swap_operations() {
         asset_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: pair_addr2.to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(10000u128),
             expires: None,
         },
         &[],
@@ -282,7 +285,7 @@ fn execute_swap_operations() {
             &msg,
             &[Coin {
                 denom: ATOM_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             }],
         )
         .unwrap();
@@ -305,6 +308,7 @@ fn execute_swap_operations() {
         minimum_receive: None,
         to: None,
         affiliates: None,
+        deadline: None,
     };
 
     let error = app
@@ -315,6 +319,22 @@ fn execute_swap_operations() {
         .to_string()
         .contains("must provide operations"));
 
+    // expired messages are rejected before the operations are checked
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: vec![],
+        minimum_receive: None,
+        to: None,
+        affiliates: None,
+        deadline: Some(1),
+    };
+    let error = app
+        .execute(Addr::unchecked("addr0000"), router_addr.clone(), &msg, &[])
+        .unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("Deadline 1 has passed"));
+
     let msg = ExecuteMsg::ExecuteSwapOperations {
         operations: vec![
             SwapOperation::OraiSwap {
@@ -337,6 +357,7 @@ fn execute_swap_operations() {
         minimum_receive: None,
         to: None,
         affiliates: None,
+        deadline: None,
     };
 
     let res = app
@@ -347,11 +368,11 @@ fn execute_swap_operations() {
             &[
                 Coin {
                     denom: ORAI_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
                 Coin {
                     denom: ATOM_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
             ],
         )
@@ -409,11 +430,11 @@ fn execute_swap_operations() {
             &[
                 Coin {
                     denom: ORAI_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
                 Coin {
                     denom: ATOM_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
             ],
         )
@@ -452,11 +473,11 @@ fn execute_swap_operations() {
         &[
             Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Coin {
                 denom: ATOM_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
     )
@@ -659,6 +680,7 @@ fn simulate_mixed_swap() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
@@ -805,6 +827,7 @@ fn execute_mixed_swap_operations() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
@@ -843,6 +866,7 @@ fn execute_mixed_swap_operations() {
         minimum_receive: None,
         to: None,
         affiliates: None,
+        deadline: None,
     };
 
     let error = app
@@ -878,6 +902,7 @@ fn execute_mixed_swap_operations() {
         minimum_receive: None,
         to: None,
         affiliates: None,
+        deadline: None,
     };
 
     let mut balances_before = app.query_token_balances("addr0000").unwrap();
@@ -1020,6 +1045,7 @@ fn test_affiliates() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
@@ -1069,6 +1095,7 @@ fn test_affiliates() {
                 address: Addr::unchecked("affiliate_2"),
             },
         ]),
+        deadline: None,
     };
 
     app.execute(
@@ -1135,6 +1162,7 @@ fn test_affiliates() {
                 address: Addr::unchecked("affiliate_2"),
             },
         ]),
+        deadline: None,
     };
 
     app.execute(
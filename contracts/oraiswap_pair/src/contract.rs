@@ -1,32 +1,59 @@
 use crate::state::{
-    ADMIN, OPERATOR, PAIR_INFO, WHITELISTED, WHITELISTED_TRADERS, WHITELISTED_WITHDRAW_LPS,
+    read_whitelisted, FlashSwapState, Observation, PoolSnapshot, StableSwapConfig, ADMIN,
+    DYNAMIC_FEE, FACTORY, FLASH_FEE, FLASH_SWAP, LOCKED_SHARE, MAX_PRICE_CHANGE, MAX_PRICE_IMPACT,
+    OBSERVATIONS, OBSERVATION_CAPACITY, OBSERVATION_COUNT, OBSERVATION_PERIOD, OPERATOR, PAIR_INFO,
+    PAUSE_STATUS, POOL_SNAPSHOT, PRICE_CUMULATIVE, PROTOCOL_FEES, PROTOCOL_FEE_ACCRUAL,
+    STABLE_SWAP_CONFIG, SWAP_HOOKS, TRADER_FEE_TIERS, WHITELISTED, WHITELISTED_TRADERS,
+    WHITELISTED_WITHDRAW_LPS,
 };
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_json, to_json_binary, Addr, Binary, CanonicalAddr, Coin, CosmosMsg, Decimal, Decimal256,
-    Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128,
-    Uint256, WasmMsg,
+    from_json, to_json_binary, Addr, Api, Binary, CanonicalAddr, Coin, CosmosMsg, Decimal,
+    Decimal256, Deps, DepsMut, Env, Event, MessageInfo, QuerierWrapper, Reply, Response, StdError,
+    StdResult, Storage, SubMsg, Uint128, Uint256, WasmMsg,
 };
 
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use cw20_base::msg::InstantiateMsg as TokenInstantiateMsg;
 use integer_sqrt::IntegerSquareRoot;
-use oraiswap::asset::{Asset, AssetInfo, AssetInfoRaw, PairInfoRaw};
+use oraiswap::asset::{Asset, AssetInfo, AssetInfoRaw, PairInfoRaw, PairStatus, PairType};
 use oraiswap::error::ContractError;
 use oraiswap::oracle::OracleContract;
 use oraiswap::pair::{
-    compute_offer_amount, compute_swap, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
-    PairResponse, PoolResponse, QueryMsg, ReverseSimulationResponse, SimulationResponse,
-    DEFAULT_COMMISSION_RATE, DEFAULT_OPERATOR_FEE,
+    assert_deadline, compute_offer_amount, compute_swap, Cw20HookMsg, DynamicFeeParams, ExecuteMsg,
+    FlashSwapReceiverMsg, InstantiateMsg, MigrateMsg, PairResponse, PoolAtHeightResponse,
+    PoolResponse, PriceCumulativeResponse, ProtocolFeesResponse, QueryMsg,
+    ReverseSimulationResponse, SimulationResponse, StableSwapConfigResponse, StatusResponse,
+    SwapHookMsg, TwapResponse, DEFAULT_COMMISSION_RATE, DEFAULT_FLASH_FEE, DEFAULT_OPERATOR_FEE,
+    MINIMUM_LIQUIDITY_AMOUNT,
 };
-use oraiswap::querier::query_supply;
+use oraiswap::querier::{query_supply, query_token_balance};
 use oraiswap::response::MsgInstantiateContractResponse;
-use std::convert::TryFrom;
+use oraiswap::stableswap::{
+    compute_stable_offer_amount, compute_stable_spot_price, compute_stable_swap, MAX_AMP,
+    MAX_AMP_CHANGE, MAX_DECIMALS, MIN_AMP, MIN_AMP_RAMP_TIME,
+};
+use oraiswap::tokenfactory::{burn_msg, create_denom_msg, mint_msg, token_factory_denom};
+use std::convert::{TryFrom, TryInto};
 use std::str::FromStr;
 
 const INSTANTIATE_REPLY_ID: u64 = 1;
+const FLASH_SWAP_REPLY_ID: u64 = 2;
+pub const SWAP_HOOK_REPLY_ID: u64 = 3;
+
+/// Subdenom of the token factory LP share
+const LP_SUBDENOM: &str = "lp";
+
+/// Maximum number of swap hooks, bounds the gas added to every swap
+const MAX_SWAP_HOOKS: u32 = 5;
+
+/// Gas available to each swap hook, running out of it fails the hook and not the swap
+pub const SWAP_HOOK_GAS_LIMIT: u64 = 300_000;
+
+/// Maximum number of corrections when rounding an exact output offer amount
+const MAX_EXACT_OUT_ITERATIONS: u8 = 8;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -35,6 +62,23 @@ pub fn instantiate(
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
+    let pair_type = match (msg.pair_type, &msg.stable_swap) {
+        (Some(PairType::Stable {}), None) => {
+            return Err(StdError::generic_err(
+                ContractError::MissingStableSwapParams {}.to_string(),
+            ))
+        }
+        (Some(PairType::Stable {}), Some(_)) => PairType::Stable {},
+        (Some(_), Some(_)) => {
+            return Err(StdError::generic_err(
+                ContractError::UnexpectedStableSwapParams {}.to_string(),
+            ))
+        }
+        (Some(pair_type), None) => pair_type,
+        (None, Some(_)) => PairType::Stable {},
+        (None, None) => PairType::Xyk {},
+    };
+
     let pair_info = &PairInfoRaw {
         // return infomation from oracle, update by multisig wallet
         oracle_addr: deps.api.addr_canonicalize(msg.oracle_addr.as_str())?,
@@ -42,6 +86,9 @@ pub fn instantiate(
         contract_addr: deps.api.addr_canonicalize(env.contract.address.as_str())?,
         // liquidity token address is ow20 to reward, mint and burn
         liquidity_token: CanonicalAddr::from(vec![]),
+        liquidity_denom: msg
+            .native_lp
+            .then(|| token_factory_denom(env.contract.address.as_str(), LP_SUBDENOM)),
         // pair info
         asset_infos: [
             msg.asset_infos[0].to_raw(deps.api)?,
@@ -52,6 +99,9 @@ pub fn instantiate(
             .commission_rate
             .unwrap_or(DEFAULT_COMMISSION_RATE.to_string()),
         operator_fee: msg.operator_fee.unwrap_or(DEFAULT_OPERATOR_FEE.to_string()),
+        pair_type,
+        status: PairStatus::Active,
+        successor: None,
     };
 
     let total_fee = Decimal256::from_str(&pair_info.commission_rate)?
@@ -71,7 +121,50 @@ pub fn instantiate(
         )?;
     }
 
+    if let Some(stable_swap) = msg.stable_swap {
+        if stable_swap.amp < MIN_AMP || stable_swap.amp > MAX_AMP {
+            return Err(StdError::generic_err(
+                ContractError::InvalidAmp {
+                    amp: stable_swap.amp,
+                }
+                .to_string(),
+            ));
+        }
+        let decimals = match stable_swap.decimals {
+            Some(decimals) => {
+                if decimals.iter().any(|decimals| *decimals > MAX_DECIMALS) {
+                    return Err(StdError::generic_err(format!(
+                        "StableSwap decimals must be at most {}",
+                        MAX_DECIMALS
+                    )));
+                }
+                msg.asset_infos.clone().into_iter().zip(decimals).collect()
+            }
+            None => vec![],
+        };
+        STABLE_SWAP_CONFIG.save(
+            deps.storage,
+            &StableSwapConfig {
+                init_amp: stable_swap.amp,
+                init_amp_time: env.block.time.seconds(),
+                future_amp: stable_swap.amp,
+                future_amp_time: env.block.time.seconds(),
+                decimals,
+            },
+        )?;
+    }
+
     PAIR_INFO.save(deps.storage, pair_info)?;
+    if let Some(factory) = msg.factory {
+        FACTORY.save(deps.storage, &deps.api.addr_validate(factory.as_str())?)?;
+    }
+
+    // the LP share is a token factory denom owned by the pair, no cw20 to instantiate
+    if let Some(denom) = &pair_info.liquidity_denom {
+        return Ok(Response::new()
+            .add_message(create_denom_msg(&env.contract.address, LP_SUBDENOM))
+            .add_attribute("liquidity_token_addr", denom));
+    }
 
     Ok(Response::new().add_submessage(SubMsg::reply_on_success(
         WasmMsg::Instantiate {
@@ -110,14 +203,20 @@ pub fn execute(
             assets,
             slippage_tolerance,
             receiver,
-        } => provide_liquidity(deps, env, info, assets, slippage_tolerance, receiver),
+            deadline,
+        } => {
+            assert_deadline(env.block.time, deadline)?;
+            provide_liquidity(deps, env, info, assets, slippage_tolerance, receiver)
+        }
         // swap token, can not swap native token directly
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            deadline,
         } => {
+            assert_deadline(env.block.time, deadline)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Unauthorized {});
             }
@@ -133,6 +232,64 @@ pub fn execute(
                 to,
             )
         }
+        ExecuteMsg::SwapExactOut {
+            offer_info,
+            max_offer_amount,
+            ask_amount,
+            to,
+            deadline,
+        } => {
+            assert_deadline(env.block.time, deadline)?;
+            if !offer_info.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let offer_asset = Asset {
+                info: offer_info,
+                amount: max_offer_amount,
+            };
+            offer_asset.assert_sent_native_token_balance(&info)?;
+
+            swap_exact_out(deps, env, info.sender, offer_asset, ask_amount, to)
+        }
+        ExecuteMsg::ZapIn {
+            offer_asset,
+            min_lp_out,
+            receiver,
+            deadline,
+        } => {
+            assert_deadline(env.block.time, deadline)?;
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+            offer_asset.assert_sent_native_token_balance(&info)?;
+
+            zap_in(deps, env, info.sender, offer_asset, min_lp_out, receiver)
+        }
+        ExecuteMsg::WithdrawLiquidity { deadline } => {
+            assert_deadline(env.block.time, deadline)?;
+            let amount = sent_native_lp_amount(deps.storage, &info)?;
+            let sender = info.sender.clone();
+            withdraw_liquidity(deps, env, info, sender, amount)
+        }
+        ExecuteMsg::ZapOut {
+            target_asset,
+            minimum_receive,
+            to,
+            deadline,
+        } => {
+            assert_deadline(env.block.time, deadline)?;
+            let amount = sent_native_lp_amount(deps.storage, &info)?;
+            zap_out(
+                deps,
+                env,
+                info.sender,
+                amount,
+                target_asset,
+                minimum_receive,
+                to,
+            )
+        }
         ExecuteMsg::EnableWhitelist { status } => {
             // check permission
             assert_admin(deps.as_ref(), info.sender.to_string())?;
@@ -154,8 +311,69 @@ pub fn execute(
         ExecuteMsg::UpdatePoolInfo {
             commission_rate,
             operator_fee,
-        } => execute_update_pool_info(deps, info, commission_rate, operator_fee),
+            flash_fee,
+        } => execute_update_pool_info(deps, info, commission_rate, operator_fee, flash_fee),
+        ExecuteMsg::FlashSwap {
+            asset,
+            receiver,
+            msg,
+        } => execute_flash_swap(deps, env, info, asset, receiver, msg),
         ExecuteMsg::UpdateOperator { operator } => execute_update_operator(deps, info, operator),
+        ExecuteMsg::UpdateDynamicFee { config } => execute_update_dynamic_fee(deps, info, config),
+        ExecuteMsg::UpdateProtocolFeeAccrual { enabled } => {
+            // check permission
+            assert_admin(deps.as_ref(), info.sender.to_string())?;
+            PROTOCOL_FEE_ACCRUAL.save(deps.storage, &enabled)?;
+
+            Ok(Response::default().add_attributes(vec![
+                ("action", "update_protocol_fee_accrual"),
+                ("enabled", &enabled.to_string()),
+            ]))
+        }
+        ExecuteMsg::CollectProtocolFees {} => execute_collect_protocol_fees(deps, info),
+        ExecuteMsg::RampAmp {
+            future_amp,
+            future_time,
+        } => execute_ramp_amp(deps, env, info, future_amp, future_time),
+        ExecuteMsg::StopRampAmp {} => execute_stop_ramp_amp(deps, env, info),
+        ExecuteMsg::AddSwapHook { hook } => execute_add_swap_hook(deps, info, hook),
+        ExecuteMsg::RemoveSwapHook { hook } => execute_remove_swap_hook(deps, info, hook),
+        ExecuteMsg::UpdatePauseStatus {
+            swap,
+            provide,
+            withdraw,
+        } => execute_update_pause_status(deps, info, swap, provide, withdraw),
+        ExecuteMsg::UpdatePairStatus { status, successor } => {
+            execute_update_pair_status(deps, info, status, successor)
+        }
+        ExecuteMsg::UpdateCircuitBreaker { max_price_change } => {
+            execute_update_circuit_breaker(deps, info, max_price_change)
+        }
+        ExecuteMsg::UpdateMaxPriceImpact { max_price_impact } => {
+            execute_update_max_price_impact(deps, info, max_price_impact)
+        }
+        ExecuteMsg::UpdateTraderFeeTier {
+            trader,
+            commission_rate,
+        } => execute_update_trader_fee_tier(deps, info, trader, commission_rate),
+        ExecuteMsg::Sync {} => execute_sync(deps, env, info),
+        ExecuteMsg::Skim { to } => execute_skim(deps, env, info, to),
+        ExecuteMsg::ProvideReceivedLiquidity {
+            receiver,
+            pools,
+            deposits,
+            balances_before,
+            slippage_tolerance,
+        } => execute_provide_received_liquidity(
+            deps,
+            env,
+            info,
+            receiver,
+            pools,
+            deposits,
+            balances_before,
+            slippage_tolerance,
+        ),
     }
 }
 
@@ -172,12 +390,14 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            deadline,
         }) => {
+            assert_deadline(env.block.time, deadline)?;
             // only asset contract can execute this message
             let mut authorized: bool = false;
             let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
             let pools: [Asset; 2] =
-                config.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
+                query_reserves(deps.as_ref(), &config, env.contract.address.clone())?;
             for pool in pools.iter() {
                 if let AssetInfo::Token { contract_addr, .. } = &pool.info {
                     if info.sender.eq(contract_addr) {
@@ -211,24 +431,232 @@ pub fn receive_cw20(
                 to_addr,
             )
         }
+        Ok(Cw20HookMsg::SwapExactOut {
+            ask_amount,
+            to,
+            deadline,
+        }) => {
+            assert_deadline(env.block.time, deadline)?;
+            // only asset contract can execute this message
+            let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+            let offer_info = AssetInfo::Token { contract_addr };
+            let offer_info_raw = offer_info.to_raw(deps.api)?;
+            if !config.asset_infos.contains(&offer_info_raw) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap_exact_out(
+                deps,
+                env,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: offer_info,
+                    amount: cw20_msg.amount,
+                },
+                ask_amount,
+                to_addr,
+            )
+        }
+        Ok(Cw20HookMsg::ZapIn {
+            min_lp_out,
+            receiver,
+            deadline,
+        }) => {
+            assert_deadline(env.block.time, deadline)?;
+            // only asset contract can execute this message
+            let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+            let offer_info = AssetInfo::Token { contract_addr };
+            let offer_info_raw = offer_info.to_raw(deps.api)?;
+            if !config.asset_infos.contains(&offer_info_raw) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let receiver = if let Some(receiver) = receiver {
+                Some(deps.api.addr_validate(receiver.as_str())?)
+            } else {
+                None
+            };
+
+            zap_in(
+                deps,
+                env,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: offer_info,
+                    amount: cw20_msg.amount,
+                },
+                min_lp_out,
+                receiver,
+            )
+        }
         // remove liquidity
-        Ok(Cw20HookMsg::WithdrawLiquidity {}) => {
+        Ok(Cw20HookMsg::WithdrawLiquidity { deadline }) => {
+            assert_deadline(env.block.time, deadline)?;
             let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-            if deps.api.addr_canonicalize(info.sender.as_str())? != config.liquidity_token {
+            if config.liquidity_denom.is_some()
+                || deps.api.addr_canonicalize(info.sender.as_str())? != config.liquidity_token
+            {
                 return Err(ContractError::Unauthorized {});
             }
             let sender_addr = deps.api.addr_validate(cw20_msg.sender.as_str())?;
             withdraw_liquidity(deps, env, info, sender_addr, cw20_msg.amount)
         }
+        Ok(Cw20HookMsg::ZapOut {
+            target_asset,
+            minimum_receive,
+            to,
+            deadline,
+        }) => {
+            assert_deadline(env.block.time, deadline)?;
+            let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+            if config.liquidity_denom.is_some()
+                || deps.api.addr_canonicalize(info.sender.as_str())? != config.liquidity_token
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+            let sender_addr = deps.api.addr_validate(cw20_msg.sender.as_str())?;
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(to_addr.as_str())?)
+            } else {
+                None
+            };
+            zap_out(
+                deps,
+                env,
+                sender_addr,
+                cw20_msg.amount,
+                target_asset,
+                minimum_receive,
+                to_addr,
+            )
+        }
         Err(err) => Err(ContractError::Std(err)),
     }
 }
 
+/// Adopts the pair balances as the tracked reserves
+fn execute_sync(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+    assert_no_flash_swap(deps.as_ref())?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let reserves = query_reserves(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+    let balances = query_balances(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+
+    // accrue prices with the reserves before the sync
+    update_price_cumulative(deps.storage, &env, [reserves[0].amount, reserves[1].amount])?;
+
+    let total_share = query_total_share(&deps.querier, deps.api, &pair_info)?;
+    save_pool_snapshot(
+        deps.storage,
+        &env,
+        [balances[0].amount, balances[1].amount],
+        total_share,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "sync"),
+        ("reserves", &format!("{}, {}", balances[0], balances[1])),
+    ]))
+}
+
+/// Mints the share of a provision whose cw20 deposits were just pulled,
+/// each one is credited with the balance it added to the pair
+#[allow(clippy::too_many_arguments)]
+fn execute_provide_received_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receiver: Addr,
+    pools: [Asset; 2],
+    deposits: [Uint128; 2],
+    balances_before: Vec<Asset>,
+    slippage_tolerance: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut deposits = deposits;
+    for balance_before in balances_before {
+        let index = pools
+            .iter()
+            .position(|pool| pool.info.eq(&balance_before.info))
+            .ok_or(ContractError::AssetMismatch {})?;
+        let balance = balance_before
+            .info
+            .query_pool(&deps.querier, env.contract.address.clone())?;
+        deposits[index] = std::cmp::min(
+            deposits[index],
+            balance.saturating_sub(balance_before.amount),
+        );
+    }
+
+    let (messages, share) =
+        mint_liquidity(deps, &env, &pools, deposits, &slippage_tolerance, &receiver)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "provide_received_liquidity"),
+        ("receiver", receiver.as_str()),
+        (
+            "deposits",
+            &format!(
+                "{}{}, {}{}",
+                deposits[0], pools[0].info, deposits[1], pools[1].info
+            ),
+        ),
+        ("share", &share.to_string()),
+    ]))
+}
+
+/// Sends the balances above the tracked reserves to `to`
+fn execute_skim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: Addr,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+    assert_no_flash_swap(deps.as_ref())?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let reserves = query_reserves(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+    let balances = query_balances(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+    let oracle_contract = OracleContract(deps.api.addr_humanize(&pair_info.oracle_addr)?);
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut skimmed: Vec<String> = vec![];
+    for (balance, reserve) in balances.iter().zip(reserves.iter()) {
+        let surplus = Asset {
+            info: balance.info.clone(),
+            amount: balance.amount.saturating_sub(reserve.amount),
+        };
+        if surplus.amount.is_zero() {
+            continue;
+        }
+        skimmed.push(surplus.to_string());
+        messages.push(surplus.into_msg(Some(&oracle_contract), &deps.querier, to.clone())?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "skim"),
+        ("to", to.as_str()),
+        ("skimmed", &skimmed.join(", ")),
+    ]))
+}
+
 fn execute_update_pool_info(
     deps: DepsMut,
     info: MessageInfo,
     commission_rate: Option<String>,
     operator_fee: Option<String>,
+    flash_fee: Option<String>,
 ) -> Result<Response, ContractError> {
     assert_admin(deps.as_ref(), info.sender.to_string())?;
 
@@ -240,11 +668,93 @@ fn execute_update_pool_info(
         config.operator_fee = operator_fee;
     };
 
+    // keep the bounds checked when the pair was configured
+    let operator_fee = Decimal256::from_str(&config.operator_fee)?;
+    if Decimal256::from_str(&config.commission_rate)? + operator_fee >= Decimal256::one() {
+        return Err(StdError::generic_err("Total fee must be less than 1").into());
+    }
+    if let Some(dynamic_fee) = DYNAMIC_FEE.may_load(deps.storage)? {
+        if Decimal256::from(dynamic_fee.max_commission_rate) + operator_fee >= Decimal256::one() {
+            return Err(ContractError::InvalidDynamicFee {});
+        }
+    }
+    if let Some(flash_fee) = flash_fee {
+        if Decimal256::from_str(&flash_fee)? >= Decimal256::one() {
+            return Err(StdError::generic_err("Flash fee must be less than 1").into());
+        }
+        FLASH_FEE.save(deps.storage, &flash_fee)?;
+    }
+
     PAIR_INFO.save(deps.storage, &config)?;
 
     Ok(Response::default().add_attribute("action", "update_pool_info"))
 }
 
+pub fn execute_update_dynamic_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    config: Option<DynamicFeeParams>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+
+    match config {
+        Some(config) => {
+            let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+            let operator_fee = Decimal::from_str(&pair_info.operator_fee)?;
+            if config.min_commission_rate > config.max_commission_rate
+                || config.max_commission_rate + operator_fee >= Decimal::one()
+                || config.max_deviation.is_zero()
+                || config.window_seconds == 0
+            {
+                return Err(ContractError::InvalidDynamicFee {});
+            }
+            DYNAMIC_FEE.save(deps.storage, &config)?;
+        }
+        None => DYNAMIC_FEE.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attribute("action", "update_dynamic_fee"))
+}
+
+pub fn execute_collect_protocol_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let operator = OPERATOR
+        .may_load(deps.storage)?
+        .ok_or(ContractError::Unauthorized {})?;
+    // operator or admin can trigger the collection
+    if deps.api.addr_canonicalize(info.sender.as_str())? != operator {
+        assert_admin(deps.as_ref(), info.sender.to_string())?;
+    }
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let operator_addr = deps.api.addr_humanize(&operator)?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut collected: Vec<String> = vec![];
+    for asset_info in pair_info.asset_infos.iter() {
+        let info = asset_info.to_normal(deps.api)?;
+        let key = info.to_string();
+        let amount = PROTOCOL_FEES
+            .may_load(deps.storage, &key)?
+            .unwrap_or_default();
+        if amount.is_zero() {
+            continue;
+        }
+        PROTOCOL_FEES.remove(deps.storage, &key);
+
+        let asset = Asset { info, amount };
+        collected.push(asset.to_string());
+        messages.push(asset.into_msg(None, &deps.querier, operator_addr.clone())?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "collect_protocol_fees"),
+        ("operator", operator_addr.as_str()),
+        ("collected", &collected.join(", ")),
+    ]))
+}
+
 pub fn execute_update_operator(
     deps: DepsMut,
     info: MessageInfo,
@@ -261,9 +771,95 @@ pub fn execute_update_operator(
     Ok(Response::default().add_attribute("action", "update_operator"))
 }
 
-/// This just stores the result for future query
+pub fn execute_ramp_amp(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    future_amp: u64,
+    future_time: u64,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+
+    let mut config = STABLE_SWAP_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NotStableSwapPair {})?;
+
+    if future_amp < MIN_AMP || future_amp > MAX_AMP {
+        return Err(ContractError::InvalidAmp { amp: future_amp });
+    }
+
+    let block_time = env.block.time.seconds();
+    let current_amp = config.current_amp(block_time);
+    // only one ramp at a time, and it must be slow and bounded
+    if block_time < config.future_amp_time
+        || future_time < block_time + MIN_AMP_RAMP_TIME
+        || future_amp > current_amp * MAX_AMP_CHANGE
+        || future_amp * MAX_AMP_CHANGE < current_amp
+    {
+        return Err(ContractError::InvalidAmpRamp {});
+    }
+
+    config.init_amp = current_amp;
+    config.init_amp_time = block_time;
+    config.future_amp = future_amp;
+    config.future_amp_time = future_time;
+    STABLE_SWAP_CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "ramp_amp"),
+        ("init_amp", &current_amp.to_string()),
+        ("future_amp", &future_amp.to_string()),
+        ("future_time", &future_time.to_string()),
+    ]))
+}
+
+pub fn execute_stop_ramp_amp(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+
+    let mut config = STABLE_SWAP_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NotStableSwapPair {})?;
+
+    let block_time = env.block.time.seconds();
+    let current_amp = config.current_amp(block_time);
+    config.init_amp = current_amp;
+    config.init_amp_time = block_time;
+    config.future_amp = current_amp;
+    config.future_amp_time = block_time;
+    STABLE_SWAP_CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "stop_ramp_amp"),
+        ("amp", &current_amp.to_string()),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_REPLY_ID => Ok(instantiate_reply(deps, msg)?),
+        FLASH_SWAP_REPLY_ID => flash_swap_reply(deps, env),
+        SWAP_HOOK_REPLY_ID => Ok(swap_hook_reply(msg)),
+        _ => Err(StdError::generic_err(format!("Unknown reply id {}", msg.id)).into()),
+    }
+}
+
+/// Swallow the error of a failed swap hook so the swap still goes through
+fn swap_hook_reply(msg: Reply) -> Response {
+    match msg.result.into_result() {
+        Ok(_) => Response::new(),
+        Err(err) => {
+            Response::new().add_attributes(vec![("action", "swap_hook_failed"), ("error", &err)])
+        }
+    }
+}
+
+/// This just stores the result for future query
+fn instantiate_reply(deps: DepsMut, msg: Reply) -> StdResult<Response> {
     let data = msg.result.unwrap().data.unwrap();
 
     let res = MsgInstantiateContractResponse::try_from(data.as_slice()).map_err(|_| {
@@ -280,27 +876,177 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
     Ok(Response::new().add_attribute("liquidity_token_addr", liquidity_token))
 }
 
-/// CONTRACT - should approve contract to use the amount of token
-pub fn provide_liquidity(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    assets: [Asset; 2],
-    slippage_tolerance: Option<Decimal>,
-    receiver: Option<Addr>,
-) -> Result<Response, ContractError> {
-    // check pool is only open for whitelisted trader
-    assert_is_open_for_whitelisted_trader(deps.as_ref(), info.sender.clone())?;
+/// Checks the borrowed reserves were restored with the flash fee,
+/// then routes the fee to the operator or leaves it to the pool
+fn flash_swap_reply(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let flash_swap = FLASH_SWAP.load(deps.storage)?;
+    FLASH_SWAP.remove(deps.storage);
 
-    for asset in assets.iter() {
-        asset.assert_sent_native_token_balance(&info)?;
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] =
+        query_balances(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+
+    // only what was received during the loan counts, a surplus sent before it stays out
+    let mut received = flash_swap.reserves;
+    for (i, reserve) in received.iter_mut().enumerate() {
+        *reserve = reserve
+            .checked_add(pools[i].amount)?
+            .checked_sub(flash_swap.balances[i])
+            .map_err(|_| ContractError::FlashSwapNotRepaid {})?;
     }
 
-    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-    let mut pools: [Asset; 2] =
-        pair_info.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
+    // the loan and the fee come back in the borrowed asset, paying with the other
+    // asset would be a swap skipping the commission and the swap guards
+    let mut balances = received;
+    let (index, other) = if flash_swap.asset.info.eq(&pools[0].info) {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    balances[index] = balances[index]
+        .checked_sub(flash_swap.fee_amount)
+        .map_err(|_| ContractError::FlashSwapNotRepaid {})?;
+    if balances[index] < flash_swap.reserves[index] || balances[other] < flash_swap.reserves[other]
+    {
+        return Err(ContractError::FlashSwapNotRepaid {});
+    }
 
-    let deposits: [Uint128; 2] = [
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let reserves_after = if let Some(operator) = OPERATOR.may_load(deps.storage)? {
+        messages.extend(pay_operator_fee(
+            deps.storage,
+            &deps.querier,
+            deps.api,
+            &operator,
+            Asset {
+                info: flash_swap.asset.info.clone(),
+                amount: flash_swap.fee_amount,
+            },
+        )?);
+        balances
+    } else {
+        received
+    };
+    let total_share = query_total_share(&deps.querier, deps.api, &pair_info)?;
+    save_pool_snapshot(deps.storage, &env, reserves_after, total_share)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "flash_swap_repaid"),
+        ("initiator", flash_swap.initiator.as_str()),
+        ("asset", &flash_swap.asset.to_string()),
+        ("fee_amount", &flash_swap.fee_amount.to_string()),
+    ]))
+}
+
+pub fn execute_flash_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    receiver: Addr,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    // check pool is only open for whitelisted trader
+    assert_is_open_for_whitelisted_trader(deps.as_ref(), info.sender.clone())?;
+    assert_no_flash_swap(deps.as_ref())?;
+    assert_not_paused(deps.storage, &[PairAction::Swap])?;
+
+    if asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] =
+        query_reserves(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+    if !asset.info.eq(&pools[0].info) && !asset.info.eq(&pools[1].info) {
+        return Err(ContractError::AssetMismatch {});
+    }
+    let reserves = [pools[0].amount, pools[1].amount];
+    let balances = query_balances(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+
+    // accrue prices with the reserves before the loan
+    update_price_cumulative(deps.storage, &env, reserves)?;
+
+    let flash_fee = Decimal256::from_str(
+        &FLASH_FEE
+            .may_load(deps.storage)?
+            .unwrap_or(DEFAULT_FLASH_FEE.to_string()),
+    )?;
+    let fee_amount: Uint128 = (Uint256::from(asset.amount) * flash_fee)
+        .try_into()
+        .map_err(StdError::from)?;
+    let receiver = deps.api.addr_validate(receiver.as_str())?;
+
+    FLASH_SWAP.save(
+        deps.storage,
+        &FlashSwapState {
+            initiator: info.sender.clone(),
+            asset: asset.clone(),
+            fee_amount,
+            reserves,
+            balances: [balances[0].amount, balances[1].amount],
+        },
+    )?;
+
+    let callback = SubMsg::reply_on_success(
+        WasmMsg::Execute {
+            contract_addr: receiver.to_string(),
+            msg: to_json_binary(&FlashSwapReceiverMsg::FlashSwapCallback {
+                initiator: info.sender.clone(),
+                asset: asset.clone(),
+                fee_amount,
+                msg,
+            })?,
+            funds: vec![],
+        },
+        FLASH_SWAP_REPLY_ID,
+    );
+
+    Ok(Response::new()
+        .add_message(asset.into_msg(None, &deps.querier, receiver.clone())?)
+        .add_submessage(callback)
+        .add_attributes(vec![
+            ("action", "flash_swap"),
+            ("initiator", info.sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("asset", &asset.to_string()),
+            ("fee_amount", &fee_amount.to_string()),
+        ]))
+}
+
+/// CONTRACT - should approve contract to use the amount of token
+pub fn provide_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    receiver: Option<Addr>,
+) -> Result<Response, ContractError> {
+    // check pool is only open for whitelisted trader
+    assert_is_open_for_whitelisted_trader(deps.as_ref(), info.sender.clone())?;
+    assert_no_flash_swap(deps.as_ref())?;
+    assert_not_paused(deps.storage, &[PairAction::Provide])?;
+
+    for asset in assets.iter() {
+        asset.assert_sent_native_token_balance(&info)?;
+    }
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    // native deposits are already in the balance, cw20 ones are pulled below
+    let native_deposits: Vec<Asset> = assets
+        .iter()
+        .filter(|asset| asset.is_native_token())
+        .cloned()
+        .collect();
+    let pools: [Asset; 2] = query_reserves_before(
+        deps.as_ref(),
+        &pair_info,
+        env.contract.address.clone(),
+        &native_deposits,
+    )?;
+
+    let deposits: [Uint128; 2] = [
         assets
             .iter()
             .find(|a| a.info.eq(&pools[0].info))
@@ -314,7 +1060,8 @@ pub fn provide_liquidity(
     ];
 
     let mut messages: Vec<CosmosMsg> = vec![];
-    for (i, pool) in pools.iter_mut().enumerate() {
+    let mut balances_before: Vec<Asset> = vec![];
+    for (i, pool) in pools.iter().enumerate() {
         // If the pool is token contract, then we need to execute TransferFrom msg to receive funds
         if let AssetInfo::Token { contract_addr, .. } = &pool.info {
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -326,21 +1073,90 @@ pub fn provide_liquidity(
                 })?,
                 funds: vec![],
             }));
-        } else {
-            // If the asset is native token, balance is already increased
-            // To calculated properly we should subtract user deposit from the pool
-            pool.amount = pool.amount.checked_sub(deposits[i])?;
+            balances_before.push(Asset {
+                info: pool.info.clone(),
+                amount: pool
+                    .info
+                    .query_pool(&deps.querier, env.contract.address.clone())?,
+            });
         }
     }
 
+    // accrue prices with the reserves before the deposit
+    update_price_cumulative(deps.storage, &env, [pools[0].amount, pools[1].amount])?;
+
+    let receiver = receiver.unwrap_or(info.sender.clone());
+    let attributes = vec![
+        ("action", "provide_liquidity".to_string()),
+        ("sender", info.sender.to_string()),
+        ("receiver", receiver.to_string()),
+        ("assets", format!("{}, {}", assets[0], assets[1])),
+    ];
+
+    // a fee-on-transfer token can deliver less than its amount, so the share
+    // is minted once the pulled deposits are in the balance
+    if !balances_before.is_empty() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_json_binary(&ExecuteMsg::ProvideReceivedLiquidity {
+                receiver,
+                pools,
+                deposits,
+                balances_before,
+                slippage_tolerance,
+            })?,
+            funds: vec![],
+        }));
+        return Ok(Response::new()
+            .add_messages(messages)
+            .add_attributes(attributes));
+    }
+
+    let (mint_messages, share) =
+        mint_liquidity(deps, &env, &pools, deposits, &slippage_tolerance, &receiver)?;
+    messages.extend(mint_messages);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes)
+        .add_attribute("share", share.to_string()))
+}
+
+/// Mints the share of `deposits` on top of `pools` to `receiver`
+fn mint_liquidity(
+    deps: DepsMut,
+    env: &Env,
+    pools: &[Asset; 2],
+    deposits: [Uint128; 2],
+    slippage_tolerance: &Option<Decimal>,
+    receiver: &Addr,
+) -> Result<(Vec<CosmosMsg>, Uint128), ContractError> {
     // assert slippage tolerance
-    assert_slippage_tolerance(&slippage_tolerance, &deposits, &pools)?;
+    assert_slippage_tolerance(slippage_tolerance, &deposits, pools)?;
 
-    let liquidity_token = deps.api.addr_humanize(&pair_info.liquidity_token)?;
-    let total_share = query_supply(&deps.querier, liquidity_token)?;
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let total_share = query_total_share(&deps.querier, deps.api, &pair_info)?;
     let share = if total_share == Uint128::zero() {
         // Initial share = collateral amount
-        Uint128::from((deposits[0].u128() * deposits[1].u128()).integer_sqrt())
+        let share = Uint128::from((deposits[0].u128() * deposits[1].u128()).integer_sqrt());
+        if share <= MINIMUM_LIQUIDITY_AMOUNT {
+            return Err(ContractError::MinimumLiquidityAmountError {
+                min_lp_amount: MINIMUM_LIQUIDITY_AMOUNT,
+            });
+        }
+
+        // lock the minimum liquidity in the pair so the share price cannot be inflated
+        LOCKED_SHARE.save(deps.storage, &MINIMUM_LIQUIDITY_AMOUNT)?;
+        messages.push(mint_lp_msg(
+            deps.api,
+            &pair_info,
+            &env.contract.address,
+            &env.contract.address,
+            MINIMUM_LIQUIDITY_AMOUNT,
+        )?);
+
+        share - MINIMUM_LIQUIDITY_AMOUNT
     } else {
         // min(1, 2)
         // 1. sqrt(deposit_0 * exchange_rate_0_to_1 * deposit_0) * (total_share / sqrt(pool_0 * pool_1))
@@ -358,27 +1174,235 @@ pub fn provide_liquidity(
         return Err(ContractError::InvalidZeroAmount {});
     }
 
-    // mint LP token to sender
-    let receiver = receiver.unwrap_or(info.sender.clone());
-    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: deps
-            .api
-            .addr_humanize(&pair_info.liquidity_token)?
-            .to_string(),
-        msg: to_json_binary(&Cw20ExecuteMsg::Mint {
-            recipient: receiver.to_string(),
-            amount: share,
-        })?,
-        funds: vec![],
-    }));
+    // the locked minimum liquidity is minted with the first share
+    let locked_share = if total_share.is_zero() {
+        MINIMUM_LIQUIDITY_AMOUNT
+    } else {
+        Uint128::zero()
+    };
+    save_pool_snapshot(
+        deps.storage,
+        env,
+        [
+            pools[0].amount.checked_add(deposits[0])?,
+            pools[1].amount.checked_add(deposits[1])?,
+        ],
+        total_share.checked_add(share)?.checked_add(locked_share)?,
+    )?;
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        ("action", "provide_liquidity"),
-        ("sender", info.sender.as_str()),
-        ("receiver", receiver.as_str()),
-        ("assets", &format!("{}, {}", assets[0], assets[1])),
-        ("share", &share.to_string()),
-    ]))
+    // mint LP token to receiver
+    messages.push(mint_lp_msg(
+        deps.api,
+        &pair_info,
+        &env.contract.address,
+        receiver,
+        share,
+    )?);
+
+    Ok((messages, share))
+}
+
+/// CONTRACT - the offer asset must already be transferred to the pool
+pub fn zap_in(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    min_lp_out: Uint128,
+    receiver: Option<Addr>,
+) -> Result<Response, ContractError> {
+    // check pool is only open for whitelisted trader
+    assert_is_open_for_whitelisted_trader(deps.as_ref(), sender.clone())?;
+    assert_no_flash_swap(deps.as_ref())?;
+    assert_not_paused(deps.storage, &[PairAction::Provide, PairAction::Swap])?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let operator = OPERATOR.may_load(deps.storage)?;
+    let pools: [Asset; 2] = query_reserves_before(
+        deps.as_ref(),
+        &pair_info,
+        env.contract.address.clone(),
+        &[offer_asset.clone()],
+    )?;
+
+    let reserves = [pools[0].amount, pools[1].amount];
+    let (offer_pool, ask_pool) = if offer_asset.info.eq(&pools[0].info) {
+        (pools[0].amount, pools[1].clone())
+    } else if offer_asset.info.eq(&pools[1].info) {
+        (pools[1].amount, pools[0].clone())
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    // a fee-on-transfer token or a taxed denom can deliver less than declared
+    let offer_asset = Asset {
+        amount: query_received_amount(
+            deps.as_ref(),
+            env.contract.address.clone(),
+            offer_pool,
+            &offer_asset,
+        )?,
+        info: offer_asset.info,
+    };
+
+    // accrue prices with the reserves before the deposit
+    update_price_cumulative(deps.storage, &env, reserves)?;
+
+    let total_share = query_total_share(&deps.querier, deps.api, &pair_info)?;
+    if total_share.is_zero() {
+        return Err(StdError::generic_err("Cannot zap into an empty pool").into());
+    }
+
+    let commission_rate = compute_commission_rate(
+        deps.storage,
+        &env,
+        reserves,
+        Decimal256::from_str(&pair_info.commission_rate)?,
+    )?;
+    let commission_rate = trader_commission_rate(deps.storage, &sender, commission_rate)?;
+    // operator fee is only charged when there is an operator to receive it
+    let operator_fee = match operator {
+        Some(_) => Decimal256::from_str(&pair_info.operator_fee)?,
+        None => Decimal256::zero(),
+    };
+    let (swap_amount, return_amount, spread_amount, commission_amount, operator_fee_amount) =
+        compute_zap_in_swap(
+            deps.storage,
+            &env,
+            &offer_asset.info,
+            offer_pool,
+            ask_pool.amount,
+            offer_asset.amount,
+            commission_rate,
+            operator_fee,
+        )?;
+    assert_max_price_impact(
+        deps.storage,
+        return_amount + commission_amount + operator_fee_amount,
+        spread_amount,
+    )?;
+
+    // the swapped portion and its return never leave the pool
+    let offer_pool = offer_pool.checked_add(swap_amount)?;
+    let ask_pool_amount = ask_pool
+        .amount
+        .checked_sub(return_amount)?
+        .checked_sub(operator_fee_amount)?;
+    let offer_deposit = offer_asset.amount.checked_sub(swap_amount)?;
+    let share = std::cmp::min(
+        offer_deposit.multiply_ratio(total_share, offer_pool),
+        return_amount.multiply_ratio(total_share, ask_pool_amount),
+    );
+
+    // prevent providing free token
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    if share < min_lp_out {
+        return Err(ContractError::MinLpOutAssertion { min_lp_out, share });
+    }
+
+    // the swap leg alone can trip the circuit breaker
+    let swap_reserves = reserves_after_swap(
+        &pools,
+        reserves,
+        &offer_asset.info,
+        swap_amount,
+        return_amount.checked_add(operator_fee_amount)?,
+    )?;
+    let circuit_breaker_tripped = trip_circuit_breaker(
+        deps.storage,
+        &env,
+        reserves,
+        [swap_reserves[0].amount, swap_reserves[1].amount],
+    )?;
+
+    // the whole offer stays in the pool, only the operator fee leaves the ask side
+    let mut reserves_after = reserves;
+    for (reserve, pool) in reserves_after.iter_mut().zip(pools.iter()) {
+        *reserve = if pool.info.eq(&offer_asset.info) {
+            reserve.checked_add(offer_asset.amount)?
+        } else {
+            reserve.checked_sub(operator_fee_amount)?
+        };
+    }
+    save_pool_snapshot(
+        deps.storage,
+        &env,
+        reserves_after,
+        total_share.checked_add(share)?,
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Some(addr) = operator {
+        messages.extend(pay_operator_fee(
+            deps.storage,
+            &deps.querier,
+            deps.api,
+            &addr,
+            Asset {
+                info: ask_pool.info.clone(),
+                amount: operator_fee_amount,
+            },
+        )?);
+    }
+
+    // mint LP token to receiver
+    let receiver = receiver.unwrap_or(sender.clone());
+    messages.push(mint_lp_msg(
+        deps.api,
+        &pair_info,
+        &env.contract.address,
+        &receiver,
+        share,
+    )?);
+    let hook_msgs = swap_hook_msgs(
+        deps.storage,
+        SwapHookMsg::SwapHook {
+            trader: sender.clone(),
+            receiver: receiver.clone(),
+            offer_asset: Asset {
+                info: offer_asset.info.clone(),
+                amount: swap_amount,
+            },
+            return_asset: Asset {
+                info: ask_pool.info.clone(),
+                amount: return_amount,
+            },
+            commission_amount,
+            operator_fee_amount,
+            reserves: [
+                Asset {
+                    info: pools[0].info.clone(),
+                    amount: reserves_after[0],
+                },
+                Asset {
+                    info: pools[1].info.clone(),
+                    amount: reserves_after[1],
+                },
+            ],
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "zap_in"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("offer_asset", &offer_asset.to_string()),
+            ("swap_amount", &swap_amount.to_string()),
+            ("return_amount", &return_amount.to_string()),
+            ("spread_amount", &spread_amount.to_string()),
+            ("commission_amount", &commission_amount.to_string()),
+            ("operator_fee_amount", &operator_fee_amount.to_string()),
+            ("share", &share.to_string()),
+            (
+                "circuit_breaker_tripped",
+                &circuit_breaker_tripped.to_string(),
+            ),
+        ]))
 }
 
 pub fn withdraw_liquidity(
@@ -390,19 +1414,23 @@ pub fn withdraw_liquidity(
 ) -> Result<Response, ContractError> {
     // check pool is only open for whitelisted provider
     assert_is_open_for_whitelisted_withdraw_lp(deps.as_ref(), sender.clone())?;
+    assert_no_flash_swap(deps.as_ref())?;
+    assert_not_paused(deps.storage, &[PairAction::Withdraw])?;
 
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-    let liquidity_addr = deps.api.addr_humanize(&pair_info.liquidity_token)?;
 
     let pools: [Asset; 2] =
-        pair_info.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
-    let total_share: Uint128 = query_supply(&deps.querier, liquidity_addr)?;
+        query_reserves(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+    let total_share: Uint128 = query_total_share(&deps.querier, deps.api, &pair_info)?;
 
     let share_ratio = Decimal::from_ratio(amount, total_share);
     if share_ratio.is_zero() {
         return Err(ContractError::InvalidZeroRatio {});
     }
 
+    // accrue prices with the reserves before the withdrawal
+    update_price_cumulative(deps.storage, &env, [pools[0].amount, pools[1].amount])?;
+
     let refund_assets: Vec<Asset> = pools
         .iter()
         .map(|a| Asset {
@@ -411,7 +1439,19 @@ pub fn withdraw_liquidity(
         })
         .collect();
 
+    // the tax is deducted from the sent amounts and stays in the pool
     let oracle_contract = OracleContract(deps.api.addr_humanize(&pair_info.oracle_addr)?);
+    let mut reserves_after = [pools[0].amount, pools[1].amount];
+    for (reserve, refund_asset) in reserves_after.iter_mut().zip(refund_assets.iter()) {
+        let tax_amount = refund_asset.compute_tax(&oracle_contract, &deps.querier)?;
+        *reserve = reserve.checked_sub(refund_asset.amount.checked_sub(tax_amount)?)?;
+    }
+    save_pool_snapshot(
+        deps.storage,
+        &env,
+        reserves_after,
+        total_share.checked_sub(amount)?,
+    )?;
 
     let messages = vec![
         refund_assets[0]
@@ -421,15 +1461,7 @@ pub fn withdraw_liquidity(
             .clone()
             .into_msg(Some(&oracle_contract), &deps.querier, sender.clone())?,
         // burn liquidity token
-        WasmMsg::Execute {
-            contract_addr: deps
-                .api
-                .addr_humanize(&pair_info.liquidity_token)?
-                .to_string(),
-            msg: to_json_binary(&Cw20ExecuteMsg::Burn { amount })?,
-            funds: vec![],
-        }
-        .into(),
+        burn_lp_msg(deps.api, &pair_info, &env.contract.address, amount)?,
     ];
 
     // update pool info
@@ -444,6 +1476,208 @@ pub fn withdraw_liquidity(
     ]))
 }
 
+/// Burns `amount` of LP and pays out only `target_asset`, the withdrawn
+/// other side is swapped back into the pool
+pub fn zap_out(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    amount: Uint128,
+    target_asset: AssetInfo,
+    minimum_receive: Uint128,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    // check pool is only open for whitelisted provider
+    assert_is_open_for_whitelisted_withdraw_lp(deps.as_ref(), sender.clone())?;
+    assert_no_flash_swap(deps.as_ref())?;
+    assert_not_paused(deps.storage, &[PairAction::Withdraw, PairAction::Swap])?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let operator = OPERATOR.may_load(deps.storage)?;
+
+    let pools: [Asset; 2] =
+        query_reserves(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+    let total_share: Uint128 = query_total_share(&deps.querier, deps.api, &pair_info)?;
+
+    let share_ratio = Decimal::from_ratio(amount, total_share);
+    if share_ratio.is_zero() {
+        return Err(ContractError::InvalidZeroRatio {});
+    }
+
+    let (target_pool, other_pool) = if target_asset.eq(&pools[0].info) {
+        (pools[0].clone(), pools[1].clone())
+    } else if target_asset.eq(&pools[1].info) {
+        (pools[1].clone(), pools[0].clone())
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    // accrue prices with the reserves before the withdrawal
+    update_price_cumulative(deps.storage, &env, [pools[0].amount, pools[1].amount])?;
+
+    let target_withdrawn = target_pool.amount * share_ratio;
+    let other_withdrawn = other_pool.amount * share_ratio;
+
+    // swap the other side against the pool left after the withdrawal
+    let commission_rate = compute_commission_rate(
+        deps.storage,
+        &env,
+        [pools[0].amount, pools[1].amount],
+        Decimal256::from_str(&pair_info.commission_rate)?,
+    )?;
+    let commission_rate = trader_commission_rate(deps.storage, &sender, commission_rate)?;
+    // operator fee is only charged when there is an operator to receive it
+    let operator_fee = match operator {
+        Some(_) => Decimal256::from_str(&pair_info.operator_fee)?,
+        None => Decimal256::zero(),
+    };
+    let (return_amount, spread_amount, commission_amount, operator_fee_amount) = compute_pool_swap(
+        deps.storage,
+        &env,
+        &other_pool.info,
+        other_pool.amount.checked_sub(other_withdrawn)?,
+        target_pool.amount.checked_sub(target_withdrawn)?,
+        other_withdrawn,
+        commission_rate,
+        operator_fee,
+    )?;
+    assert_max_price_impact(
+        deps.storage,
+        return_amount + commission_amount + operator_fee_amount,
+        spread_amount,
+    )?;
+
+    let receive_amount = target_withdrawn.checked_add(return_amount)?;
+    if receive_amount < minimum_receive {
+        return Err(ContractError::SwapAssertionFailure {
+            minium_receive: minimum_receive,
+            swap_amount: receive_amount,
+        });
+    }
+
+    // the swap leg runs against the pool left after the withdrawal
+    let mut swap_reserves = [pools[0].amount, pools[1].amount];
+    for (reserve, pool) in swap_reserves.iter_mut().zip(pools.iter()) {
+        *reserve = if pool.info.eq(&target_asset) {
+            reserve.checked_sub(target_withdrawn)?
+        } else {
+            reserve.checked_sub(other_withdrawn)?
+        };
+    }
+    let swap_reserves_after = reserves_after_swap(
+        &pools,
+        swap_reserves,
+        &other_pool.info,
+        other_withdrawn,
+        return_amount.checked_add(operator_fee_amount)?,
+    )?;
+    let circuit_breaker_tripped = trip_circuit_breaker(
+        deps.storage,
+        &env,
+        swap_reserves,
+        [swap_reserves_after[0].amount, swap_reserves_after[1].amount],
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Some(addr) = operator {
+        messages.extend(pay_operator_fee(
+            deps.storage,
+            &deps.querier,
+            deps.api,
+            &addr,
+            Asset {
+                info: target_asset.clone(),
+                amount: operator_fee_amount,
+            },
+        )?);
+    }
+
+    let oracle_contract = OracleContract(deps.api.addr_humanize(&pair_info.oracle_addr)?);
+    let receive_asset = Asset {
+        info: target_asset.clone(),
+        amount: receive_amount,
+    };
+    let tax_amount = receive_asset.compute_tax(&oracle_contract, &deps.querier)?;
+
+    // the other side is swapped back, only the target side leaves the pool,
+    // the tax is deducted from the sent amount and stays in the pool
+    let mut reserves_after = [pools[0].amount, pools[1].amount];
+    for (reserve, pool) in reserves_after.iter_mut().zip(pools.iter()) {
+        if pool.info.eq(&target_asset) {
+            *reserve = reserve
+                .checked_sub(receive_amount.checked_sub(tax_amount)?)?
+                .checked_sub(operator_fee_amount)?;
+        }
+    }
+    save_pool_snapshot(
+        deps.storage,
+        &env,
+        reserves_after,
+        total_share.checked_sub(amount)?,
+    )?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    messages.push(receive_asset.clone().into_msg(
+        Some(&oracle_contract),
+        &deps.querier,
+        receiver.clone(),
+    )?);
+    // burn liquidity token
+    messages.push(burn_lp_msg(
+        deps.api,
+        &pair_info,
+        &env.contract.address,
+        amount,
+    )?);
+    let hook_msgs = swap_hook_msgs(
+        deps.storage,
+        SwapHookMsg::SwapHook {
+            trader: sender.clone(),
+            receiver: receiver.clone(),
+            offer_asset: Asset {
+                info: other_pool.info.clone(),
+                amount: other_withdrawn,
+            },
+            return_asset: Asset {
+                info: target_asset.clone(),
+                amount: return_amount,
+            },
+            commission_amount,
+            operator_fee_amount,
+            reserves: [
+                Asset {
+                    info: pools[0].info.clone(),
+                    amount: reserves_after[0],
+                },
+                Asset {
+                    info: pools[1].info.clone(),
+                    amount: reserves_after[1],
+                },
+            ],
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "zap_out"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("withdrawn_share", &amount.to_string()),
+            ("swap_amount", &other_withdrawn.to_string()),
+            ("return_amount", &return_amount.to_string()),
+            ("spread_amount", &spread_amount.to_string()),
+            ("commission_amount", &commission_amount.to_string()),
+            ("operator_fee_amount", &operator_fee_amount.to_string()),
+            ("receive_asset", &receive_asset.to_string()),
+            ("tax_amount", &tax_amount.to_string()),
+            (
+                "circuit_breaker_tripped",
+                &circuit_breaker_tripped.to_string(),
+            ),
+        ]))
+}
+
 /// CONTRACT - a user must do token approval
 /// some params retrieving from oracle contract
 #[allow(clippy::too_many_arguments)]
@@ -459,6 +1693,8 @@ pub fn swap(
 ) -> Result<Response, ContractError> {
     // check pool is only open for whitelisted trader
     assert_is_open_for_whitelisted_trader(deps.as_ref(), sender.clone())?;
+    assert_no_flash_swap(deps.as_ref())?;
+    assert_not_paused(deps.storage, &[PairAction::Swap])?;
 
     offer_asset.assert_sent_native_token_balance(&info)?;
 
@@ -467,35 +1703,59 @@ pub fn swap(
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
     let operator = OPERATOR.may_load(deps.storage)?;
 
-    let pools: [Asset; 2] =
-        pair_info.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
+    let pools: [Asset; 2] = query_reserves_before(
+        deps.as_ref(),
+        &pair_info,
+        env.contract.address.clone(),
+        &[offer_asset.clone()],
+    )?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
 
-    // If the asset balance is already increased
-    // To calculated properly we should subtract user deposit from the pool
     if offer_asset.info.eq(&pools[0].info) {
-        offer_pool = Asset {
-            amount: pools[0].amount.checked_sub(offer_asset.amount)?,
-            info: pools[0].info.clone(),
-        };
+        offer_pool = pools[0].clone();
         ask_pool = pools[1].clone();
     } else if offer_asset.info.eq(&pools[1].info) {
-        offer_pool = Asset {
-            amount: pools[1].amount.checked_sub(offer_asset.amount)?,
-            info: pools[1].info.clone(),
-        };
+        offer_pool = pools[1].clone();
         ask_pool = pools[0].clone();
     } else {
         return Err(ContractError::AssetMismatch {});
     }
 
-    let commission_rate = Decimal256::from_str(&pair_info.commission_rate)?;
+    // a fee-on-transfer token or a taxed denom can deliver less than declared
+    let offer_asset = Asset {
+        amount: query_received_amount(
+            deps.as_ref(),
+            env.contract.address.clone(),
+            offer_pool.amount,
+            &offer_asset,
+        )?,
+        info: offer_asset.info,
+    };
+
+    // accrue prices with the reserves before the trade
+    let reserves = if offer_pool.info.eq(&pools[0].info) {
+        [offer_pool.amount, ask_pool.amount]
+    } else {
+        [ask_pool.amount, offer_pool.amount]
+    };
+    update_price_cumulative(deps.storage, &env, reserves)?;
+
+    let commission_rate = compute_commission_rate(
+        deps.storage,
+        &env,
+        reserves,
+        Decimal256::from_str(&pair_info.commission_rate)?,
+    )?;
+    let commission_rate = trader_commission_rate(deps.storage, &sender, commission_rate)?;
     let operator_fee = Decimal256::from_str(&pair_info.operator_fee)?;
     let offer_amount = offer_asset.amount;
     let (mut return_amount, spread_amount, commission_amount, mut operator_fee_amount) =
-        compute_swap(
+        compute_pool_swap(
+            deps.storage,
+            &env,
+            &offer_pool.info,
             offer_pool.amount,
             ask_pool.amount,
             offer_amount,
@@ -503,6 +1763,12 @@ pub fn swap(
             operator_fee,
         )?;
 
+    assert_max_price_impact(
+        deps.storage,
+        return_amount + commission_amount + operator_fee_amount,
+        spread_amount,
+    )?;
+
     // check max spread limit if exist
     assert_max_spread(
         belief_price,
@@ -514,21 +1780,16 @@ pub fn swap(
 
     // check if there is no operator, refund  fee to the trader
     match operator {
-        Some(addr) => {
-            if !operator_fee_amount.is_zero() {
-                messages.push(
-                    Asset {
-                        info: ask_pool.info.clone(),
-                        amount: operator_fee_amount,
-                    }
-                    .into_msg(
-                        None,
-                        &deps.querier,
-                        deps.api.addr_humanize(&addr)?,
-                    )?,
-                )
-            }
-        }
+        Some(addr) => messages.extend(pay_operator_fee(
+            deps.storage,
+            &deps.querier,
+            deps.api,
+            &addr,
+            Asset {
+                info: ask_pool.info.clone(),
+                amount: operator_fee_amount,
+            },
+        )?),
         None => {
             return_amount += operator_fee_amount;
             operator_fee_amount = Uint128::zero();
@@ -556,21 +1817,325 @@ pub fn swap(
         )?);
     }
 
+    // the tax is deducted from the sent amount and stays in the pool
+    let reserves_after = reserves_after_swap(
+        &pools,
+        reserves,
+        &offer_asset.info,
+        offer_amount,
+        return_amount - tax_amount + operator_fee_amount,
+    )?;
+    let circuit_breaker_tripped = trip_circuit_breaker(
+        deps.storage,
+        &env,
+        reserves,
+        [reserves_after[0].amount, reserves_after[1].amount],
+    )?;
+    let total_share = query_total_share(&deps.querier, deps.api, &pair_info)?;
+    save_pool_snapshot(
+        deps.storage,
+        &env,
+        [reserves_after[0].amount, reserves_after[1].amount],
+        total_share,
+    )?;
+    let hook_msgs = swap_hook_msgs(
+        deps.storage,
+        SwapHookMsg::SwapHook {
+            trader: sender.clone(),
+            receiver: receiver.clone(),
+            offer_asset: offer_asset.clone(),
+            return_asset,
+            commission_amount,
+            operator_fee_amount,
+            reserves: reserves_after,
+        },
+    )?;
+
     // 1. send collateral token from the contract to a user
     // 2. send inactive commission to collector
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        ("action", "swap"),
-        ("sender", sender.as_str()),
-        ("receiver", receiver.as_str()),
-        ("offer_asset", &offer_asset.info.to_string()),
-        ("ask_asset", &ask_pool.info.to_string()),
-        ("offer_amount", &offer_amount.to_string()),
-        ("return_amount", &return_amount.to_string()),
-        ("tax_amount", &tax_amount.to_string()),
-        ("spread_amount", &spread_amount.to_string()),
-        ("commission_amount", &commission_amount.to_string()),
-        ("operator_fee_amount", &operator_fee_amount.to_string()),
-    ]))
+    // 3. notify the swap hooks
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "swap"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("offer_asset", &offer_asset.info.to_string()),
+            ("ask_asset", &ask_pool.info.to_string()),
+            ("offer_amount", &offer_amount.to_string()),
+            ("return_amount", &return_amount.to_string()),
+            ("tax_amount", &tax_amount.to_string()),
+            ("spread_amount", &spread_amount.to_string()),
+            ("commission_amount", &commission_amount.to_string()),
+            ("operator_fee_amount", &operator_fee_amount.to_string()),
+            (
+                "circuit_breaker_tripped",
+                &circuit_breaker_tripped.to_string(),
+            ),
+        ]))
+}
+
+/// Reserves after a swap from the reserves before it, in the pair's asset order
+fn reserves_after_swap(
+    pools: &[Asset; 2],
+    reserves: [Uint128; 2],
+    offer_info: &AssetInfo,
+    offer_amount: Uint128,
+    ask_out_amount: Uint128,
+) -> StdResult<[Asset; 2]> {
+    let (offer_index, ask_index) = if offer_info.eq(&pools[0].info) {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    let mut amounts = reserves;
+    amounts[offer_index] = amounts[offer_index].checked_add(offer_amount)?;
+    amounts[ask_index] = amounts[ask_index].checked_sub(ask_out_amount)?;
+
+    Ok([
+        Asset {
+            info: pools[0].info.clone(),
+            amount: amounts[0],
+        },
+        Asset {
+            info: pools[1].info.clone(),
+            amount: amounts[1],
+        },
+    ])
+}
+
+/// Records the reserves and total share after a change, the latest reserves
+/// are the tracked ones
+pub fn save_pool_snapshot(
+    storage: &mut dyn Storage,
+    env: &Env,
+    reserves: [Uint128; 2],
+    total_share: Uint128,
+) -> StdResult<()> {
+    POOL_SNAPSHOT.save(
+        storage,
+        &PoolSnapshot {
+            reserves,
+            total_share,
+        },
+        env.block.height,
+    )
+}
+
+/// Submessages notifying every swap hook, their errors are caught in the reply
+pub fn swap_hook_msgs(storage: &dyn Storage, hook_msg: SwapHookMsg) -> StdResult<Vec<SubMsg>> {
+    let hooks = SWAP_HOOKS.may_load(storage)?.unwrap_or_default();
+    if hooks.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let msg = to_json_binary(&hook_msg)?;
+    Ok(hooks
+        .into_iter()
+        .map(|hook| {
+            SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: hook.to_string(),
+                    msg: msg.clone(),
+                    funds: vec![],
+                },
+                SWAP_HOOK_REPLY_ID,
+            )
+            .with_gas_limit(SWAP_HOOK_GAS_LIMIT)
+        })
+        .collect())
+}
+
+/// CONTRACT - the offer asset must already be transferred, `offer_asset.amount` is the max offer amount
+pub fn swap_exact_out(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_amount: Uint128,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    // check pool is only open for whitelisted trader
+    assert_is_open_for_whitelisted_trader(deps.as_ref(), sender.clone())?;
+    assert_no_flash_swap(deps.as_ref())?;
+    assert_not_paused(deps.storage, &[PairAction::Swap])?;
+
+    if ask_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let operator = OPERATOR.may_load(deps.storage)?;
+
+    let pools: [Asset; 2] = query_reserves_before(
+        deps.as_ref(),
+        &pair_info,
+        env.contract.address.clone(),
+        &[offer_asset.clone()],
+    )?;
+
+    let reserves = [pools[0].amount, pools[1].amount];
+    let (offer_pool, ask_pool) = if offer_asset.info.eq(&pools[0].info) {
+        (pools[0].amount, pools[1].clone())
+    } else if offer_asset.info.eq(&pools[1].info) {
+        (pools[1].amount, pools[0].clone())
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    // a fee-on-transfer token or a taxed denom can deliver less than declared
+    let offer_asset = Asset {
+        amount: query_received_amount(
+            deps.as_ref(),
+            env.contract.address.clone(),
+            offer_pool,
+            &offer_asset,
+        )?,
+        info: offer_asset.info,
+    };
+
+    // accrue prices with the reserves before the trade
+    update_price_cumulative(deps.storage, &env, reserves)?;
+
+    let commission_rate = compute_commission_rate(
+        deps.storage,
+        &env,
+        reserves,
+        Decimal256::from_str(&pair_info.commission_rate)?,
+    )?;
+    let commission_rate = trader_commission_rate(deps.storage, &sender, commission_rate)?;
+    // operator fee is only charged when there is an operator to receive it
+    let operator_fee = match operator {
+        Some(_) => Decimal256::from_str(&pair_info.operator_fee)?,
+        None => Decimal256::zero(),
+    };
+    let (offer_amount, spread_amount, commission_amount, operator_fee_amount) =
+        compute_pool_exact_out(
+            deps.storage,
+            &env,
+            &offer_asset.info,
+            offer_pool,
+            ask_pool.amount,
+            ask_amount,
+            commission_rate,
+            operator_fee,
+        )?;
+
+    assert_max_price_impact(
+        deps.storage,
+        ask_amount + commission_amount + operator_fee_amount,
+        spread_amount,
+    )?;
+
+    if offer_amount > offer_asset.amount {
+        return Err(ContractError::MaxOfferAssertion {
+            max_offer_amount: offer_asset.amount,
+            offer_amount,
+        });
+    }
+
+    if let Some(addr) = operator {
+        messages.extend(pay_operator_fee(
+            deps.storage,
+            &deps.querier,
+            deps.api,
+            &addr,
+            Asset {
+                info: ask_pool.info.clone(),
+                amount: operator_fee_amount,
+            },
+        )?);
+    }
+
+    // rounding dust of the return amount stays in the pool
+    let return_asset = Asset {
+        info: ask_pool.info.clone(),
+        amount: ask_amount,
+    };
+
+    let oracle_contract = OracleContract(deps.api.addr_humanize(&pair_info.oracle_addr)?);
+
+    let tax_amount = return_asset.compute_tax(&oracle_contract, &deps.querier)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+
+    messages.push(return_asset.into_msg(
+        Some(&oracle_contract),
+        &deps.querier,
+        receiver.clone(),
+    )?);
+
+    // refund the unused offer amount
+    let refund_amount = offer_asset.amount - offer_amount;
+    if !refund_amount.is_zero() {
+        messages.push(
+            Asset {
+                info: offer_asset.info.clone(),
+                amount: refund_amount,
+            }
+            .into_msg(None, &deps.querier, sender.clone())?,
+        );
+    }
+
+    let reserves_after = reserves_after_swap(
+        &pools,
+        reserves,
+        &offer_asset.info,
+        offer_amount,
+        ask_amount - tax_amount + operator_fee_amount,
+    )?;
+    let circuit_breaker_tripped = trip_circuit_breaker(
+        deps.storage,
+        &env,
+        reserves,
+        [reserves_after[0].amount, reserves_after[1].amount],
+    )?;
+    let total_share = query_total_share(&deps.querier, deps.api, &pair_info)?;
+    save_pool_snapshot(
+        deps.storage,
+        &env,
+        [reserves_after[0].amount, reserves_after[1].amount],
+        total_share,
+    )?;
+    let hook_msgs = swap_hook_msgs(
+        deps.storage,
+        SwapHookMsg::SwapHook {
+            trader: sender.clone(),
+            receiver: receiver.clone(),
+            offer_asset: Asset {
+                info: offer_asset.info.clone(),
+                amount: offer_amount,
+            },
+            return_asset,
+            commission_amount,
+            operator_fee_amount,
+            reserves: reserves_after,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(hook_msgs)
+        .add_attributes(vec![
+            ("action", "swap_exact_out"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("offer_asset", &offer_asset.info.to_string()),
+            ("ask_asset", &ask_pool.info.to_string()),
+            ("offer_amount", &offer_amount.to_string()),
+            ("refund_amount", &refund_amount.to_string()),
+            ("return_amount", &ask_amount.to_string()),
+            ("tax_amount", &tax_amount.to_string()),
+            ("spread_amount", &spread_amount.to_string()),
+            ("commission_amount", &commission_amount.to_string()),
+            ("operator_fee_amount", &operator_fee_amount.to_string()),
+            (
+                "circuit_breaker_tripped",
+                &circuit_breaker_tripped.to_string(),
+            ),
+        ]))
 }
 
 fn execute_register_traders(
@@ -586,55 +2151,379 @@ fn execute_register_traders(
         WHITELISTED_TRADERS.save(deps.storage, trader, &true)?;
     }
 
-    Ok(Response::new().add_attributes(vec![("action", "register_trader")]))
+    Ok(Response::new()
+        .add_attributes(vec![("action", "register_trader")])
+        .add_events(whitelist_events("register_trader", "trader", &traders)))
+}
+
+fn execute_deregister_traders(
+    deps: DepsMut,
+    info: MessageInfo,
+    traders: Vec<Addr>,
+) -> Result<Response, ContractError> {
+    // check permission
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+
+    // remove traders from whitelist
+    for trader in &traders {
+        WHITELISTED_TRADERS.remove(deps.storage, trader);
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![("action", "deregister_trader")])
+        .add_events(whitelist_events("deregister_trader", "trader", &traders)))
+}
+
+fn execute_register_withdraw_lps(
+    deps: DepsMut,
+    info: MessageInfo,
+    providers: Vec<Addr>,
+) -> Result<Response, ContractError> {
+    // check permission
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+
+    // add providers to whitelist
+    for provider in &providers {
+        WHITELISTED_WITHDRAW_LPS.save(deps.storage, provider, &true)?;
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![("action", "register_whitelist_lp")])
+        .add_events(whitelist_events(
+            "register_whitelist_lp",
+            "provider",
+            &providers,
+        )))
+}
+
+fn execute_deregister_withdraw_lps(
+    deps: DepsMut,
+    info: MessageInfo,
+    providers: Vec<Addr>,
+) -> Result<Response, ContractError> {
+    // check permission
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+
+    // remove providers from whitelist
+    for provider in &providers {
+        WHITELISTED_WITHDRAW_LPS.remove(deps.storage, provider);
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![("action", "deregister_whitelist_lp")])
+        .add_events(whitelist_events(
+            "deregister_whitelist_lp",
+            "provider",
+            &providers,
+        )))
+}
+
+/// One event per address so indexers can follow whitelist changes
+fn whitelist_events(ty: &str, key: &str, addresses: &[Addr]) -> Vec<Event> {
+    addresses
+        .iter()
+        .map(|addr| Event::new(ty).add_attribute(key, addr))
+        .collect()
+}
+
+fn execute_add_swap_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    hook: Addr,
+) -> Result<Response, ContractError> {
+    // check permission
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+
+    let hook = deps.api.addr_validate(hook.as_str())?;
+    let mut hooks = SWAP_HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if hooks.contains(&hook) {
+        return Err(ContractError::SwapHookAlreadyRegistered {
+            hook: hook.to_string(),
+        });
+    }
+    if hooks.len() as u32 >= MAX_SWAP_HOOKS {
+        return Err(ContractError::TooManySwapHooks {
+            max: MAX_SWAP_HOOKS,
+        });
+    }
+
+    hooks.push(hook.clone());
+    SWAP_HOOKS.save(deps.storage, &hooks)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "add_swap_hook"), ("hook", hook.as_str())]))
+}
+
+fn execute_remove_swap_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    hook: Addr,
+) -> Result<Response, ContractError> {
+    // check permission
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+
+    let mut hooks = SWAP_HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if !hooks.contains(&hook) {
+        return Err(ContractError::SwapHookNotFound {
+            hook: hook.to_string(),
+        });
+    }
+
+    hooks.retain(|registered| *registered != hook);
+    SWAP_HOOKS.save(deps.storage, &hooks)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "remove_swap_hook"),
+        ("hook", hook.as_str()),
+    ]))
+}
+
+fn execute_update_pause_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    swap: Option<bool>,
+    provide: Option<bool>,
+    withdraw: Option<bool>,
+) -> Result<Response, ContractError> {
+    // the factory guardian pauses through the factory
+    if FACTORY.may_load(deps.storage)?.as_ref() != Some(&info.sender) {
+        assert_admin(deps.as_ref(), info.sender.to_string())?;
+    }
+
+    let mut status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    if let Some(swap) = swap {
+        status.swap = swap;
+    }
+    if let Some(provide) = provide {
+        status.provide = provide;
+    }
+    if let Some(withdraw) = withdraw {
+        status.withdraw = withdraw;
+    }
+    PAUSE_STATUS.save(deps.storage, &status)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_pause_status"),
+        ("swap_paused", &status.swap.to_string()),
+        ("provide_paused", &status.provide.to_string()),
+        ("withdraw_paused", &status.withdraw.to_string()),
+    ]))
+}
+
+fn execute_update_pair_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    status: PairStatus,
+    successor: Option<Addr>,
+) -> Result<Response, ContractError> {
+    // the factory registry owns the status, the pair only mirrors it
+    if FACTORY.may_load(deps.storage)?.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut pair_info = PAIR_INFO.load(deps.storage)?;
+    pair_info.status = status.clone();
+    pair_info.successor = successor
+        .as_ref()
+        .map(|successor| deps.api.addr_canonicalize(successor.as_str()))
+        .transpose()?;
+    PAIR_INFO.save(deps.storage, &pair_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_pair_status"),
+        ("status", &format!("{:?}", status)),
+        (
+            "successor",
+            successor
+                .as_ref()
+                .map(|successor| successor.as_str())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+fn execute_update_circuit_breaker(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_price_change: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    // check permission
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+
+    match max_price_change {
+        Some(max_price_change) => {
+            if max_price_change.is_zero() {
+                return Err(ContractError::InvalidMaxPriceChange {});
+            }
+            MAX_PRICE_CHANGE.save(deps.storage, &max_price_change)?;
+        }
+        None => MAX_PRICE_CHANGE.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_circuit_breaker"),
+        (
+            "max_price_change",
+            &max_price_change.map_or("none".to_string(), |v| v.to_string()),
+        ),
+    ]))
+}
+
+fn execute_update_max_price_impact(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_price_impact: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    // check permission
+    assert_admin(deps.as_ref(), info.sender.to_string())?;
+
+    match max_price_impact {
+        Some(max_price_impact) => {
+            if max_price_impact.is_zero() || max_price_impact >= Decimal::one() {
+                return Err(ContractError::InvalidMaxPriceImpact {});
+            }
+            MAX_PRICE_IMPACT.save(deps.storage, &max_price_impact)?;
+        }
+        None => MAX_PRICE_IMPACT.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_max_price_impact"),
+        (
+            "max_price_impact",
+            &max_price_impact.map_or("none".to_string(), |v| v.to_string()),
+        ),
+    ]))
 }
 
-fn execute_deregister_traders(
-    deps: DepsMut,
-    info: MessageInfo,
-    traders: Vec<Addr>,
-) -> Result<Response, ContractError> {
-    // check permission
-    assert_admin(deps.as_ref(), info.sender.to_string())?;
+/// Price impact is the spread relative to the return at the pre-trade spot price,
+/// `return_amount` is taken before fees
+fn assert_max_price_impact(
+    storage: &dyn Storage,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+) -> Result<(), ContractError> {
+    let max_price_impact = match MAX_PRICE_IMPACT.may_load(storage)? {
+        Some(max_price_impact) => max_price_impact,
+        None => return Ok(()),
+    };
+    let spot_return = return_amount.checked_add(spread_amount)?;
+    if spot_return.is_zero() {
+        return Ok(());
+    }
 
-    // remove traders from whitelist
-    for trader in &traders {
-        WHITELISTED_TRADERS.save(deps.storage, trader, &false)?;
+    let price_impact = Decimal::from_ratio(spread_amount, spot_return);
+    if price_impact > max_price_impact {
+        return Err(ContractError::PriceImpactTooHigh {
+            price_impact,
+            max_price_impact,
+        });
     }
 
-    Ok(Response::new().add_attributes(vec![("action", "deregister_trader")]))
+    Ok(())
 }
 
-fn execute_register_withdraw_lps(
+fn execute_update_trader_fee_tier(
     deps: DepsMut,
     info: MessageInfo,
-    providers: Vec<Addr>,
+    trader: Addr,
+    commission_rate: Option<String>,
 ) -> Result<Response, ContractError> {
-    // check permission
     assert_admin(deps.as_ref(), info.sender.to_string())?;
+    let trader = deps.api.addr_validate(trader.as_str())?;
+
+    match &commission_rate {
+        Some(commission_rate) => {
+            if Decimal256::from_str(commission_rate)? >= Decimal256::one() {
+                return Err(
+                    StdError::generic_err("Fee tier commission rate must be less than 1").into(),
+                );
+            }
+            TRADER_FEE_TIERS.save(deps.storage, &trader, commission_rate)?;
+        }
+        None => TRADER_FEE_TIERS.remove(deps.storage, &trader),
+    }
 
-    // add traders to whitelist
-    for provider in &providers {
-        WHITELISTED_WITHDRAW_LPS.save(deps.storage, provider, &true)?;
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_trader_fee_tier"),
+        ("trader", trader.as_str()),
+        (
+            "commission_rate",
+            commission_rate.as_deref().unwrap_or("none"),
+        ),
+    ]))
+}
+
+/// Commission rate charged to `trader`, a fee tier only ever lowers the pool rate
+pub fn trader_commission_rate(
+    storage: &dyn Storage,
+    trader: &Addr,
+    commission_rate: Decimal256,
+) -> StdResult<Decimal256> {
+    match TRADER_FEE_TIERS.may_load(storage, trader)? {
+        Some(tier) => Ok(Decimal256::from_str(&tier)?.min(commission_rate)),
+        None => Ok(commission_rate),
     }
+}
 
-    Ok(Response::new().add_attributes(vec![("action", "register_whitelist_lp")]))
+#[derive(Clone, Copy)]
+enum PairAction {
+    Swap,
+    Provide,
+    Withdraw,
 }
 
-fn execute_deregister_withdraw_lps(
-    deps: DepsMut,
-    info: MessageInfo,
-    providers: Vec<Addr>,
-) -> Result<Response, ContractError> {
-    // check permission
-    assert_admin(deps.as_ref(), info.sender.to_string())?;
+fn assert_not_paused(storage: &dyn Storage, actions: &[PairAction]) -> Result<(), ContractError> {
+    let status = PAUSE_STATUS.may_load(storage)?.unwrap_or_default();
+    for action in actions {
+        match action {
+            PairAction::Swap if status.swap => return Err(ContractError::SwapPaused {}),
+            PairAction::Provide if status.provide => return Err(ContractError::ProvidePaused {}),
+            PairAction::Withdraw if status.withdraw => {
+                return Err(ContractError::WithdrawPaused {})
+            }
+            _ => {}
+        }
+    }
 
-    // add traders to whitelist
-    for provider in &providers {
-        WHITELISTED_WITHDRAW_LPS.save(deps.storage, provider, &false)?;
+    Ok(())
+}
+
+/// Pause swaps when a swap moved the spot price more than the circuit breaker allows,
+/// the swap itself still goes through
+fn trip_circuit_breaker(
+    storage: &mut dyn Storage,
+    env: &Env,
+    reserves_before: [Uint128; 2],
+    reserves_after: [Uint128; 2],
+) -> StdResult<bool> {
+    let max_price_change = match MAX_PRICE_CHANGE.may_load(storage)? {
+        Some(max_price_change) => max_price_change,
+        None => return Ok(false),
+    };
+    if reserves_before
+        .iter()
+        .chain(reserves_after.iter())
+        .any(|reserve| reserve.is_zero())
+    {
+        return Ok(false);
+    }
+
+    let (price_before, _) = compute_pool_spot_prices(storage, env, reserves_before)?;
+    let (price_after, _) = compute_pool_spot_prices(storage, env, reserves_after)?;
+    let price_diff = if price_after > price_before {
+        price_after - price_before
+    } else {
+        price_before - price_after
+    };
+    if price_diff / price_before <= Decimal256::from(max_price_change) {
+        return Ok(false);
     }
 
-    Ok(Response::new().add_attributes(vec![("action", "deregister_whitelist_lp")]))
+    let mut status = PAUSE_STATUS.may_load(storage)?.unwrap_or_default();
+    status.swap = true;
+    PAUSE_STATUS.save(storage, &status)?;
+
+    Ok(true)
 }
 
 fn assert_admin(deps: Deps, sender: String) -> Result<(), ContractError> {
@@ -653,6 +2542,14 @@ fn assert_admin(deps: Deps, sender: String) -> Result<(), ContractError> {
     Ok(())
 }
 
+fn assert_no_flash_swap(deps: Deps) -> Result<(), ContractError> {
+    if FLASH_SWAP.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::FlashSwapInProgress {});
+    }
+
+    Ok(())
+}
+
 fn assert_is_open_for_whitelisted_trader(deps: Deps, trader: Addr) -> Result<(), ContractError> {
     let is_whitelisted = WHITELISTED.may_load(deps.storage)?.unwrap_or(false);
 
@@ -701,21 +2598,66 @@ fn assert_is_open_for_whitelisted_withdraw_lp(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
         QueryMsg::Pair {} => Ok(to_json_binary(&query_pair_info(deps)?)?),
         QueryMsg::Pool {} => Ok(to_json_binary(&query_pool(deps)?)?),
-        QueryMsg::Simulation { offer_asset } => {
-            Ok(to_json_binary(&query_simulation(deps, offer_asset)?)?)
-        }
-        QueryMsg::ReverseSimulation { ask_asset } => {
-            Ok(to_json_binary(&query_reverse_simulation(deps, ask_asset)?)?)
+        QueryMsg::PoolAtHeight { height } => {
+            Ok(to_json_binary(&query_pool_at_height(deps, height)?)?)
         }
+        QueryMsg::Simulation { offer_asset } => Ok(to_json_binary(&query_simulation(
+            deps,
+            env,
+            offer_asset,
+            None,
+        )?)?),
+        QueryMsg::TraderSimulation {
+            offer_asset,
+            trader,
+        } => Ok(to_json_binary(&query_simulation(
+            deps,
+            env,
+            offer_asset,
+            Some(trader),
+        )?)?),
+        QueryMsg::TraderFeeTier { trader } => Ok(to_json_binary(
+            &TRADER_FEE_TIERS.may_load(deps.storage, &trader)?,
+        )?),
+        QueryMsg::ReverseSimulation { ask_asset } => Ok(to_json_binary(
+            &query_reverse_simulation(deps, env, ask_asset)?,
+        )?),
         QueryMsg::TraderIsWhitelisted { trader } => {
             Ok(to_json_binary(&query_trader_is_whitelisted(deps, trader)?)?)
         }
+        QueryMsg::WithdrawLpIsWhitelisted { provider } => Ok(to_json_binary(
+            &assert_is_open_for_whitelisted_withdraw_lp(deps, provider).is_ok(),
+        )?),
+        QueryMsg::WhitelistStatus {} => Ok(to_json_binary(
+            &WHITELISTED.may_load(deps.storage)?.unwrap_or(false),
+        )?),
+        QueryMsg::WhitelistedTraders { start_after, limit } => Ok(to_json_binary(
+            &read_whitelisted(deps.storage, WHITELISTED_TRADERS, start_after, limit)?,
+        )?),
+        QueryMsg::WhitelistedWithdrawLps { start_after, limit } => Ok(to_json_binary(
+            &read_whitelisted(deps.storage, WHITELISTED_WITHDRAW_LPS, start_after, limit)?,
+        )?),
         QueryMsg::Admin {} => Ok(to_json_binary(&query_admin(deps)?)?),
         QueryMsg::Operator {} => Ok(to_json_binary(&query_operator(deps)?)?),
+        QueryMsg::FlashFee {} => Ok(to_json_binary(&query_flash_fee(deps)?)?),
+        QueryMsg::ProtocolFees {} => Ok(to_json_binary(&query_protocol_fees(deps)?)?),
+        QueryMsg::Status {} => Ok(to_json_binary(&query_status(deps)?)?),
+        QueryMsg::MaxPriceImpact {} => {
+            Ok(to_json_binary(&MAX_PRICE_IMPACT.may_load(deps.storage)?)?)
+        }
+        QueryMsg::SwapHooks {} => Ok(to_json_binary(
+            &SWAP_HOOKS.may_load(deps.storage)?.unwrap_or_default(),
+        )?),
+        QueryMsg::DynamicFee {} => Ok(to_json_binary(&DYNAMIC_FEE.may_load(deps.storage)?)?),
+        QueryMsg::StableSwapConfig {} => Ok(to_json_binary(&query_stable_swap_config(deps, env)?)?),
+        QueryMsg::PriceCumulative {} => Ok(to_json_binary(&query_price_cumulative(deps, env)?)?),
+        QueryMsg::Twap { window_seconds } => {
+            Ok(to_json_binary(&query_twap(deps, env, window_seconds)?)?)
+        }
     }
 }
 
@@ -723,6 +2665,17 @@ fn query_trader_is_whitelisted(deps: Deps, trader: Addr) -> StdResult<bool> {
     Ok(assert_is_open_for_whitelisted_trader(deps, trader).is_ok())
 }
 
+fn query_status(deps: Deps) -> StdResult<StatusResponse> {
+    let status = PAUSE_STATUS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(StatusResponse {
+        swap_paused: status.swap,
+        provide_paused: status.provide,
+        withdraw_paused: status.withdraw,
+        max_price_change: MAX_PRICE_CHANGE.may_load(deps.storage)?,
+        factory: FACTORY.may_load(deps.storage)?,
+    })
+}
+
 fn query_admin(deps: Deps) -> StdResult<String> {
     let admin = ADMIN.may_load(deps.storage)?;
     Ok(match admin {
@@ -739,6 +2692,131 @@ fn query_operator(deps: Deps) -> StdResult<String> {
     })
 }
 
+fn query_protocol_fees(deps: Deps) -> StdResult<ProtocolFeesResponse> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let fee_of = |asset_info: &AssetInfoRaw| -> StdResult<Asset> {
+        let info = asset_info.to_normal(deps.api)?;
+        let amount = PROTOCOL_FEES
+            .may_load(deps.storage, &info.to_string())?
+            .unwrap_or_default();
+        Ok(Asset { info, amount })
+    };
+
+    Ok(ProtocolFeesResponse {
+        accrual_enabled: PROTOCOL_FEE_ACCRUAL
+            .may_load(deps.storage)?
+            .unwrap_or(false),
+        fees: [
+            fee_of(&pair_info.asset_infos[0])?,
+            fee_of(&pair_info.asset_infos[1])?,
+        ],
+    })
+}
+
+fn query_flash_fee(deps: Deps) -> StdResult<String> {
+    Ok(FLASH_FEE
+        .may_load(deps.storage)?
+        .unwrap_or(DEFAULT_FLASH_FEE.to_string()))
+}
+
+fn query_stable_swap_config(
+    deps: Deps,
+    env: Env,
+) -> Result<StableSwapConfigResponse, ContractError> {
+    let config = STABLE_SWAP_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NotStableSwapPair {})?;
+
+    Ok(StableSwapConfigResponse {
+        amp: config.current_amp(env.block.time.seconds()),
+        init_amp: config.init_amp,
+        init_amp_time: config.init_amp_time,
+        future_amp: config.future_amp,
+        future_amp_time: config.future_amp_time,
+    })
+}
+
+pub fn query_price_cumulative(
+    deps: Deps,
+    env: Env,
+) -> Result<PriceCumulativeResponse, ContractError> {
+    let cumulative = current_price_cumulative(deps, &env)?;
+
+    Ok(PriceCumulativeResponse {
+        price0_cumulative: cumulative.price0_cumulative,
+        price1_cumulative: cumulative.price1_cumulative,
+        block_time: cumulative.timestamp,
+    })
+}
+
+pub fn query_twap(
+    deps: Deps,
+    env: Env,
+    window_seconds: u64,
+) -> Result<TwapResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    let pools: [Asset; 2] = query_reserves(deps, &pair_info, contract_addr)?;
+
+    compute_twap(
+        deps.storage,
+        &env,
+        [pools[0].amount, pools[1].amount],
+        window_seconds,
+    )
+}
+
+/// Time-weighted average prices over the last `window_seconds`, `reserves` must be
+/// the reserves held since the last accumulator update
+pub fn compute_twap(
+    storage: &dyn Storage,
+    env: &Env,
+    reserves: [Uint128; 2],
+    window_seconds: u64,
+) -> Result<TwapResponse, ContractError> {
+    if window_seconds == 0 {
+        return Err(ContractError::InvalidTwapWindow {});
+    }
+
+    let current = accumulate_prices(storage, env, reserves)?;
+    let target_time = current
+        .timestamp
+        .checked_sub(window_seconds)
+        .ok_or(ContractError::TwapWindowTooLong { window_seconds })?;
+
+    // binary search the newest observation taken at or before the target time
+    let count = OBSERVATION_COUNT.may_load(storage)?.unwrap_or_default();
+    let mut low = count.saturating_sub(OBSERVATION_CAPACITY);
+    let mut high = count;
+    let mut start: Option<Observation> = None;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let observation = OBSERVATIONS.load(storage, mid % OBSERVATION_CAPACITY)?;
+        if observation.timestamp <= target_time {
+            start = Some(observation);
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    let start = start.ok_or(ContractError::TwapWindowTooLong { window_seconds })?;
+
+    let elapsed = current.timestamp - start.timestamp;
+    let elapsed_seconds = Decimal256::from_ratio(elapsed, 1u64);
+
+    Ok(TwapResponse {
+        price0_average: current
+            .price0_cumulative
+            .checked_sub(start.price0_cumulative)?
+            / elapsed_seconds,
+        price1_average: current
+            .price1_cumulative
+            .checked_sub(start.price1_cumulative)?
+            / elapsed_seconds,
+        window_seconds: elapsed,
+    })
+}
+
 pub fn query_pair_info(deps: Deps) -> StdResult<PairResponse> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
     pair_info
@@ -746,18 +2824,108 @@ pub fn query_pair_info(deps: Deps) -> StdResult<PairResponse> {
         .map(|info| PairResponse { info })
 }
 
+pub fn query_pool_at_height(deps: Deps, height: u64) -> StdResult<Option<PoolAtHeightResponse>> {
+    // snapshots are read at the start of a block, so take the next one
+    let snapshot = POOL_SNAPSHOT.may_load_at_height(deps.storage, height.saturating_add(1))?;
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+
+    snapshot
+        .map(|snapshot| {
+            Ok(PoolAtHeightResponse {
+                assets: [
+                    Asset {
+                        info: pair_info.asset_infos[0].to_normal(deps.api)?,
+                        amount: snapshot.reserves[0],
+                    },
+                    Asset {
+                        info: pair_info.asset_infos[1].to_normal(deps.api)?,
+                        amount: snapshot.reserves[1],
+                    },
+                ],
+                total_share: snapshot.total_share,
+            })
+        })
+        .transpose()
+}
+
+/// Total LP supply, whether the share is a cw20 token or a token factory denom
+pub fn query_total_share(
+    querier: &QuerierWrapper,
+    api: &dyn Api,
+    pair_info: &PairInfoRaw,
+) -> StdResult<Uint128> {
+    match &pair_info.liquidity_denom {
+        Some(denom) => Ok(querier.query_supply(denom.to_string())?.amount),
+        None => query_supply(querier, api.addr_humanize(&pair_info.liquidity_token)?),
+    }
+}
+
+fn mint_lp_msg(
+    api: &dyn Api,
+    pair_info: &PairInfoRaw,
+    contract_addr: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    Ok(match &pair_info.liquidity_denom {
+        Some(denom) => mint_msg(contract_addr, denom, amount, recipient),
+        None => WasmMsg::Execute {
+            contract_addr: api.addr_humanize(&pair_info.liquidity_token)?.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+/// Burns LP shares already held by the pair
+fn burn_lp_msg(
+    api: &dyn Api,
+    pair_info: &PairInfoRaw,
+    contract_addr: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    Ok(match &pair_info.liquidity_denom {
+        Some(denom) => burn_msg(contract_addr, denom, amount),
+        None => WasmMsg::Execute {
+            contract_addr: api.addr_humanize(&pair_info.liquidity_token)?.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+/// LP shares sent as funds, the pair must mint a token factory denom
+fn sent_native_lp_amount(
+    storage: &dyn Storage,
+    info: &MessageInfo,
+) -> Result<Uint128, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(storage)?;
+    let denom = pair_info
+        .liquidity_denom
+        .ok_or(ContractError::Unauthorized {})?;
+    match info.funds.as_slice() {
+        [coin] if coin.denom == denom => Ok(coin.amount),
+        _ => Err(ContractError::InvalidFunds {}),
+    }
+}
+
 pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
     let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let assets: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
-    let total_share: Uint128 = query_supply(
-        &deps.querier,
-        deps.api.addr_humanize(&pair_info.liquidity_token)?,
-    )?;
+    let assets: [Asset; 2] = query_reserves(deps, &pair_info, contract_addr)?;
+    let total_share: Uint128 = query_total_share(&deps.querier, deps.api, &pair_info)?;
+    // pairs created before the lock have nothing locked
+    let locked_share = LOCKED_SHARE.may_load(deps.storage)?.unwrap_or_default();
 
     let resp = PoolResponse {
         assets,
         total_share,
+        locked_share,
     };
 
     Ok(resp)
@@ -765,12 +2933,14 @@ pub fn query_pool(deps: Deps) -> Result<PoolResponse, ContractError> {
 
 pub fn query_simulation(
     deps: Deps,
+    env: Env,
     offer_asset: Asset,
+    trader: Option<Addr>,
 ) -> Result<SimulationResponse, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
     let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let pools: [Asset; 2] = query_reserves(deps, &pair_info, contract_addr)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
@@ -784,10 +2954,22 @@ pub fn query_simulation(
         return Err(ContractError::AssetMismatch {});
     }
 
-    let commission_rate = Decimal256::from_str(&pair_info.commission_rate)?;
+    let commission_rate = compute_commission_rate(
+        deps.storage,
+        &env,
+        [pools[0].amount, pools[1].amount],
+        Decimal256::from_str(&pair_info.commission_rate)?,
+    )?;
+    let commission_rate = match trader {
+        Some(trader) => trader_commission_rate(deps.storage, &trader, commission_rate)?,
+        None => commission_rate,
+    };
     let operator_fee = Decimal256::from_str(&pair_info.operator_fee)?;
 
-    let (return_amount, spread_amount, commission_amount, operator_fee_amount) = compute_swap(
+    let (return_amount, spread_amount, commission_amount, operator_fee_amount) = compute_pool_swap(
+        deps.storage,
+        &env,
+        &offer_pool.info,
         offer_pool.amount,
         ask_pool.amount,
         offer_asset.amount,
@@ -795,22 +2977,33 @@ pub fn query_simulation(
         operator_fee,
     )?;
 
+    // the tax is deducted from the return when it is sent
+    let oracle_contract = OracleContract(deps.api.addr_humanize(&pair_info.oracle_addr)?);
+    let tax_amount = Asset {
+        info: ask_pool.info,
+        amount: return_amount,
+    }
+    .compute_tax(&oracle_contract, &deps.querier)?;
+
     Ok(SimulationResponse {
         return_amount,
         spread_amount,
         commission_amount,
         operator_fee_amount,
+        commission_rate,
+        tax_amount,
     })
 }
 
 pub fn query_reverse_simulation(
     deps: Deps,
+    env: Env,
     ask_asset: Asset,
 ) -> Result<ReverseSimulationResponse, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
     let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let pools: [Asset; 2] = query_reserves(deps, &pair_info, contract_addr)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
@@ -824,8 +3017,16 @@ pub fn query_reverse_simulation(
         return Err(ContractError::AssetMismatch {});
     }
 
-    let commission_rate = Decimal256::from_str(&pair_info.commission_rate)?;
-    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+    let commission_rate = compute_commission_rate(
+        deps.storage,
+        &env,
+        [pools[0].amount, pools[1].amount],
+        Decimal256::from_str(&pair_info.commission_rate)?,
+    )?;
+    let (offer_amount, spread_amount, commission_amount) = compute_pool_offer_amount(
+        deps.storage,
+        &env,
+        &offer_pool.info,
         offer_pool.amount,
         ask_pool.amount,
         ask_asset.amount,
@@ -839,6 +3040,416 @@ pub fn query_reverse_simulation(
     })
 }
 
+/// Computes the swap with the StableSwap curve if the pair was created with
+/// StableSwap params, otherwise with the constant product curve
+pub fn compute_pool_swap(
+    storage: &dyn Storage,
+    env: &Env,
+    offer_info: &AssetInfo,
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    offer_amount: Uint128,
+    commission_rate: Decimal256,
+    operator_fee: Decimal256,
+) -> Result<(Uint128, Uint128, Uint128, Uint128), ContractError> {
+    match STABLE_SWAP_CONFIG.may_load(storage)? {
+        Some(config) => compute_stable_swap(
+            offer_pool,
+            ask_pool,
+            offer_amount,
+            config.current_amp(env.block.time.seconds()),
+            config.decimals(offer_info),
+            commission_rate,
+            operator_fee,
+        ),
+        None => compute_swap(
+            offer_pool,
+            ask_pool,
+            offer_amount,
+            commission_rate,
+            operator_fee,
+        ),
+    }
+}
+
+pub fn compute_pool_offer_amount(
+    storage: &dyn Storage,
+    env: &Env,
+    offer_info: &AssetInfo,
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    ask_amount: Uint128,
+    commission_rate: Decimal256,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    match STABLE_SWAP_CONFIG.may_load(storage)? {
+        Some(config) => compute_stable_offer_amount(
+            offer_pool,
+            ask_pool,
+            ask_amount,
+            config.current_amp(env.block.time.seconds()),
+            config.decimals(offer_info),
+            commission_rate,
+        ),
+        None => compute_offer_amount(offer_pool, ask_pool, ask_amount, commission_rate),
+    }
+}
+
+/// Smallest offer amount whose swap returns at least `ask_amount` after all fees,
+/// returns the offer, spread, commission and operator fee amounts
+pub fn compute_pool_exact_out(
+    storage: &dyn Storage,
+    env: &Env,
+    offer_info: &AssetInfo,
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    ask_amount: Uint128,
+    commission_rate: Decimal256,
+    operator_fee: Decimal256,
+) -> Result<(Uint128, Uint128, Uint128, Uint128), ContractError> {
+    // invert the curve with both fees, then correct the rounding against the forward swap
+    let (mut offer_amount, ..) = compute_pool_offer_amount(
+        storage,
+        env,
+        offer_info,
+        offer_pool,
+        ask_pool,
+        ask_amount,
+        commission_rate + operator_fee,
+    )?;
+
+    for _ in 0..MAX_EXACT_OUT_ITERATIONS {
+        let (return_amount, spread_amount, commission_amount, operator_fee_amount) =
+            compute_pool_swap(
+                storage,
+                env,
+                offer_info,
+                offer_pool,
+                ask_pool,
+                offer_amount,
+                commission_rate,
+                operator_fee,
+            )?;
+        if return_amount >= ask_amount {
+            return Ok((
+                offer_amount,
+                spread_amount,
+                commission_amount,
+                operator_fee_amount,
+            ));
+        }
+
+        let shortfall = (ask_amount - return_amount)
+            .multiply_ratio(offer_amount, std::cmp::max(return_amount, Uint128::one()));
+        offer_amount = offer_amount
+            .checked_add(shortfall)?
+            .checked_add(Uint128::one())?;
+    }
+
+    Err(ContractError::Std(StdError::generic_err(
+        "Cannot compute the offer amount for the exact output",
+    )))
+}
+
+/// Largest portion of `offer_amount` to swap so that the remainder and the return
+/// match the pool ratio after the swap, returns the swap, return, spread, commission
+/// and operator fee amounts
+pub fn compute_zap_in_swap(
+    storage: &dyn Storage,
+    env: &Env,
+    offer_info: &AssetInfo,
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    offer_amount: Uint128,
+    commission_rate: Decimal256,
+    operator_fee: Decimal256,
+) -> Result<(Uint128, Uint128, Uint128, Uint128, Uint128), ContractError> {
+    let mut low = Uint128::zero();
+    let mut high = offer_amount;
+    while low < high {
+        let mid = low + (high - low + Uint128::one()) / Uint128::from(2u128);
+        let (return_amount, _, _, operator_fee_amount) = compute_pool_swap(
+            storage,
+            env,
+            offer_info,
+            offer_pool,
+            ask_pool,
+            mid,
+            commission_rate,
+            operator_fee,
+        )?;
+        let new_offer_pool = offer_pool.checked_add(mid)?;
+        let new_ask_pool = ask_pool
+            .checked_sub(return_amount)?
+            .checked_sub(operator_fee_amount)?;
+
+        // the remaining offer still covers its side of the pool ratio
+        if (offer_amount - mid).full_mul(new_ask_pool) >= return_amount.full_mul(new_offer_pool) {
+            low = mid;
+        } else {
+            high = mid - Uint128::one();
+        }
+    }
+
+    let (return_amount, spread_amount, commission_amount, operator_fee_amount) = compute_pool_swap(
+        storage,
+        env,
+        offer_info,
+        offer_pool,
+        ask_pool,
+        low,
+        commission_rate,
+        operator_fee,
+    )?;
+
+    Ok((
+        low,
+        return_amount,
+        spread_amount,
+        commission_amount,
+        operator_fee_amount,
+    ))
+}
+
+/// Commission rate applied to a trade, in dynamic fee mode it moves from the min to
+/// the max rate as the spot price deviates from the TWAP up to `max_deviation`
+pub fn compute_commission_rate(
+    storage: &dyn Storage,
+    env: &Env,
+    reserves: [Uint128; 2],
+    commission_rate: Decimal256,
+) -> StdResult<Decimal256> {
+    let config = match DYNAMIC_FEE.may_load(storage)? {
+        Some(config) => config,
+        None => return Ok(commission_rate),
+    };
+
+    let min_rate: Decimal256 = config.min_commission_rate.into();
+    let max_rate: Decimal256 = config.max_commission_rate.into();
+    if reserves[0].is_zero() || reserves[1].is_zero() {
+        return Ok(min_rate);
+    }
+
+    // not enough history yet is treated as a calm market
+    let twap_price = match compute_twap(storage, env, reserves, config.window_seconds) {
+        Ok(twap) if !twap.price0_average.is_zero() => twap.price0_average,
+        _ => return Ok(min_rate),
+    };
+    let (spot_price, _) = compute_pool_spot_prices(storage, env, reserves)?;
+    let price_diff = if spot_price > twap_price {
+        spot_price - twap_price
+    } else {
+        twap_price - spot_price
+    };
+    let deviation = price_diff / twap_price;
+
+    let max_deviation: Decimal256 = config.max_deviation.into();
+    let ratio = std::cmp::min(deviation / max_deviation, Decimal256::one());
+
+    Ok(min_rate + (max_rate - min_rate) * ratio)
+}
+
+/// Tracked reserves the pool math runs on, donations to the pair are not part of them.
+/// Pairs that have not recorded any reserves yet fall back to their balances
+pub fn query_reserves(
+    deps: Deps,
+    pair_info: &PairInfoRaw,
+    contract_addr: Addr,
+) -> StdResult<[Asset; 2]> {
+    query_reserves_before(deps, pair_info, contract_addr, &[])
+}
+
+/// Reserves before `incoming`, which is already in the pair balance.
+/// Only the untracked balance fallback has to exclude it
+pub fn query_reserves_before(
+    deps: Deps,
+    pair_info: &PairInfoRaw,
+    contract_addr: Addr,
+    incoming: &[Asset],
+) -> StdResult<[Asset; 2]> {
+    let mut pools = query_balances(deps, pair_info, contract_addr)?;
+    match POOL_SNAPSHOT.may_load(deps.storage)? {
+        // a shortfall, e.g. from a fee-on-transfer deposit, is taken from the reserves
+        Some(snapshot) => {
+            for (pool, reserve) in pools.iter_mut().zip(snapshot.reserves) {
+                pool.amount = std::cmp::min(pool.amount, reserve);
+            }
+        }
+        None => {
+            for pool in pools.iter_mut() {
+                for asset in incoming.iter().filter(|asset| asset.info.eq(&pool.info)) {
+                    pool.amount = pool.amount.checked_sub(asset.amount)?;
+                }
+            }
+        }
+    }
+
+    Ok(pools)
+}
+
+/// Part of `offer_asset` that reached the pair on top of `reserve`,
+/// never more than the declared amount
+fn query_received_amount(
+    deps: Deps,
+    contract_addr: Addr,
+    reserve: Uint128,
+    offer_asset: &Asset,
+) -> StdResult<Uint128> {
+    let accrued = PROTOCOL_FEES
+        .may_load(deps.storage, &offer_asset.info.to_string())?
+        .unwrap_or_default();
+    let balance = offer_asset
+        .info
+        .query_pool(&deps.querier, contract_addr)?
+        .checked_sub(accrued)?;
+
+    Ok(std::cmp::min(
+        offer_asset.amount,
+        balance.saturating_sub(reserve),
+    ))
+}
+
+/// Pair balances without the accrued protocol fees
+pub fn query_balances(
+    deps: Deps,
+    pair_info: &PairInfoRaw,
+    contract_addr: Addr,
+) -> StdResult<[Asset; 2]> {
+    let mut pools = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    for pool in pools.iter_mut() {
+        let accrued = PROTOCOL_FEES
+            .may_load(deps.storage, &pool.info.to_string())?
+            .unwrap_or_default();
+        pool.amount = pool.amount.checked_sub(accrued)?;
+    }
+
+    Ok(pools)
+}
+
+/// Sends the operator fee right away, or keeps it inside the pair in accrual mode
+fn pay_operator_fee(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    api: &dyn Api,
+    operator: &CanonicalAddr,
+    fee: Asset,
+) -> StdResult<Option<CosmosMsg>> {
+    if fee.amount.is_zero() {
+        return Ok(None);
+    }
+
+    if PROTOCOL_FEE_ACCRUAL.may_load(storage)?.unwrap_or(false) {
+        PROTOCOL_FEES.update(storage, &fee.info.to_string(), |accrued| -> StdResult<_> {
+            Ok(accrued.unwrap_or_default().checked_add(fee.amount)?)
+        })?;
+        return Ok(None);
+    }
+
+    Ok(Some(fee.into_msg(
+        None,
+        querier,
+        api.addr_humanize(operator)?,
+    )?))
+}
+
+/// Spot prices of asset_infos[0] in asset_infos[1] and the other way around
+pub fn compute_pool_spot_prices(
+    storage: &dyn Storage,
+    env: &Env,
+    reserves: [Uint128; 2],
+) -> StdResult<(Decimal256, Decimal256)> {
+    match STABLE_SWAP_CONFIG.may_load(storage)? {
+        Some(config) => {
+            let amp = config.current_amp(env.block.time.seconds());
+            let decimals = config
+                .decimals
+                .first()
+                .map(|(info, _)| config.decimals(info))
+                .unwrap_or_default();
+            Ok((
+                compute_stable_spot_price(amp, reserves[0].into(), reserves[1].into(), decimals)?,
+                compute_stable_spot_price(
+                    amp,
+                    reserves[1].into(),
+                    reserves[0].into(),
+                    [decimals[1], decimals[0]],
+                )?,
+            ))
+        }
+        None => Ok((
+            Decimal256::from_ratio(reserves[1], reserves[0]),
+            Decimal256::from_ratio(reserves[0], reserves[1]),
+        )),
+    }
+}
+
+/// Cumulative prices accrued up to the current block, `reserves` must be the
+/// reserves held since the last update
+fn accumulate_prices(
+    storage: &dyn Storage,
+    env: &Env,
+    reserves: [Uint128; 2],
+) -> StdResult<Observation> {
+    let block_time = env.block.time.seconds();
+    let mut cumulative = match PRICE_CUMULATIVE.may_load(storage)? {
+        Some(cumulative) => cumulative,
+        None => {
+            return Ok(Observation {
+                timestamp: block_time,
+                price0_cumulative: Decimal256::zero(),
+                price1_cumulative: Decimal256::zero(),
+            })
+        }
+    };
+
+    let elapsed = block_time.saturating_sub(cumulative.timestamp);
+    if elapsed > 0 && !reserves[0].is_zero() && !reserves[1].is_zero() {
+        let (price0, price1) = compute_pool_spot_prices(storage, env, reserves)?;
+        let elapsed = Decimal256::from_ratio(elapsed, 1u64);
+        cumulative.price0_cumulative = cumulative
+            .price0_cumulative
+            .checked_add(price0.checked_mul(elapsed)?)?;
+        cumulative.price1_cumulative = cumulative
+            .price1_cumulative
+            .checked_add(price1.checked_mul(elapsed)?)?;
+    }
+    cumulative.timestamp = block_time;
+
+    Ok(cumulative)
+}
+
+/// Must be called before the reserves change, stores an observation at most once per `OBSERVATION_PERIOD`
+pub fn update_price_cumulative(
+    storage: &mut dyn Storage,
+    env: &Env,
+    reserves: [Uint128; 2],
+) -> StdResult<()> {
+    let cumulative = accumulate_prices(storage, env, reserves)?;
+    PRICE_CUMULATIVE.save(storage, &cumulative)?;
+
+    let count = OBSERVATION_COUNT.may_load(storage)?.unwrap_or_default();
+    let should_observe = match count.checked_sub(1) {
+        None => true,
+        Some(latest) => {
+            let latest = OBSERVATIONS.load(storage, latest % OBSERVATION_CAPACITY)?;
+            cumulative.timestamp >= latest.timestamp + OBSERVATION_PERIOD
+        }
+    };
+    if should_observe {
+        OBSERVATIONS.save(storage, count % OBSERVATION_CAPACITY, &cumulative)?;
+        OBSERVATION_COUNT.save(storage, &(count + 1))?;
+    }
+
+    Ok(())
+}
+
+fn current_price_cumulative(deps: Deps, env: &Env) -> StdResult<Observation> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    let pools: [Asset; 2] = query_reserves(deps, &pair_info, contract_addr)?;
+
+    accumulate_prices(deps.storage, env, [pools[0].amount, pools[1].amount])
+}
+
 pub fn amount_of(coins: &[Coin], denom: String) -> Uint128 {
     match coins.iter().find(|x| x.denom == denom) {
         Some(coin) => coin.amount,
@@ -933,5 +3544,8 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, Co
         pair_info.asset_infos = asset_infos_raw;
         PAIR_INFO.save(deps.storage, &pair_info)?;
     }
+    if let Some(factory) = msg.factory {
+        FACTORY.save(deps.storage, &deps.api.addr_validate(&factory)?)?;
+    }
     Ok(Response::default())
 }
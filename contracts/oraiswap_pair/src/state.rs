@@ -1,6 +1,91 @@
-use cosmwasm_std::{Addr, CanonicalAddr};
-use cw_storage_plus::{Item, Map};
-use oraiswap::asset::PairInfoRaw;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, CanonicalAddr, Decimal, Decimal256, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Item, Map, SnapshotItem, Strategy};
+use oraiswap::asset::{Asset, AssetInfo, PairInfoRaw};
+use oraiswap::pair::DynamicFeeParams;
+
+#[cw_serde]
+pub struct StableSwapConfig {
+    pub init_amp: u64,
+    pub init_amp_time: u64,
+    pub future_amp: u64,
+    pub future_amp_time: u64,
+    /// Decimals of each asset, empty when they are equal
+    #[serde(default)]
+    pub decimals: Vec<(AssetInfo, u8)>,
+}
+
+impl StableSwapConfig {
+    /// Decimals of `offer_info` and of the other asset
+    pub fn decimals(&self, offer_info: &AssetInfo) -> [u8; 2] {
+        match self
+            .decimals
+            .iter()
+            .position(|(info, _)| info.eq(offer_info))
+        {
+            Some(index) => [self.decimals[index].1, self.decimals[1 - index].1],
+            None => [0, 0],
+        }
+    }
+
+    /// Amplification coefficient at `block_time`, linearly interpolated while ramping
+    pub fn current_amp(&self, block_time: u64) -> u64 {
+        if block_time >= self.future_amp_time || self.future_amp_time <= self.init_amp_time {
+            return self.future_amp;
+        }
+
+        let elapsed = block_time.saturating_sub(self.init_amp_time) as u128;
+        let duration = (self.future_amp_time - self.init_amp_time) as u128;
+        if self.future_amp > self.init_amp {
+            let delta = (self.future_amp - self.init_amp) as u128 * elapsed / duration;
+            self.init_amp + delta as u64
+        } else {
+            let delta = (self.init_amp - self.future_amp) as u128 * elapsed / duration;
+            self.init_amp - delta as u64
+        }
+    }
+}
+
+/// Cumulative prices at `timestamp`, price0 is asset_infos[0] in asset_infos[1]
+#[cw_serde]
+pub struct Observation {
+    pub timestamp: u64,
+    pub price0_cumulative: Decimal256,
+    pub price1_cumulative: Decimal256,
+}
+
+/// Flash swap waiting for its callback to complete
+#[cw_serde]
+pub struct FlashSwapState {
+    pub initiator: Addr,
+    pub asset: Asset,
+    pub fee_amount: Uint128,
+    /// Reserves before the asset was lent
+    pub reserves: [Uint128; 2],
+    /// Balances before the asset was lent, surplus included
+    pub balances: [Uint128; 2],
+}
+
+/// Paused actions of the pair
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseStatus {
+    pub swap: bool,
+    pub provide: bool,
+    pub withdraw: bool,
+}
+
+/// Reserves and total share after a change of the pool
+#[cw_serde]
+pub struct PoolSnapshot {
+    pub reserves: [Uint128; 2],
+    pub total_share: Uint128,
+}
+
+/// Size of the observation ring buffer
+pub const OBSERVATION_CAPACITY: u32 = 720;
+/// Minimum number of seconds between two stored observations
+pub const OBSERVATION_PERIOD: u64 = 60;
 
 // put the length bytes at the first for compatibility with legacy singleton store
 pub const PAIR_INFO: Item<PairInfoRaw> = Item::new("\u{0}\u{9}pair_info");
@@ -11,6 +96,64 @@ pub const WHITELISTED_TRADERS: Map<&Addr, bool> = Map::new("\u{0}\u{19}whitelist
 pub const WHITELISTED_WITHDRAW_LPS: Map<&Addr, bool> =
     Map::new("\u{0}\u{20}whitelisted_withdraw_lp");
 pub const OPERATOR: Item<CanonicalAddr> = Item::new("\u{0}\u{8}operator");
+// only stored for StableSwap pairs
+pub const STABLE_SWAP_CONFIG: Item<StableSwapConfig> = Item::new("stable_swap_config");
+// latest cumulative prices, updated on every reserve change
+pub const PRICE_CUMULATIVE: Item<Observation> = Item::new("price_cumulative");
+// ring buffer of observations, the next slot is `OBSERVATION_COUNT % OBSERVATION_CAPACITY`
+pub const OBSERVATIONS: Map<u32, Observation> = Map::new("observations");
+pub const OBSERVATION_COUNT: Item<u32> = Item::new("observation_count");
+pub const FLASH_FEE: Item<String> = Item::new("flash_fee");
+pub const FLASH_SWAP: Item<FlashSwapState> = Item::new("flash_swap");
+// keep the operator fee inside the pair instead of sending it on every trade
+pub const PROTOCOL_FEE_ACCRUAL: Item<bool> = Item::new("protocol_fee_accrual");
+// accrued operator fees keyed by asset, excluded from the reserves
+pub const PROTOCOL_FEES: Map<&str, Uint128> = Map::new("protocol_fees");
+// only stored when the dynamic fee mode is on
+pub const DYNAMIC_FEE: Item<DynamicFeeParams> = Item::new("dynamic_fee");
+// contracts notified after every swap
+pub const SWAP_HOOKS: Item<Vec<Addr>> = Item::new("swap_hooks");
+pub const PAUSE_STATUS: Item<PauseStatus> = Item::new("pause_status");
+// only stored when the circuit breaker is on
+pub const MAX_PRICE_CHANGE: Item<Decimal> = Item::new("max_price_change");
+// only stored when swaps are capped by price impact
+pub const MAX_PRICE_IMPACT: Item<Decimal> = Item::new("max_price_impact");
+// discounted commission rates keyed by trader
+pub const TRADER_FEE_TIERS: Map<&Addr, String> = Map::new("trader_fee_tiers");
+// LP amount locked by the first provision, not stored for pairs created before the lock
+pub const LOCKED_SHARE: Item<Uint128> = Item::new("locked_share");
+// factory that instantiated the pair, allowed to pause it
+pub const FACTORY: Item<Addr> = Item::new("factory");
+// written on every reserve or share change so the pool can be read at past heights,
+// the latest one holds the tracked reserves
+pub const POOL_SNAPSHOT: SnapshotItem<PoolSnapshot> = SnapshotItem::new(
+    "pool_snapshot",
+    "pool_snapshot__checkpoints",
+    "pool_snapshot__changelog",
+    Strategy::EveryBlock,
+);
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Lists the addresses registered in one of the whitelist maps, ordered by address
+pub fn read_whitelisted(
+    storage: &dyn Storage,
+    whitelist: Map<&Addr, bool>,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Addr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    whitelist
+        .range(storage, start, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok((_, false))))
+        .take(limit)
+        .map(|item| item.map(|(addr, _)| addr))
+        .collect()
+}
 
 #[cfg(test)]
 mod test {
@@ -19,7 +162,7 @@ mod test {
     use cosmwasm_std::testing::mock_dependencies;
     use cosmwasm_std::{Api, StdResult, Storage};
     use cosmwasm_storage::{singleton, singleton_read};
-    use oraiswap::asset::AssetInfoRaw;
+    use oraiswap::asset::{AssetInfoRaw, PairStatus, PairType};
     use oraiswap::pair::{DEFAULT_COMMISSION_RATE, DEFAULT_OPERATOR_FEE};
     const KEY_PAIR_INFO: &[u8] = b"pair_info";
 
@@ -47,8 +190,12 @@ mod test {
                 ],
                 contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
                 liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+                liquidity_denom: None,
                 commission_rate: DEFAULT_COMMISSION_RATE.to_string(),
                 operator_fee: DEFAULT_OPERATOR_FEE.to_string(),
+                pair_type: PairType::Xyk {},
+                status: PairStatus::Active,
+                successor: None,
             },
         )
         .unwrap();
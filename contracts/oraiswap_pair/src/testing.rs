@@ -1,10 +1,34 @@
-use cosmwasm_std::testing::MOCK_CONTRACT_ADDR;
-use cosmwasm_std::{attr, to_binary, to_json_binary, Addr, Coin, Decimal, Uint128};
+use std::str::FromStr;
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    attr, to_binary, to_json_binary, Addr, CanonicalAddr, Coin, Decimal, Decimal256, Event, Reply,
+    ReplyOn, StdResult, SubMsg, SubMsgResult, Uint128, WasmMsg,
+};
 use cw20::Cw20ReceiveMsg;
-use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
+use oraiswap::asset::{
+    Asset, AssetInfo, AssetInfoRaw, PairInfoRaw, PairStatus, PairType, ORAI_DENOM,
+};
 use oraiswap::create_entry_points_testing;
-use oraiswap::pair::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PairResponse, QueryMsg};
+use oraiswap::error::ContractError;
+use oraiswap::pair::{
+    Cw20HookMsg, DynamicFeeParams, ExecuteMsg, InstantiateMsg, MigrateMsg, PairResponse,
+    PoolAtHeightResponse, PoolResponse, PriceCumulativeResponse, ProtocolFeesResponse, QueryMsg,
+    SimulationResponse, StableSwapConfigResponse, StableSwapParams, StatusResponse, SwapHookMsg,
+    TwapResponse, MINIMUM_LIQUIDITY_AMOUNT,
+};
+use oraiswap::querier::query_token_balance;
 use oraiswap::testing::{MockApp, APP_OWNER, ATOM_DENOM};
+use oraiswap::tokenfactory::{create_denom_msg, mint_msg, token_factory_denom};
+
+use crate::contract::{
+    compute_commission_rate, execute, instantiate, query_pair_info, query_pool,
+    query_pool_at_height, reply, save_pool_snapshot, swap_hook_msgs, update_price_cumulative,
+    SWAP_HOOK_GAS_LIMIT, SWAP_HOOK_REPLY_ID,
+};
+use crate::state::{
+    DYNAMIC_FEE, OBSERVATIONS, OBSERVATION_COUNT, PAIR_INFO, PRICE_CUMULATIVE, SWAP_HOOKS,
+};
 
 #[test]
 fn provide_liquidity_and_change_obtc_to_native_btc() {
@@ -13,11 +37,11 @@ fn provide_liquidity_and_change_obtc_to_native_btc() {
         &[
             Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(200u128),
+                amount: Uint128::from(20000u128),
             },
             Coin {
                 denom: "native_btc".to_string(),
-                amount: Uint128::from(200u128),
+                amount: Uint128::from(20000u128),
             },
         ],
     )]);
@@ -25,7 +49,7 @@ fn provide_liquidity_and_change_obtc_to_native_btc() {
     app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
     app.set_token_balances(&[(
         &"obtc".to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), 1000u128)],
+        &[(&MOCK_CONTRACT_ADDR.to_string(), 100000u128)],
     )])
     .unwrap();
 
@@ -68,6 +92,10 @@ fn provide_liquidity_and_change_obtc_to_native_btc() {
         admin: Some(owner.clone()),
         operator_fee: None,
         operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
     };
     // we can just call .unwrap() to assert this was a success
     let code_id = app.upload(Box::new(
@@ -85,7 +113,7 @@ fn provide_liquidity_and_change_obtc_to_native_btc() {
         obtc_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: pair_addr.to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(10000u128),
             expires: None,
         },
         &[],
@@ -98,17 +126,18 @@ fn provide_liquidity_and_change_obtc_to_native_btc() {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::Token {
                     contract_addr: obtc_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: Some(pair_addr.clone()),
+        deadline: None,
     };
     let res = app
         .execute(
@@ -118,11 +147,11 @@ fn provide_liquidity_and_change_obtc_to_native_btc() {
             &[
                 Coin {
                     denom: ORAI_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
                 Coin {
                     denom: "native_btc".to_string(),
-                    amount: Uint128::from(200u128),
+                    amount: Uint128::from(20000u128),
                 },
             ],
         )
@@ -156,6 +185,7 @@ fn provide_liquidity_and_change_obtc_to_native_btc() {
                         denom: "native_btc".to_string(),
                     },
                 ]),
+                factory: None,
             },
             new_code_id,
         )
@@ -167,13 +197,13 @@ fn provide_liquidity_and_change_obtc_to_native_btc() {
     println!("{:?}", pair_info);
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: MOCK_CONTRACT_ADDR.into(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
-        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { deadline: None }).unwrap(),
+        amount: Uint128::from(10000u128),
     });
     let balance = app
         .query_balance(Addr::unchecked(MOCK_CONTRACT_ADDR), "orai".to_string())
         .unwrap();
-    assert_eq!(balance, Uint128::new(100));
+    assert_eq!(balance, Uint128::new(10000));
     let balance = app
         .query_balance(
             Addr::unchecked(MOCK_CONTRACT_ADDR),
@@ -202,7 +232,7 @@ fn provide_liquidity_and_change_obtc_to_native_btc() {
 
     let res = app
         .execute(
-            pair_info.info.liquidity_token.into(),
+            pair_info.info.liquidity_token.unwrap().into(),
             pair_addr.clone(),
             &msg,
             &[],
@@ -212,14 +242,14 @@ fn provide_liquidity_and_change_obtc_to_native_btc() {
     let balance = app
         .query_balance(Addr::unchecked(MOCK_CONTRACT_ADDR), "orai".to_string())
         .unwrap();
-    assert_eq!(balance, Uint128::new(200));
+    assert_eq!(balance, Uint128::new(20000));
     let balance = app
         .query_balance(
             Addr::unchecked(MOCK_CONTRACT_ADDR),
             "native_btc".to_string(),
         )
         .unwrap();
-    assert_eq!(balance, Uint128::new(200));
+    assert_eq!(balance, Uint128::new(20000));
 }
 
 #[test]
@@ -229,11 +259,11 @@ fn provide_liquidity_both_native() {
         &[
             Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(200u128),
+                amount: Uint128::from(20000u128),
             },
             Coin {
                 denom: ATOM_DENOM.to_string(),
-                amount: Uint128::from(200u128),
+                amount: Uint128::from(20000u128),
             },
         ],
     )]);
@@ -263,6 +293,10 @@ fn provide_liquidity_both_native() {
         admin: None,
         operator_fee: None,
         operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -281,17 +315,18 @@ fn provide_liquidity_both_native() {
                 info: AssetInfo::NativeToken {
                     denom: ATOM_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let res = app
@@ -302,11 +337,11 @@ fn provide_liquidity_both_native() {
             &[
                 Coin {
                     denom: ORAI_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
                 Coin {
                     denom: ATOM_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
             ],
         )
@@ -323,7 +358,7 @@ fn provide_liquidity() {
         &MOCK_CONTRACT_ADDR.to_string(),
         &[Coin {
             denom: ORAI_DENOM.to_string(),
-            amount: Uint128::from(400u128),
+            amount: Uint128::from(40000u128),
         }],
     )]);
     app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
@@ -331,8 +366,11 @@ fn provide_liquidity() {
     app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
 
     app.set_token_balances(&[
-        ("liquidity", &[(&MOCK_CONTRACT_ADDR.to_string(), 1000u128)]),
-        ("asset", &[(&MOCK_CONTRACT_ADDR.to_string(), 1000u128)]),
+        (
+            "liquidity",
+            &[(&MOCK_CONTRACT_ADDR.to_string(), 100000u128)],
+        ),
+        ("asset", &[(&MOCK_CONTRACT_ADDR.to_string(), 100000u128)]),
     ])
     .unwrap();
 
@@ -353,6 +391,10 @@ fn provide_liquidity() {
         admin: None,
         operator_fee: None,
         operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -369,7 +411,7 @@ fn provide_liquidity() {
         asset_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: pair_addr.to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(10000u128),
             expires: None,
         },
         &[],
@@ -383,17 +425,18 @@ fn provide_liquidity() {
                 info: AssetInfo::Token {
                     contract_addr: asset_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
@@ -403,18 +446,18 @@ fn provide_liquidity() {
             &msg,
             &[Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             }],
         )
         .unwrap();
 
-    // set allowance one more 100
+    // set allowance one more 10000
     app.execute(
         Addr::unchecked(MOCK_CONTRACT_ADDR),
         asset_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: pair_addr.to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(10000u128),
             expires: None,
         },
         &[],
@@ -427,20 +470,21 @@ fn provide_liquidity() {
                 info: AssetInfo::Token {
                     contract_addr: asset_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(200u128),
+                amount: Uint128::from(20000u128),
             },
         ],
         slippage_tolerance: None,
-        receiver: Some(Addr::unchecked("staking0000")), // try changing receiver
+        receiver: Some(Addr::unchecked("staking0000")), // try changing receiver,
+        deadline: None,
     };
 
-    // only accept 100, then 50 share will be generated with 100 * (100 / 200)
+    // only accept 10000, then 5000 share will be generated with 10000 * (10000 / 20000)
     let _res = app
         .execute(
             Addr::unchecked(MOCK_CONTRACT_ADDR),
@@ -448,7 +492,7 @@ fn provide_liquidity() {
             &msg,
             &[Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(200u128),
+                amount: Uint128::from(20000u128),
             }],
         )
         .unwrap();
@@ -460,17 +504,18 @@ fn provide_liquidity() {
                 info: AssetInfo::Token {
                     contract_addr: asset_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(50u128),
+                amount: Uint128::from(5000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let error = app
@@ -480,7 +525,7 @@ fn provide_liquidity() {
             &msg,
             &[Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             }],
         )
         .unwrap_err();
@@ -494,7 +539,7 @@ fn withdraw_liquidity() {
         "addr0000",
         &[Coin {
             denom: ORAI_DENOM.to_string(),
-            amount: Uint128::from(1000u128),
+            amount: Uint128::from(100000u128),
         }],
     )]);
 
@@ -504,7 +549,7 @@ fn withdraw_liquidity() {
 
     app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
 
-    app.set_token_balances(&[("liquidity", &[("addr0000", 1000u128)])])
+    app.set_token_balances(&[("liquidity", &[("addr0000", 100000u128)])])
         .unwrap();
 
     let liquidity_addr = app.get_token_addr("liquidity").unwrap();
@@ -524,6 +569,10 @@ fn withdraw_liquidity() {
         admin: None,
         operator_fee: None,
         operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
     };
 
     let pair_id = app.upload(Box::new(
@@ -534,13 +583,13 @@ fn withdraw_liquidity() {
         .instantiate(pair_id, Addr::unchecked("addr0000"), &msg, &[], "pair")
         .unwrap();
 
-    // set allowance one more 100
+    // set allowance
     app.execute(
         Addr::unchecked("addr0000"),
         liquidity_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: pair_addr.to_string(),
-            amount: Uint128::from(1000u128),
+            amount: Uint128::from(100000u128),
             expires: None,
         },
         &[],
@@ -553,21 +602,21 @@ fn withdraw_liquidity() {
                 info: AssetInfo::Token {
                     contract_addr: liquidity_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         // we send lq token to pair and later call it directly to test
         receiver: Some(pair_addr.clone()),
+        deadline: None,
     };
 
-    // only accept 100, then 50 share will be generated with 100 * (100 / 200)
     let _res = app
         .execute(
             Addr::unchecked("addr0000"),
@@ -575,23 +624,28 @@ fn withdraw_liquidity() {
             &msg,
             &[Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             }],
         )
         .unwrap();
 
-    // withdraw liquidity
+    // withdraw liquidity, the locked minimum liquidity stays in the pair
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".into(),
-        msg: to_json_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
-        amount: Uint128::from(100u128),
+        msg: to_json_binary(&Cw20HookMsg::WithdrawLiquidity { deadline: None }).unwrap(),
+        amount: Uint128::from(9000u128),
     });
 
     let PairResponse { info: pair_info } =
         app.query(pair_addr.clone(), &QueryMsg::Pair {}).unwrap();
 
     let res = app
-        .execute(pair_info.liquidity_token, pair_addr.clone(), &msg, &[])
+        .execute(
+            pair_info.liquidity_token.unwrap(),
+            pair_addr.clone(),
+            &msg,
+            &[],
+        )
         .unwrap();
 
     let attributes = res.custom_attrs(1);
@@ -600,13 +654,13 @@ fn withdraw_liquidity() {
 
     assert_eq!(
         log_withdrawn_share,
-        &attr("withdrawn_share", 100u128.to_string())
+        &attr("withdrawn_share", 9000u128.to_string())
     );
     assert_eq!(
         log_refund_assets,
         &attr(
             "refund_assets",
-            format!("100{}, 100{}", ORAI_DENOM, liquidity_addr)
+            format!("9000{}, 9000{}", ORAI_DENOM, liquidity_addr)
         )
     );
 }
@@ -619,7 +673,7 @@ fn test_pool_whitelist_for_trader() {
         &MOCK_CONTRACT_ADDR.to_string(),
         &[Coin {
             denom: ORAI_DENOM.to_string(),
-            amount: Uint128::from(400u128),
+            amount: Uint128::from(40000u128),
         }],
     )]);
     app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
@@ -627,9 +681,12 @@ fn test_pool_whitelist_for_trader() {
     app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
 
     app.set_token_balances(&[
-        ("liquidity", &[(&MOCK_CONTRACT_ADDR.to_string(), 1000u128)]),
-        ("asset", &[(&MOCK_CONTRACT_ADDR.to_string(), 1000u128)]),
-        ("asset", &[("addr0000", 1000u128)]),
+        (
+            "liquidity",
+            &[(&MOCK_CONTRACT_ADDR.to_string(), 100000u128)],
+        ),
+        ("asset", &[(&MOCK_CONTRACT_ADDR.to_string(), 100000u128)]),
+        ("asset", &[("addr0000", 100000u128)]),
     ])
     .unwrap();
 
@@ -650,6 +707,10 @@ fn test_pool_whitelist_for_trader() {
         admin: Some(Addr::unchecked("admin")),
         operator_fee: None,
         operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -667,7 +728,7 @@ fn test_pool_whitelist_for_trader() {
         asset_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: pair_addr.to_string(),
-            amount: Uint128::from(1000u128),
+            amount: Uint128::from(100000u128),
             expires: None,
         },
         &[],
@@ -678,7 +739,7 @@ fn test_pool_whitelist_for_trader() {
         asset_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: pair_addr.to_string(),
-            amount: Uint128::from(1000u128),
+            amount: Uint128::from(100000u128),
             expires: None,
         },
         &[],
@@ -692,17 +753,18 @@ fn test_pool_whitelist_for_trader() {
                 info: AssetInfo::Token {
                     contract_addr: asset_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
@@ -712,7 +774,7 @@ fn test_pool_whitelist_for_trader() {
             &msg,
             &[Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             }],
         )
         .unwrap();
@@ -754,17 +816,18 @@ fn test_pool_whitelist_for_trader() {
                 info: AssetInfo::Token {
                     contract_addr: asset_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let error = app
@@ -774,7 +837,7 @@ fn test_pool_whitelist_for_trader() {
             &msg,
             &[Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             }],
         )
         .unwrap_err();
@@ -787,7 +850,7 @@ fn test_pool_whitelist_for_trader() {
         &msg,
         &[Coin {
             denom: ORAI_DENOM.to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(10000u128),
         }],
     )
     .unwrap();
@@ -798,11 +861,12 @@ fn test_pool_whitelist_for_trader() {
             info: AssetInfo::NativeToken {
                 denom: ORAI_DENOM.to_string(),
             },
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(10000u128),
         },
         belief_price: None,
         max_spread: None,
         to: None,
+        deadline: None,
     };
 
     let error = app
@@ -812,7 +876,7 @@ fn test_pool_whitelist_for_trader() {
             &swap_msg,
             &[Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             }],
         )
         .unwrap_err();
@@ -825,7 +889,7 @@ fn test_pool_whitelist_for_trader() {
         &swap_msg,
         &[Coin {
             denom: ORAI_DENOM.to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(10000u128),
         }],
     )
     .unwrap();
@@ -873,6 +937,10 @@ fn test_update_executor() {
         admin: Some(Addr::unchecked("admin")),
         operator_fee: None,
         operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -967,6 +1035,10 @@ fn test_swap_with_operator_fee() {
         admin: Some(Addr::unchecked("admin")),
         operator_fee: None,
         operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -1018,6 +1090,7 @@ fn test_swap_with_operator_fee() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
@@ -1048,6 +1121,7 @@ fn test_swap_with_operator_fee() {
         belief_price: None,
         max_spread: None,
         to: None,
+        deadline: None,
     };
 
     let res = app
@@ -1121,3 +1195,3828 @@ fn test_swap_with_operator_fee() {
         }
     }
 }
+
+/// The pair reserves match its balances when nothing is accrued, the oracle tax
+/// of the sent amounts stays in both
+fn assert_reserves_match_balances(app: &MockApp, pair_addr: &Addr) {
+    let pool: PoolResponse = app.query(pair_addr.clone(), &QueryMsg::Pool {}).unwrap();
+    for asset in pool.assets {
+        if let AssetInfo::NativeToken { denom } = asset.info {
+            assert_eq!(
+                asset.amount,
+                app.query_balance(pair_addr.clone(), denom).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_stable_swap_pair() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_tax(Decimal::permille(3), &[(ATOM_DENOM, 1000000u128)]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: ATOM_DENOM.to_string(),
+        },
+    ];
+
+    // the curve params must match the pair type
+    for (pair_type, stable_swap, error) in [
+        (
+            PairType::Stable {},
+            None,
+            "StableSwap pairs require the amplification params",
+        ),
+        (
+            PairType::Xyk {},
+            Some(StableSwapParams {
+                amp: 100,
+                decimals: None,
+            }),
+            "Only StableSwap pairs take the amplification params",
+        ),
+    ] {
+        let msg = InstantiateMsg {
+            oracle_addr: app.oracle_addr.clone(),
+            asset_infos: asset_infos.clone(),
+            token_code_id: app.token_id(),
+            commission_rate: None,
+            admin: None,
+            operator_fee: None,
+            operator: None,
+            stable_swap,
+            pair_type: Some(pair_type),
+            native_lp: false,
+            factory: None,
+        };
+        let err = app
+            .instantiate(code_id, Addr::unchecked("owner"), &msg, &[], "pair")
+            .unwrap_err();
+        assert!(format!("{err:?}").contains(error));
+    }
+
+    // the same pool with both curves
+    let mut pair_addrs = vec![];
+    for stable_swap in [
+        None,
+        Some(StableSwapParams {
+            amp: 100,
+            decimals: None,
+        }),
+    ] {
+        let msg = InstantiateMsg {
+            oracle_addr: app.oracle_addr.clone(),
+            asset_infos: asset_infos.clone(),
+            token_code_id: app.token_id(),
+            commission_rate: None,
+            admin: Some(Addr::unchecked("admin")),
+            operator_fee: None,
+            operator: None,
+            stable_swap,
+            pair_type: None,
+            native_lp: false,
+            factory: None,
+        };
+        let pair_addr = app
+            .instantiate(code_id, Addr::unchecked("owner"), &msg, &[], "pair")
+            .unwrap();
+
+        app.execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pair_addr.clone(),
+            &ExecuteMsg::ProvideLiquidity {
+                assets: [
+                    Asset {
+                        info: asset_infos[0].clone(),
+                        amount: Uint128::from(1000000000u128),
+                    },
+                    Asset {
+                        info: asset_infos[1].clone(),
+                        amount: Uint128::from(1000000000u128),
+                    },
+                ],
+                slippage_tolerance: None,
+                receiver: None,
+                deadline: None,
+            },
+            &[
+                Coin {
+                    denom: ORAI_DENOM.to_string(),
+                    amount: Uint128::from(1000000000u128),
+                },
+                Coin {
+                    denom: ATOM_DENOM.to_string(),
+                    amount: Uint128::from(1000000000u128),
+                },
+            ],
+        )
+        .unwrap();
+        pair_addrs.push(pair_addr);
+    }
+
+    let offer_asset = Asset {
+        info: asset_infos[0].clone(),
+        amount: Uint128::from(10000000u128),
+    };
+    let xyk_simulation: SimulationResponse = app
+        .query(
+            pair_addrs[0].clone(),
+            &QueryMsg::Simulation {
+                offer_asset: offer_asset.clone(),
+            },
+        )
+        .unwrap();
+    let stable_simulation: SimulationResponse = app
+        .query(
+            pair_addrs[1].clone(),
+            &QueryMsg::Simulation {
+                offer_asset: offer_asset.clone(),
+            },
+        )
+        .unwrap();
+    assert!(stable_simulation.return_amount > xyk_simulation.return_amount);
+    assert!(stable_simulation.spread_amount < xyk_simulation.spread_amount);
+
+    // constant product pair has no amplification
+    let res: Result<StableSwapConfigResponse, _> =
+        app.query(pair_addrs[0].clone(), &QueryMsg::StableSwapConfig {});
+    assert!(res.is_err());
+
+    // only admin can ramp, and the ramp must last at least one day
+    let ramp_msg = ExecuteMsg::RampAmp {
+        future_amp: 200,
+        future_time: 4102444800,
+    };
+    app.execute(
+        Addr::unchecked("addr0000"),
+        pair_addrs[1].clone(),
+        &ramp_msg,
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addrs[1].clone(),
+        &ExecuteMsg::RampAmp {
+            future_amp: 200,
+            future_time: 1,
+        },
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addrs[1].clone(),
+        &ramp_msg,
+        &[],
+    )
+    .unwrap();
+
+    let config: StableSwapConfigResponse = app
+        .query(pair_addrs[1].clone(), &QueryMsg::StableSwapConfig {})
+        .unwrap();
+    assert_eq!(config.amp, 100);
+    assert_eq!(config.future_amp, 200);
+
+    // swap executes on the StableSwap curve
+    let res = app
+        .execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pair_addrs[1].clone(),
+            &ExecuteMsg::Swap {
+                offer_asset,
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                deadline: None,
+            },
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            }],
+        )
+        .unwrap();
+    let return_amount = res
+        .events
+        .iter()
+        .flat_map(|event| &event.attributes)
+        .find(|attr| attr.key == "return_amount")
+        .map(|attr| attr.value.clone());
+    assert_eq!(
+        return_amount.unwrap(),
+        stable_simulation.return_amount.to_string()
+    );
+
+    // the tax withheld from the return stays in the reserves
+    let tax_amount = res
+        .events
+        .iter()
+        .flat_map(|event| &event.attributes)
+        .find(|attr| attr.key == "tax_amount")
+        .map(|attr| attr.value.clone());
+    assert_ne!(tax_amount.unwrap(), "0");
+    assert_reserves_match_balances(&app, &pair_addrs[1]);
+}
+
+#[test]
+fn test_price_cumulative() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let reserves = [Uint128::from(1000u128), Uint128::from(4000u128)];
+
+    // first update only initializes the accumulator
+    update_price_cumulative(deps.as_mut().storage, &env, reserves).unwrap();
+    let cumulative = PRICE_CUMULATIVE.load(&deps.storage).unwrap();
+    assert_eq!(cumulative.price0_cumulative, Decimal256::zero());
+    assert_eq!(OBSERVATION_COUNT.load(&deps.storage).unwrap(), 1);
+
+    // price0 = 4, price1 = 0.25 during 100 seconds
+    env.block.time = env.block.time.plus_seconds(100);
+    update_price_cumulative(deps.as_mut().storage, &env, reserves).unwrap();
+    let cumulative = PRICE_CUMULATIVE.load(&deps.storage).unwrap();
+    assert_eq!(cumulative.timestamp, env.block.time.seconds());
+    assert_eq!(
+        cumulative.price0_cumulative,
+        Decimal256::from_ratio(400u128, 1u128)
+    );
+    assert_eq!(
+        cumulative.price1_cumulative,
+        Decimal256::from_ratio(25u128, 1u128)
+    );
+    assert_eq!(OBSERVATIONS.load(&deps.storage, 1).unwrap(), cumulative);
+
+    // no new observation within the observation period, but prices still accrue
+    env.block.time = env.block.time.plus_seconds(30);
+    update_price_cumulative(
+        deps.as_mut().storage,
+        &env,
+        [Uint128::from(2000u128), Uint128::from(2000u128)],
+    )
+    .unwrap();
+    let cumulative = PRICE_CUMULATIVE.load(&deps.storage).unwrap();
+    assert_eq!(
+        cumulative.price0_cumulative,
+        Decimal256::from_ratio(430u128, 1u128)
+    );
+    assert_eq!(OBSERVATION_COUNT.load(&deps.storage).unwrap(), 2);
+
+    // empty pool does not accrue
+    env.block.time = env.block.time.plus_seconds(60);
+    update_price_cumulative(
+        deps.as_mut().storage,
+        &env,
+        [Uint128::zero(), Uint128::zero()],
+    )
+    .unwrap();
+    let cumulative = PRICE_CUMULATIVE.load(&deps.storage).unwrap();
+    assert_eq!(
+        cumulative.price0_cumulative,
+        Decimal256::from_ratio(430u128, 1u128)
+    );
+    assert_eq!(OBSERVATION_COUNT.load(&deps.storage).unwrap(), 3);
+}
+
+#[test]
+fn test_twap_query() {
+    let mut app = MockApp::new(&[(
+        &"addr0000".to_string(),
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_token_balances(&[(
+        &"asset".to_string(),
+        &[(&"addr0000".to_string(), 1000000u128)],
+    )])
+    .unwrap();
+    let token_addr = app.get_token_addr("asset").unwrap();
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: token_addr.clone(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        oracle_addr: app.oracle_addr.clone(),
+        commission_rate: None,
+        admin: None,
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("owner"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked("addr0000"),
+        token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::IncreaseAllowance {
+            spender: pair_addr.to_string(),
+            amount: Uint128::from(1000000u128),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked("addr0000"),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(100000u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: token_addr.clone(),
+                    },
+                    amount: Uint128::from(200000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(100000u128),
+        }],
+    )
+    .unwrap();
+
+    // accumulator starts at the first deposit
+    let cumulative: PriceCumulativeResponse = app
+        .query(pair_addr.clone(), &QueryMsg::PriceCumulative {})
+        .unwrap();
+    assert_eq!(cumulative.price0_cumulative, Decimal256::zero());
+
+    // not enough history yet
+    let res: StdResult<TwapResponse> =
+        app.query(pair_addr.clone(), &QueryMsg::Twap { window_seconds: 0 });
+    assert!(res.is_err());
+    let res: StdResult<TwapResponse> = app.query(
+        pair_addr.clone(),
+        &QueryMsg::Twap {
+            window_seconds: 3600,
+        },
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_swap_exact_out() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(10000000u128),
+        }],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_token_balances(&[(
+        &"asset".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), 10000000u128)],
+    )])
+    .unwrap();
+    app.set_tax(Decimal::zero(), &[(ORAI_DENOM, 0u128)]);
+    let token_addr = app.get_token_addr("asset").unwrap();
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: token_addr.clone(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: None,
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("owner"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::IncreaseAllowance {
+            spender: pair_addr.to_string(),
+            amount: Uint128::from(1000000u128),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: token_addr.clone(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    )
+    .unwrap();
+
+    // max offer amount is too small
+    let msg = ExecuteMsg::SwapExactOut {
+        offer_info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        max_offer_amount: Uint128::from(10000u128),
+        ask_amount: Uint128::from(10000u128),
+        to: Some(Addr::unchecked("addr0001")),
+        deadline: None,
+    };
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &msg,
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    )
+    .unwrap_err();
+
+    // receiver gets exactly the ask amount, the sender is refunded the rest
+    let msg = ExecuteMsg::SwapExactOut {
+        offer_info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        max_offer_amount: Uint128::from(20000u128),
+        ask_amount: Uint128::from(10000u128),
+        to: Some(Addr::unchecked("addr0001")),
+        deadline: None,
+    };
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &msg,
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(20000u128),
+        }],
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_token_balance(
+            &app.as_querier().into_empty(),
+            token_addr.clone(),
+            Addr::unchecked("addr0001"),
+        )
+        .unwrap(),
+        Uint128::from(10000u128)
+    );
+    let spent = Uint128::from(10000000u128 - 1000000u128)
+        - app
+            .query_balance(Addr::unchecked(MOCK_CONTRACT_ADDR), ORAI_DENOM.to_string())
+            .unwrap();
+    // 10000 / (1 - 0.003) plus the price impact
+    assert!(spent > Uint128::from(10130u128) && spent < Uint128::from(10140u128));
+    assert_eq!(
+        app.query_balance(pair_addr.clone(), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(1000000u128) + spent
+    );
+
+    // cw20 offer
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: pair_addr.to_string(),
+            amount: Uint128::from(10000u128),
+            msg: to_json_binary(&Cw20HookMsg::SwapExactOut {
+                ask_amount: Uint128::from(5000u128),
+                to: Some("addr0001".to_string()),
+                deadline: None,
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        app.query_balance(Addr::unchecked("addr0001"), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(5000u128)
+    );
+}
+
+mod flash_receiver {
+    use cosmwasm_std::{
+        from_json, BankMsg, Binary, Coin, Deps, DepsMut, Empty, Env, MessageInfo, Response,
+        StdResult,
+    };
+    use oraiswap::pair::FlashSwapReceiverMsg;
+
+    pub fn instantiate(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    /// repays the coin encoded in the callback msg
+    pub fn execute(
+        _deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: FlashSwapReceiverMsg,
+    ) -> StdResult<Response> {
+        let FlashSwapReceiverMsg::FlashSwapCallback { msg, .. } = msg;
+        let repay: Coin = from_json(&msg)?;
+
+        Ok(Response::new().add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![repay],
+        }))
+    }
+
+    pub fn query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        Ok(Binary::default())
+    }
+}
+
+#[test]
+fn test_flash_swap() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: None,
+        operator_fee: None,
+        operator: Some(Addr::unchecked("operator")),
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("owner"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    // receiver holds enough to pay the fee, or to repay in the other asset
+    let receiver_code_id = app.upload(Box::new(
+        oraiswap::cosmwasm_testing_util::ContractWrapper::new_with_empty(
+            flash_receiver::execute,
+            flash_receiver::instantiate,
+            flash_receiver::query,
+        ),
+    ));
+    let receiver_addr = app
+        .instantiate(
+            receiver_code_id,
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            &cosmwasm_std::Empty {},
+            &[
+                Coin {
+                    denom: ORAI_DENOM.to_string(),
+                    amount: Uint128::from(1000u128),
+                },
+                Coin {
+                    denom: ATOM_DENOM.to_string(),
+                    amount: Uint128::from(200000u128),
+                },
+            ],
+            "flash_receiver",
+        )
+        .unwrap();
+
+    let flash_fee: String = app
+        .query(pair_addr.clone(), &QueryMsg::FlashFee {})
+        .unwrap();
+    assert_eq!(flash_fee, "0.0009");
+
+    let borrowed = Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: Uint128::from(100000u128),
+    };
+
+    // repaying without the fee fails
+    app.execute(
+        Addr::unchecked("addr0000"),
+        pair_addr.clone(),
+        &ExecuteMsg::FlashSwap {
+            asset: borrowed.clone(),
+            receiver: receiver_addr.clone(),
+            msg: to_json_binary(&Coin::new(100000, ORAI_DENOM)).unwrap(),
+        },
+        &[],
+    )
+    .unwrap_err();
+
+    // repaying in the other asset would be a swap without the commission
+    let error = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            pair_addr.clone(),
+            &ExecuteMsg::FlashSwap {
+                asset: borrowed.clone(),
+                receiver: receiver_addr.clone(),
+                msg: to_json_binary(&Coin::new(200000, ATOM_DENOM)).unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("Flash swap was not repaid in the borrowed asset"));
+
+    // 0.09% of the borrowed amount goes to the operator
+    app.execute(
+        Addr::unchecked("addr0000"),
+        pair_addr.clone(),
+        &ExecuteMsg::FlashSwap {
+            asset: borrowed,
+            receiver: receiver_addr.clone(),
+            msg: to_json_binary(&Coin::new(100090, ORAI_DENOM)).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.query_balance(Addr::unchecked("operator"), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(90u128)
+    );
+    assert_eq!(
+        app.query_balance(pair_addr.clone(), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(1000000u128)
+    );
+    assert_eq!(
+        app.query_balance(receiver_addr, ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(910u128)
+    );
+}
+
+#[test]
+fn test_zap_in() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: None,
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("owner"), &msg, &[], "pair")
+        .unwrap();
+
+    let zap_in_msg = |min_lp_out: u128| ExecuteMsg::ZapIn {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            amount: Uint128::from(100000u128),
+        },
+        min_lp_out: Uint128::from(min_lp_out),
+        receiver: Some(Addr::unchecked("addr0001")),
+        deadline: None,
+    };
+    let offer_funds = [Coin {
+        denom: ORAI_DENOM.to_string(),
+        amount: Uint128::from(100000u128),
+    }];
+
+    // cannot zap into an empty pool
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &zap_in_msg(0),
+        &offer_funds,
+    )
+    .unwrap_err();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    // min lp out is not reached
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &zap_in_msg(50000),
+        &offer_funds,
+    )
+    .unwrap_err();
+
+    // 48885 orai is swapped to 46467 atom, the rest is deposited in the new ratio
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &zap_in_msg(48000),
+        &offer_funds,
+    )
+    .unwrap();
+
+    let pair_res: PairResponse = app.query(pair_addr.clone(), &QueryMsg::Pair {}).unwrap();
+    assert_eq!(
+        query_token_balance(
+            &app.as_querier().into_empty(),
+            pair_res.info.liquidity_token.unwrap(),
+            Addr::unchecked("addr0001"),
+        )
+        .unwrap(),
+        Uint128::from(48731u128)
+    );
+    assert_eq!(
+        app.query_balance(pair_addr.clone(), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(1100000u128)
+    );
+    assert_eq!(
+        app.query_balance(pair_addr, ATOM_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(1000000u128)
+    );
+}
+
+#[test]
+fn test_zap_out() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_tax(Decimal::zero(), &[(ATOM_DENOM, 0u128)]);
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: None,
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("owner"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    let pair_res: PairResponse = app.query(pair_addr.clone(), &QueryMsg::Pair {}).unwrap();
+    let zap_out_msg = |minimum_receive: u128| cw20::Cw20ExecuteMsg::Send {
+        contract: pair_addr.to_string(),
+        amount: Uint128::from(100000u128),
+        msg: to_json_binary(&Cw20HookMsg::ZapOut {
+            target_asset: AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+            minimum_receive: Uint128::from(minimum_receive),
+            to: Some("addr0001".to_string()),
+            deadline: None,
+        })
+        .unwrap(),
+    };
+
+    // minimum receive is not reached
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_res.info.liquidity_token.clone().unwrap(),
+        &zap_out_msg(200000),
+        &[],
+    )
+    .unwrap_err();
+
+    // 100000 atom withdrawn + 100000 orai swapped to 89730 atom
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_res.info.liquidity_token.clone().unwrap(),
+        &zap_out_msg(189000),
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.query_balance(Addr::unchecked("addr0001"), ATOM_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(189730u128)
+    );
+    assert_eq!(
+        app.query_balance(pair_addr.clone(), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(1000000u128)
+    );
+    assert_eq!(
+        query_token_balance(
+            &app.as_querier().into_empty(),
+            pair_res.info.liquidity_token.unwrap(),
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+        )
+        .unwrap(),
+        Uint128::from(899000u128)
+    );
+}
+
+#[test]
+fn test_dynamic_commission_rate() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let base_rate = Decimal256::from_str("0.003").unwrap();
+    let balanced = [Uint128::from(1000u128), Uint128::from(1000u128)];
+    let skewed = [Uint128::from(1000u128), Uint128::from(1100u128)];
+
+    // price stays at 1 for an hour
+    update_price_cumulative(deps.as_mut().storage, &env, balanced).unwrap();
+    env.block.time = env.block.time.plus_seconds(100);
+    update_price_cumulative(deps.as_mut().storage, &env, balanced).unwrap();
+    env.block.time = env.block.time.plus_seconds(3600);
+    update_price_cumulative(deps.as_mut().storage, &env, balanced).unwrap();
+
+    // static rate when the dynamic fee is off
+    assert_eq!(
+        compute_commission_rate(deps.as_ref().storage, &env, skewed, base_rate).unwrap(),
+        base_rate
+    );
+
+    DYNAMIC_FEE
+        .save(
+            deps.as_mut().storage,
+            &DynamicFeeParams {
+                min_commission_rate: Decimal::from_str("0.001").unwrap(),
+                max_commission_rate: Decimal::from_str("0.01").unwrap(),
+                window_seconds: 3600,
+                max_deviation: Decimal::from_str("0.2").unwrap(),
+            },
+        )
+        .unwrap();
+
+    // spot price equals the twap
+    assert_eq!(
+        compute_commission_rate(deps.as_ref().storage, &env, balanced, base_rate).unwrap(),
+        Decimal256::from_str("0.001").unwrap()
+    );
+    // 10% away from the twap is half way to the max rate
+    assert_eq!(
+        compute_commission_rate(deps.as_ref().storage, &env, skewed, base_rate).unwrap(),
+        Decimal256::from_str("0.0055").unwrap()
+    );
+    // capped at the max rate
+    assert_eq!(
+        compute_commission_rate(
+            deps.as_ref().storage,
+            &env,
+            [Uint128::from(1000u128), Uint128::from(2000u128)],
+            base_rate
+        )
+        .unwrap(),
+        Decimal256::from_str("0.01").unwrap()
+    );
+}
+
+#[test]
+fn test_update_pool_info_fee_bounds() {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        oracle_addr: Addr::unchecked("oracle"),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: 0,
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: true,
+        factory: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("factory", &[]), msg).unwrap();
+    let update_pool_info =
+        |commission_rate: Option<&str>, operator_fee: Option<&str>| ExecuteMsg::UpdatePoolInfo {
+            commission_rate: commission_rate.map(str::to_string),
+            operator_fee: operator_fee.map(str::to_string),
+            flash_fee: None,
+        };
+
+    // the commission rate must be a decimal and leave room for the operator fee
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        update_pool_info(Some("abc"), None),
+    )
+    .unwrap_err();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        update_pool_info(Some("0.999"), None),
+    )
+    .unwrap_err();
+
+    // the max dynamic fee must leave room for a new operator fee too
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateDynamicFee {
+            config: Some(DynamicFeeParams {
+                min_commission_rate: Decimal::from_str("0.001").unwrap(),
+                max_commission_rate: Decimal::from_str("0.5").unwrap(),
+                window_seconds: 3600,
+                max_deviation: Decimal::from_str("0.2").unwrap(),
+            }),
+        },
+    )
+    .unwrap();
+    let error = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        update_pool_info(None, Some("0.5")),
+    )
+    .unwrap_err();
+    assert_eq!(error, ContractError::InvalidDynamicFee {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        update_pool_info(Some("0.01"), Some("0.1")),
+    )
+    .unwrap();
+    let pair_info = PAIR_INFO.load(deps.as_ref().storage).unwrap();
+    assert_eq!(pair_info.commission_rate, "0.01");
+    assert_eq!(pair_info.operator_fee, "0.1");
+}
+
+#[test]
+fn test_protocol_fee_accrual() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_tax(Decimal::zero(), &[(ATOM_DENOM, 0u128)]);
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        operator_fee: None,
+        operator: Some(Addr::unchecked("operator")),
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("owner"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    // only admin can turn on the accrual mode
+    let accrual_msg = ExecuteMsg::UpdateProtocolFeeAccrual { enabled: true };
+    app.execute(
+        Addr::unchecked("operator"),
+        pair_addr.clone(),
+        &accrual_msg,
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &accrual_msg,
+        &[],
+    )
+    .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::Swap {
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(10000u128),
+            },
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        },
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    )
+    .unwrap();
+
+    // operator fee stays in the pair but out of the reserves
+    assert_eq!(
+        app.query_balance(Addr::unchecked("operator"), ATOM_DENOM.to_string())
+            .unwrap(),
+        Uint128::zero()
+    );
+    let fees: ProtocolFeesResponse = app
+        .query(pair_addr.clone(), &QueryMsg::ProtocolFees {})
+        .unwrap();
+    assert!(fees.accrual_enabled);
+    assert_eq!(fees.fees[0].amount, Uint128::zero());
+    assert_eq!(fees.fees[1].amount, Uint128::from(9u128));
+
+    let pool: PoolResponse = app.query(pair_addr.clone(), &QueryMsg::Pool {}).unwrap();
+    assert_eq!(pool.assets[1].amount, Uint128::from(990129u128));
+    assert_eq!(
+        app.query_balance(pair_addr.clone(), ATOM_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(990138u128)
+    );
+
+    // anyone else cannot collect
+    app.execute(
+        Addr::unchecked("addr0000"),
+        pair_addr.clone(),
+        &ExecuteMsg::CollectProtocolFees {},
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked("operator"),
+        pair_addr.clone(),
+        &ExecuteMsg::CollectProtocolFees {},
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.query_balance(Addr::unchecked("operator"), ATOM_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(9u128)
+    );
+    let fees: ProtocolFeesResponse = app
+        .query(pair_addr.clone(), &QueryMsg::ProtocolFees {})
+        .unwrap();
+    assert_eq!(fees.fees[1].amount, Uint128::zero());
+    let pool: PoolResponse = app.query(pair_addr, &QueryMsg::Pool {}).unwrap();
+    assert_eq!(pool.assets[1].amount, Uint128::from(990129u128));
+}
+
+mod swap_hook {
+    use cosmwasm_std::{
+        to_json_vec, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult,
+    };
+    use oraiswap::pair::SwapHookMsg;
+
+    const FAIL_KEY: &[u8] = b"fail";
+    const LAST_HOOK_KEY: &[u8] = b"last_hook";
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        fail: bool,
+    ) -> StdResult<Response> {
+        deps.storage.set(FAIL_KEY, &to_json_vec(&fail)?);
+        Ok(Response::new())
+    }
+
+    /// stores the last notification, or fails when instantiated to
+    pub fn execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: SwapHookMsg,
+    ) -> StdResult<Response> {
+        if deps.storage.get(FAIL_KEY) == Some(to_json_vec(&true)?) {
+            return Err(StdError::generic_err("broken hook"));
+        }
+        deps.storage.set(LAST_HOOK_KEY, &to_json_vec(&msg)?);
+        Ok(Response::new())
+    }
+
+    pub fn query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        Ok(Binary::from(
+            deps.storage.get(LAST_HOOK_KEY).unwrap_or_default(),
+        ))
+    }
+}
+
+#[test]
+fn test_swap_hooks() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_tax(Decimal::zero(), &[(ORAI_DENOM, 0u128), (ATOM_DENOM, 0u128)]);
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        operator_fee: None,
+        operator: Some(Addr::unchecked("operator")),
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("owner"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    let hook_code_id = app.upload(Box::new(
+        oraiswap::cosmwasm_testing_util::ContractWrapper::new_with_empty(
+            swap_hook::execute,
+            swap_hook::instantiate,
+            swap_hook::query,
+        ),
+    ));
+    let hook_addr = app
+        .instantiate(hook_code_id, Addr::unchecked("owner"), &false, &[], "hook")
+        .unwrap();
+    let broken_hook_addr = app
+        .instantiate(
+            hook_code_id,
+            Addr::unchecked("owner"),
+            &true,
+            &[],
+            "broken_hook",
+        )
+        .unwrap();
+
+    // only the admin can register hooks
+    app.execute(
+        Addr::unchecked("addr0000"),
+        pair_addr.clone(),
+        &ExecuteMsg::AddSwapHook {
+            hook: hook_addr.clone(),
+        },
+        &[],
+    )
+    .unwrap_err();
+    for hook in [&broken_hook_addr, &hook_addr] {
+        app.execute(
+            Addr::unchecked("admin"),
+            pair_addr.clone(),
+            &ExecuteMsg::AddSwapHook { hook: hook.clone() },
+            &[],
+        )
+        .unwrap();
+    }
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::AddSwapHook {
+            hook: hook_addr.clone(),
+        },
+        &[],
+    )
+    .unwrap_err();
+    let hooks: Vec<Addr> = app
+        .query(pair_addr.clone(), &QueryMsg::SwapHooks {})
+        .unwrap();
+    assert_eq!(hooks, vec![broken_hook_addr.clone(), hook_addr.clone()]);
+
+    // the broken hook does not block the swap
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: Uint128::from(10000u128),
+    };
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::Swap {
+            offer_asset: offer_asset.clone(),
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        },
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    )
+    .unwrap();
+
+    let pool: PoolResponse = app.query(pair_addr.clone(), &QueryMsg::Pool {}).unwrap();
+    let hook_msg: SwapHookMsg = app
+        .query(hook_addr.clone(), &cosmwasm_std::Empty {})
+        .unwrap();
+    let SwapHookMsg::SwapHook {
+        trader,
+        offer_asset: hooked_offer_asset,
+        return_asset,
+        reserves,
+        ..
+    } = hook_msg;
+    assert_eq!(trader, Addr::unchecked(MOCK_CONTRACT_ADDR));
+    assert_eq!(hooked_offer_asset, offer_asset);
+    assert_eq!(
+        return_asset.amount,
+        Uint128::from(1000000u128)
+            - pool.assets[1].amount
+            - app
+                .query_balance(Addr::unchecked("operator"), ATOM_DENOM.to_string())
+                .unwrap()
+    );
+    assert_eq!(reserves, pool.assets);
+
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::RemoveSwapHook {
+            hook: broken_hook_addr,
+        },
+        &[],
+    )
+    .unwrap();
+    let hooks: Vec<Addr> = app.query(pair_addr, &QueryMsg::SwapHooks {}).unwrap();
+    assert_eq!(hooks, vec![hook_addr]);
+}
+
+#[test]
+fn test_swap_hook_out_of_gas() {
+    let mut deps = mock_dependencies();
+    let hook = Addr::unchecked("hook");
+    SWAP_HOOKS
+        .save(deps.as_mut().storage, &vec![hook.clone()])
+        .unwrap();
+
+    let hook_msg = SwapHookMsg::SwapHook {
+        trader: Addr::unchecked("trader"),
+        receiver: Addr::unchecked("trader"),
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            amount: Uint128::from(10000u128),
+        },
+        return_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+            amount: Uint128::from(9900u128),
+        },
+        commission_amount: Uint128::from(30u128),
+        operator_fee_amount: Uint128::from(10u128),
+        reserves: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(1010000u128),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ATOM_DENOM.to_string(),
+                },
+                amount: Uint128::from(990100u128),
+            },
+        ],
+    };
+
+    // every hook runs with a capped gas limit and its failure is caught
+    let hook_msgs = swap_hook_msgs(deps.as_ref().storage, hook_msg.clone()).unwrap();
+    assert_eq!(
+        hook_msgs,
+        vec![SubMsg {
+            id: SWAP_HOOK_REPLY_ID,
+            msg: WasmMsg::Execute {
+                contract_addr: hook.to_string(),
+                msg: to_json_binary(&hook_msg).unwrap(),
+                funds: vec![],
+            }
+            .into(),
+            gas_limit: Some(SWAP_HOOK_GAS_LIMIT),
+            reply_on: ReplyOn::Error,
+        }]
+    );
+
+    // a hook burning its whole gas limit does not revert the swap
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: SWAP_HOOK_REPLY_ID,
+            result: SubMsgResult::Err("out of gas".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "swap_hook_failed"),
+            attr("error", "out of gas")
+        ]
+    );
+}
+
+#[test]
+fn test_pause_and_circuit_breaker() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_tax(Decimal::permille(3), &[(ATOM_DENOM, 1000000u128)]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+
+    // the instantiator is not the factory unless it says so
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("factory"), &msg, &[], "pair")
+        .unwrap();
+    app.execute(
+        Addr::unchecked("factory"),
+        pair_addr,
+        &ExecuteMsg::UpdatePauseStatus {
+            swap: Some(true),
+            provide: None,
+            withdraw: None,
+        },
+        &[],
+    )
+    .unwrap_err();
+
+    let msg = InstantiateMsg {
+        factory: Some(Addr::unchecked("factory")),
+        ..msg
+    };
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("factory"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    let swap = |app: &mut MockApp, amount: u128| {
+        app.execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pair_addr.clone(),
+            &ExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(amount),
+                },
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                deadline: None,
+            },
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+    };
+
+    // only the admin and the factory can pause
+    app.execute(
+        Addr::unchecked("addr0000"),
+        pair_addr.clone(),
+        &ExecuteMsg::UpdatePauseStatus {
+            swap: Some(true),
+            provide: None,
+            withdraw: None,
+        },
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked("factory"),
+        pair_addr.clone(),
+        &ExecuteMsg::UpdatePauseStatus {
+            swap: Some(true),
+            provide: None,
+            withdraw: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let error = swap(&mut app, 10000).unwrap_err();
+    assert!(error.root_cause().to_string().contains("Swaps are paused"));
+
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::UpdatePauseStatus {
+            swap: Some(false),
+            provide: None,
+            withdraw: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // a 5% price move trips the breaker
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::UpdateCircuitBreaker {
+            max_price_change: Some(Decimal::percent(5)),
+        },
+        &[],
+    )
+    .unwrap();
+    swap(&mut app, 10000).unwrap();
+    let status: StatusResponse = app.query(pair_addr.clone(), &QueryMsg::Status {}).unwrap();
+    assert!(!status.swap_paused);
+
+    // the tripping swap still goes through
+    swap(&mut app, 50000).unwrap();
+    let status: StatusResponse = app.query(pair_addr.clone(), &QueryMsg::Status {}).unwrap();
+    assert_eq!(
+        status,
+        StatusResponse {
+            swap_paused: true,
+            provide_paused: false,
+            withdraw_paused: false,
+            max_price_change: Some(Decimal::percent(5)),
+            factory: Some(Addr::unchecked("factory")),
+        }
+    );
+    swap(&mut app, 10000).unwrap_err();
+
+    // the swap legs of zaps trip the breaker as well
+    let unpause = |app: &mut MockApp| {
+        app.execute(
+            Addr::unchecked("admin"),
+            pair_addr.clone(),
+            &ExecuteMsg::UpdatePauseStatus {
+                swap: Some(false),
+                provide: None,
+                withdraw: None,
+            },
+            &[],
+        )
+        .unwrap();
+    };
+    unpause(&mut app);
+    let res = app
+        .execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pair_addr.clone(),
+            &ExecuteMsg::ZapIn {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(200000u128),
+                },
+                min_lp_out: Uint128::zero(),
+                receiver: None,
+                deadline: None,
+            },
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(200000u128),
+            }],
+        )
+        .unwrap();
+    assert!(res.has_event(&Event::new("wasm").add_attribute("circuit_breaker_tripped", "true")));
+    let status: StatusResponse = app.query(pair_addr.clone(), &QueryMsg::Status {}).unwrap();
+    assert!(status.swap_paused);
+
+    unpause(&mut app);
+    let pair_res: PairResponse = app.query(pair_addr.clone(), &QueryMsg::Pair {}).unwrap();
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_res.info.liquidity_token.unwrap(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: pair_addr.to_string(),
+            amount: Uint128::from(100000u128),
+            msg: to_json_binary(&Cw20HookMsg::ZapOut {
+                target_asset: AssetInfo::NativeToken {
+                    denom: ATOM_DENOM.to_string(),
+                },
+                minimum_receive: Uint128::zero(),
+                to: None,
+                deadline: None,
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    let status: StatusResponse = app.query(pair_addr.clone(), &QueryMsg::Status {}).unwrap();
+    assert!(status.swap_paused);
+    assert_reserves_match_balances(&app, &pair_addr);
+}
+
+#[test]
+fn test_minimum_liquidity_lock() {
+    let mut app = MockApp::new(&[]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_token_balances(&[
+        (
+            "asseta",
+            &[("attacker", 10000000u128), ("victim", 10000000u128)],
+        ),
+        (
+            "assetb",
+            &[("attacker", 10000000u128), ("victim", 10000000u128)],
+        ),
+    ])
+    .unwrap();
+    let asset_a = app.get_token_addr("asseta").unwrap();
+    let asset_b = app.get_token_addr("assetb").unwrap();
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::Token {
+                contract_addr: asset_a.clone(),
+            },
+            AssetInfo::Token {
+                contract_addr: asset_b.clone(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("owner"), &msg, &[], "pair")
+        .unwrap();
+
+    for sender in ["attacker", "victim"] {
+        for token in [&asset_a, &asset_b] {
+            app.execute(
+                Addr::unchecked(sender),
+                token.clone(),
+                &cw20::Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: pair_addr.to_string(),
+                    amount: Uint128::from(10000000u128),
+                    expires: None,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+    }
+    let provide_msg = |amount: u128| ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: asset_a.clone(),
+                },
+                amount: Uint128::from(amount),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: asset_b.clone(),
+                },
+                amount: Uint128::from(amount),
+            },
+        ],
+        slippage_tolerance: None,
+        receiver: None,
+        deadline: None,
+    };
+
+    // a dust first deposit cannot mint shares anymore
+    let error = app
+        .execute(
+            Addr::unchecked("attacker"),
+            pair_addr.clone(),
+            &provide_msg(1),
+            &[],
+        )
+        .unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("Initial liquidity must be more than 1000 LP tokens"));
+
+    // the smallest first deposit gets 1 share, 1000 are locked in the pair
+    app.execute(
+        Addr::unchecked("attacker"),
+        pair_addr.clone(),
+        &provide_msg(1001),
+        &[],
+    )
+    .unwrap();
+    let pool: PoolResponse = app.query(pair_addr.clone(), &QueryMsg::Pool {}).unwrap();
+    assert_eq!(pool.total_share, Uint128::from(1001u128));
+    assert_eq!(pool.locked_share, Uint128::from(1000u128));
+
+    // shares sent to the pair are not reported as locked
+    let PairResponse { info: pair_info } =
+        app.query(pair_addr.clone(), &QueryMsg::Pair {}).unwrap();
+    app.execute(
+        Addr::unchecked("attacker"),
+        pair_info.liquidity_token.unwrap(),
+        &cw20::Cw20ExecuteMsg::Transfer {
+            recipient: pair_addr.to_string(),
+            amount: Uint128::one(),
+        },
+        &[],
+    )
+    .unwrap();
+    let pool: PoolResponse = app.query(pair_addr.clone(), &QueryMsg::Pool {}).unwrap();
+    assert_eq!(pool.locked_share, Uint128::from(1000u128));
+
+    // a donation is not part of the tracked reserves
+    let donate = |app: &mut MockApp, amount: u128| {
+        for token in [&asset_a, &asset_b] {
+            app.execute(
+                Addr::unchecked("attacker"),
+                token.clone(),
+                &cw20::Cw20ExecuteMsg::Transfer {
+                    recipient: pair_addr.to_string(),
+                    amount: Uint128::from(amount),
+                },
+                &[],
+            )
+            .unwrap();
+        }
+    };
+    donate(&mut app, 1000000);
+    let pool: PoolResponse = app.query(pair_addr.clone(), &QueryMsg::Pool {}).unwrap();
+    assert_eq!(pool.assets[0].amount, Uint128::from(1001u128));
+    assert_eq!(pool.assets[1].amount, Uint128::from(1001u128));
+
+    // so the victim mints at the tracked share price and loses only rounding dust
+    app.execute(
+        Addr::unchecked("victim"),
+        pair_addr.clone(),
+        &provide_msg(500000),
+        &[],
+    )
+    .unwrap();
+    let PairResponse { info: pair_info } =
+        app.query(pair_addr.clone(), &QueryMsg::Pair {}).unwrap();
+    let victim_share = query_token_balance(
+        &app.as_querier().into_empty(),
+        pair_info.liquidity_token.clone().unwrap(),
+        Addr::unchecked("victim"),
+    )
+    .unwrap();
+    assert_eq!(victim_share, Uint128::from(500000u128));
+
+    app.execute(
+        Addr::unchecked("victim"),
+        pair_info.liquidity_token.unwrap(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: pair_addr.to_string(),
+            amount: victim_share,
+            msg: to_json_binary(&Cw20HookMsg::WithdrawLiquidity { deadline: None }).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        query_token_balance(
+            &app.as_querier().into_empty(),
+            asset_a.clone(),
+            Addr::unchecked("victim"),
+        )
+        .unwrap(),
+        Uint128::from(9999999u128)
+    );
+    let pool: PoolResponse = app.query(pair_addr.clone(), &QueryMsg::Pool {}).unwrap();
+    assert_eq!(pool.total_share, Uint128::from(1001u128));
+    assert_eq!(pool.assets[0].amount, Uint128::from(1002u128));
+
+    // only the admin can skim the donation out of the pair
+    app.execute(
+        Addr::unchecked("attacker"),
+        pair_addr.clone(),
+        &ExecuteMsg::Skim {
+            to: Addr::unchecked("attacker"),
+        },
+        &[],
+    )
+    .unwrap_err();
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::Skim {
+            to: Addr::unchecked("admin"),
+        },
+        &[],
+    )
+    .unwrap();
+    for token in [&asset_a, &asset_b] {
+        assert_eq!(
+            query_token_balance(
+                &app.as_querier().into_empty(),
+                token.clone(),
+                Addr::unchecked("admin"),
+            )
+            .unwrap(),
+            Uint128::from(1000000u128)
+        );
+        assert_eq!(
+            query_token_balance(
+                &app.as_querier().into_empty(),
+                token.clone(),
+                pair_addr.clone(),
+            )
+            .unwrap(),
+            Uint128::from(1002u128)
+        );
+    }
+
+    // or adopt a later donation as reserves
+    donate(&mut app, 5000);
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::Sync {},
+        &[],
+    )
+    .unwrap();
+    let pool: PoolResponse = app.query(pair_addr, &QueryMsg::Pool {}).unwrap();
+    assert_eq!(pool.assets[0].amount, Uint128::from(6002u128));
+    assert_eq!(pool.assets[1].amount, Uint128::from(6002u128));
+    assert_eq!(pool.total_share, Uint128::from(1001u128));
+}
+
+#[test]
+fn test_whitelist_queries() {
+    let mut app = MockApp::new(&[]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("factory"), &msg, &[], "pair")
+        .unwrap();
+
+    let whitelisted: bool = app
+        .query(pair_addr.clone(), &QueryMsg::WhitelistStatus {})
+        .unwrap();
+    assert!(!whitelisted);
+
+    let traders: Vec<Addr> = ["trader1", "trader2", "trader3"]
+        .iter()
+        .map(|trader| Addr::unchecked(*trader))
+        .collect();
+    let res = app
+        .execute(
+            Addr::unchecked("admin"),
+            pair_addr.clone(),
+            &ExecuteMsg::RegisterTrader {
+                traders: traders.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+    let registered: Vec<String> = res
+        .events
+        .iter()
+        .filter(|event| event.ty == "wasm-register_trader")
+        .flat_map(|event| event.attributes.iter())
+        .filter(|attr| attr.key == "trader")
+        .map(|attr| attr.value.clone())
+        .collect();
+    assert_eq!(registered, vec!["trader1", "trader2", "trader3"]);
+
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::RegisterWithdrawLp {
+            providers: vec![Addr::unchecked("provider")],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // paginate the traders
+    let page: Vec<Addr> = app
+        .query(
+            pair_addr.clone(),
+            &QueryMsg::WhitelistedTraders {
+                start_after: None,
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+    assert_eq!(page, traders[..2].to_vec());
+    let page: Vec<Addr> = app
+        .query(
+            pair_addr.clone(),
+            &QueryMsg::WhitelistedTraders {
+                start_after: Some(traders[1].clone()),
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+    assert_eq!(page, traders[2..].to_vec());
+
+    // deregistered traders are dropped from the list
+    let res = app
+        .execute(
+            Addr::unchecked("admin"),
+            pair_addr.clone(),
+            &ExecuteMsg::DeregisterTrader {
+                traders: vec![traders[1].clone()],
+            },
+            &[],
+        )
+        .unwrap();
+    assert!(res.has_event(&Event::new("wasm-deregister_trader").add_attribute("trader", "trader2")));
+    let page: Vec<Addr> = app
+        .query(
+            pair_addr.clone(),
+            &QueryMsg::WhitelistedTraders {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(page, vec![traders[0].clone(), traders[2].clone()]);
+
+    let providers: Vec<Addr> = app
+        .query(
+            pair_addr.clone(),
+            &QueryMsg::WhitelistedWithdrawLps {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(providers, vec![Addr::unchecked("provider")]);
+
+    // withdraw whitelist only applies once the pool is whitelisted
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::EnableWhitelist { status: true },
+        &[],
+    )
+    .unwrap();
+    let whitelisted: bool = app
+        .query(pair_addr.clone(), &QueryMsg::WhitelistStatus {})
+        .unwrap();
+    assert!(whitelisted);
+    for (provider, expected) in [("provider", true), ("trader1", true), ("trader2", false)] {
+        let res: bool = app
+            .query(
+                pair_addr.clone(),
+                &QueryMsg::WithdrawLpIsWhitelisted {
+                    provider: Addr::unchecked(provider),
+                },
+            )
+            .unwrap();
+        assert_eq!(res, expected);
+    }
+}
+
+#[test]
+fn test_max_price_impact() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_tax(Decimal::permille(3), &[(ATOM_DENOM, 1000000u128)]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("factory"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    let swap = |app: &mut MockApp, amount: u128| {
+        app.execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pair_addr.clone(),
+            &ExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(amount),
+                },
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                deadline: None,
+            },
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+    };
+
+    // only the admin can set the cap, and it must stay below one
+    app.execute(
+        Addr::unchecked("addr0000"),
+        pair_addr.clone(),
+        &ExecuteMsg::UpdateMaxPriceImpact {
+            max_price_impact: Some(Decimal::percent(2)),
+        },
+        &[],
+    )
+    .unwrap_err();
+    let error = app
+        .execute(
+            Addr::unchecked("admin"),
+            pair_addr.clone(),
+            &ExecuteMsg::UpdateMaxPriceImpact {
+                max_price_impact: Some(Decimal::one()),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("Max price impact must be between zero and one"));
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::UpdateMaxPriceImpact {
+            max_price_impact: Some(Decimal::percent(2)),
+        },
+        &[],
+    )
+    .unwrap();
+    let max_price_impact: Option<Decimal> = app
+        .query(pair_addr.clone(), &QueryMsg::MaxPriceImpact {})
+        .unwrap();
+    assert_eq!(max_price_impact, Some(Decimal::percent(2)));
+
+    // ~1% impact is accepted, ~3% is rejected even without max_spread
+    swap(&mut app, 10000).unwrap();
+    let error = swap(&mut app, 30000).unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("exceeds the pool limit 0.02"));
+
+    // the swap legs of zap in and zap out are capped as well
+    let error = app
+        .execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pair_addr.clone(),
+            &ExecuteMsg::ZapIn {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(100000u128),
+                },
+                min_lp_out: Uint128::zero(),
+                receiver: None,
+                deadline: None,
+            },
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(100000u128),
+            }],
+        )
+        .unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("exceeds the pool limit 0.02"));
+    let pair_res: PairResponse = app.query(pair_addr.clone(), &QueryMsg::Pair {}).unwrap();
+    let error = app
+        .execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pair_res.info.liquidity_token.unwrap(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: pair_addr.to_string(),
+                amount: Uint128::from(100000u128),
+                msg: to_json_binary(&Cw20HookMsg::ZapOut {
+                    target_asset: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    minimum_receive: Uint128::zero(),
+                    to: None,
+                    deadline: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("exceeds the pool limit 0.02"));
+
+    // removing the cap lets the trade through
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::UpdateMaxPriceImpact {
+            max_price_impact: None,
+        },
+        &[],
+    )
+    .unwrap();
+    swap(&mut app, 30000).unwrap();
+    assert_reserves_match_balances(&app, &pair_addr);
+}
+
+#[test]
+fn test_trader_fee_tier() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_tax(Decimal::permille(3), &[(ATOM_DENOM, 1000000u128)]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("factory"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    let swap = |app: &mut MockApp, amount: u128| {
+        app.execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pair_addr.clone(),
+            &ExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(amount),
+                },
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                deadline: None,
+            },
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+    };
+
+    // only the admin can assign tiers
+    let update_tier = |app: &mut MockApp, sender: &str, commission_rate: Option<&str>| {
+        app.execute(
+            Addr::unchecked(sender),
+            pair_addr.clone(),
+            &ExecuteMsg::UpdateTraderFeeTier {
+                trader: Addr::unchecked(MOCK_CONTRACT_ADDR),
+                commission_rate: commission_rate.map(|rate| rate.to_string()),
+            },
+            &[],
+        )
+    };
+    update_tier(&mut app, "factory", Some("0.001")).unwrap_err();
+    update_tier(&mut app, "admin", Some("1")).unwrap_err();
+    update_tier(&mut app, "admin", Some("0.001")).unwrap();
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::UpdateTraderFeeTier {
+            trader: Addr::unchecked(""),
+            commission_rate: Some("0.001".to_string()),
+        },
+        &[],
+    )
+    .unwrap_err();
+    let tier: Option<String> = app
+        .query(
+            pair_addr.clone(),
+            &QueryMsg::TraderFeeTier {
+                trader: Addr::unchecked(MOCK_CONTRACT_ADDR),
+            },
+        )
+        .unwrap();
+    assert_eq!(tier, Some("0.001".to_string()));
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: Uint128::from(10000u128),
+    };
+    let default_simulation: SimulationResponse = app
+        .query(
+            pair_addr.clone(),
+            &QueryMsg::Simulation {
+                offer_asset: offer_asset.clone(),
+            },
+        )
+        .unwrap();
+    let trader_simulation: SimulationResponse = app
+        .query(
+            pair_addr.clone(),
+            &QueryMsg::TraderSimulation {
+                offer_asset: offer_asset.clone(),
+                trader: Addr::unchecked(MOCK_CONTRACT_ADDR),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        trader_simulation.commission_rate,
+        Decimal256::from_str("0.001").unwrap()
+    );
+    assert!(trader_simulation.commission_amount < default_simulation.commission_amount);
+    assert!(trader_simulation.return_amount > default_simulation.return_amount);
+
+    // the swap charges the discounted commission
+    let res = swap(&mut app, 10000).unwrap();
+    let commission_amount = res
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == "commission_amount")
+        .unwrap();
+    assert_eq!(
+        commission_amount.value,
+        trader_simulation.commission_amount.to_string()
+    );
+    assert_reserves_match_balances(&app, &pair_addr);
+
+    // the swap leg of a zap charges the tier as well
+    update_tier(&mut app, "admin", Some("0")).unwrap();
+    let res = app
+        .execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pair_addr.clone(),
+            &ExecuteMsg::ZapIn {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(10000u128),
+                },
+                min_lp_out: Uint128::zero(),
+                receiver: None,
+                deadline: None,
+            },
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000u128),
+            }],
+        )
+        .unwrap();
+    assert!(res.has_event(
+        &Event::new("wasm")
+            .add_attribute("action", "zap_in")
+            .add_attribute("commission_amount", "0")
+    ));
+
+    // removing the tier restores the pool rate
+    update_tier(&mut app, "admin", None).unwrap();
+    let trader_simulation: SimulationResponse = app
+        .query(
+            pair_addr.clone(),
+            &QueryMsg::TraderSimulation {
+                offer_asset,
+                trader: Addr::unchecked(MOCK_CONTRACT_ADDR),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        trader_simulation.commission_rate,
+        default_simulation.commission_rate
+    );
+}
+
+#[test]
+fn test_deadline() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_tax(Decimal::permille(3), &[(ATOM_DENOM, 1000000u128)]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("factory"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    let swap = |app: &mut MockApp, deadline: Option<u64>| {
+        app.execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pair_addr.clone(),
+            &ExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(10000u128),
+                },
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                deadline,
+            },
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000u128),
+            }],
+        )
+    };
+
+    let error = swap(&mut app, Some(1)).unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("Deadline 1 has passed"));
+    swap(&mut app, Some(u64::MAX)).unwrap();
+    swap(&mut app, None).unwrap();
+    assert_reserves_match_balances(&app, &pair_addr);
+
+    // withdrawals through the lp token hook are checked as well
+    let pair_info: PairResponse = app.query(pair_addr.clone(), &QueryMsg::Pair {}).unwrap();
+    let error = app
+        .execute(
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pair_info.info.liquidity_token.unwrap(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: pair_addr.to_string(),
+                amount: Uint128::from(1000u128),
+                msg: to_json_binary(&Cw20HookMsg::WithdrawLiquidity { deadline: Some(1) }).unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("Deadline 1 has passed"));
+}
+
+#[test]
+fn test_pool_at_height() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    PAIR_INFO
+        .save(
+            deps.as_mut().storage,
+            &PairInfoRaw {
+                asset_infos: [
+                    AssetInfoRaw::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    AssetInfoRaw::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                ],
+                contract_addr: CanonicalAddr::from(vec![]),
+                liquidity_token: CanonicalAddr::from(vec![]),
+                liquidity_denom: None,
+                oracle_addr: CanonicalAddr::from(vec![]),
+                commission_rate: "0.003".to_string(),
+                operator_fee: "0.001".to_string(),
+                pair_type: PairType::Xyk {},
+                status: PairStatus::Active,
+                successor: None,
+            },
+        )
+        .unwrap();
+
+    // two changes in block 100, one in block 200
+    let snapshots = [
+        (100, [1000u128, 1000u128], 2000u128),
+        (100, [1100, 910], 2000),
+        (200, [1500, 1300], 2400),
+    ];
+    for (height, reserves, total_share) in snapshots {
+        env.block.height = height;
+        save_pool_snapshot(
+            deps.as_mut().storage,
+            &env,
+            [Uint128::from(reserves[0]), Uint128::from(reserves[1])],
+            Uint128::from(total_share),
+        )
+        .unwrap();
+    }
+
+    let amounts = |height: u64| {
+        query_pool_at_height(deps.as_ref(), height)
+            .unwrap()
+            .map(|pool| {
+                (
+                    pool.assets[0].amount.u128(),
+                    pool.assets[1].amount.u128(),
+                    pool.total_share.u128(),
+                )
+            })
+    };
+    assert_eq!(amounts(99), None);
+    assert_eq!(amounts(100), Some((1100, 910, 2000)));
+    assert_eq!(amounts(150), Some((1100, 910, 2000)));
+    assert_eq!(amounts(200), Some((1500, 1300, 2400)));
+    assert_eq!(amounts(300), Some((1500, 1300, 2400)));
+}
+
+#[test]
+fn test_pool_snapshots_follow_reserves() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_tax(Decimal::permille(3), &[(ATOM_DENOM, 1000000u128)]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("factory"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    // the latest snapshot must match the pool read from the balances,
+    // the tax withheld from the sent atom included
+    let assert_snapshot = |app: &MockApp| {
+        assert_reserves_match_balances(app, &pair_addr);
+        let pool: PoolResponse = app.query(pair_addr.clone(), &QueryMsg::Pool {}).unwrap();
+        let snapshot: Option<PoolAtHeightResponse> = app
+            .query(
+                pair_addr.clone(),
+                &QueryMsg::PoolAtHeight { height: u64::MAX },
+            )
+            .unwrap();
+        assert_eq!(
+            snapshot,
+            Some(PoolAtHeightResponse {
+                assets: pool.assets,
+                total_share: pool.total_share,
+            })
+        );
+    };
+    assert_snapshot(&app);
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::Swap {
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                amount: Uint128::from(10000u128),
+            },
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        },
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    )
+    .unwrap();
+    assert_snapshot(&app);
+
+    let pair_info: PairResponse = app.query(pair_addr.clone(), &QueryMsg::Pair {}).unwrap();
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_info.info.liquidity_token.unwrap(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: pair_addr.to_string(),
+            amount: Uint128::from(100000u128),
+            msg: to_json_binary(&Cw20HookMsg::WithdrawLiquidity { deadline: None }).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_snapshot(&app);
+}
+
+#[test]
+fn test_native_lp_shares() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let contract_addr = Addr::unchecked(MOCK_CONTRACT_ADDR);
+    let lp_denom = token_factory_denom(MOCK_CONTRACT_ADDR, "lp");
+
+    let msg = InstantiateMsg {
+        oracle_addr: Addr::unchecked("oracle"),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: 0,
+        commission_rate: None,
+        admin: None,
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: true,
+        factory: None,
+    };
+    let res = instantiate(deps.as_mut(), env.clone(), mock_info("factory", &[]), msg).unwrap();
+    // the denom is created directly, no cw20 instantiate reply
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].msg, create_denom_msg(&contract_addr, "lp"));
+
+    let pair_info = query_pair_info(deps.as_ref()).unwrap().info;
+    assert_eq!(pair_info.liquidity_denom, Some(lp_denom.clone()));
+    assert_eq!(pair_info.liquidity_token, None);
+    assert_eq!(
+        pair_info.liquidity_token_info().unwrap(),
+        AssetInfo::NativeToken {
+            denom: lp_denom.clone()
+        }
+    );
+
+    // the first provide mints the locked minimum to the pair and the rest to the provider
+    let funds = vec![
+        Coin::new(1_000_000, ORAI_DENOM),
+        Coin::new(1_000_000, ATOM_DENOM),
+    ];
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, funds.clone());
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("provider", &funds),
+        ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: pair_info.asset_infos[0].clone(),
+                    amount: Uint128::from(1_000_000u128),
+                },
+                Asset {
+                    info: pair_info.asset_infos[1].clone(),
+                    amount: Uint128::from(1_000_000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+    )
+    .unwrap();
+    let share = Uint128::from(1_000_000u128) - MINIMUM_LIQUIDITY_AMOUNT;
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<_>>(),
+        vec![
+            mint_msg(
+                &contract_addr,
+                &lp_denom,
+                MINIMUM_LIQUIDITY_AMOUNT,
+                &contract_addr
+            ),
+            mint_msg(
+                &contract_addr,
+                &lp_denom,
+                share,
+                &Addr::unchecked("provider")
+            ),
+        ]
+    );
+
+    // the bank supply now backs the pool share
+    let mut balances = funds.clone();
+    balances.push(Coin::new(MINIMUM_LIQUIDITY_AMOUNT.u128(), &lp_denom));
+    deps.querier.update_balance(MOCK_CONTRACT_ADDR, balances);
+    deps.querier
+        .update_balance("provider", vec![Coin::new(share.u128(), &lp_denom)]);
+    let pool = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(pool.total_share, Uint128::from(1_000_000u128));
+    assert_eq!(pool.locked_share, MINIMUM_LIQUIDITY_AMOUNT);
+
+    // withdrawals must send the LP denom as funds
+    let mut withdraw = |funds: &[Coin]| {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("provider", funds),
+            ExecuteMsg::WithdrawLiquidity { deadline: None },
+        )
+    };
+    assert_eq!(withdraw(&[]).unwrap_err(), ContractError::InvalidFunds {});
+    assert_eq!(
+        withdraw(&[Coin::new(100, ORAI_DENOM)]).unwrap_err(),
+        ContractError::InvalidFunds {}
+    );
+}
+
+#[test]
+fn test_sync_and_skim() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("factory"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    let pool_amounts = |app: &MockApp| {
+        let pool: PoolResponse = app.query(pair_addr.clone(), &QueryMsg::Pool {}).unwrap();
+        [pool.assets[0].amount.u128(), pool.assets[1].amount.u128()]
+    };
+    let orai_balance = |app: &MockApp, addr: &str| {
+        app.query_balance(Addr::unchecked(addr), ORAI_DENOM.to_string())
+            .unwrap()
+            .u128()
+    };
+
+    // a donation does not move the tracked reserves
+    app.set_balances(&[(ORAI_DENOM, &[(pair_addr.as_str(), 1005000u128)])]);
+    let surplus = orai_balance(&app, pair_addr.as_str()) - 1000000;
+    assert!(surplus > 0);
+    assert_eq!(pool_amounts(&app), [1000000, 1000000]);
+
+    // only the admin can skim or sync
+    for msg in [
+        ExecuteMsg::Sync {},
+        ExecuteMsg::Skim {
+            to: Addr::unchecked("recipient"),
+        },
+    ] {
+        app.execute(Addr::unchecked("trader"), pair_addr.clone(), &msg, &[])
+            .unwrap_err();
+    }
+
+    // skim sends out the surplus and leaves the reserves untouched
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::Skim {
+            to: Addr::unchecked("recipient"),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(orai_balance(&app, "recipient"), surplus);
+    assert_eq!(orai_balance(&app, pair_addr.as_str()), 1000000);
+    assert_eq!(pool_amounts(&app), [1000000, 1000000]);
+
+    // sync adopts a new donation into the reserves
+    app.set_balances(&[(ORAI_DENOM, &[(pair_addr.as_str(), 1005000u128)])]);
+    let balance = orai_balance(&app, pair_addr.as_str());
+    app.execute(
+        Addr::unchecked("admin"),
+        pair_addr.clone(),
+        &ExecuteMsg::Sync {},
+        &[],
+    )
+    .unwrap();
+    assert_eq!(pool_amounts(&app), [balance, 1000000]);
+}
+
+#[test]
+fn test_simulation_tax() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_tax(Decimal::zero(), &[(ORAI_DENOM, 0u128), (ATOM_DENOM, 0u128)]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        // no operator fee, the whole return goes to the trader
+        operator_fee: Some("0".to_string()),
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("factory"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    app.set_tax(Decimal::percent(1), &[(ATOM_DENOM, 1000000u128)]);
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: Uint128::from(10000u128),
+    };
+    let simulation: SimulationResponse = app
+        .query(
+            pair_addr.clone(),
+            &QueryMsg::Simulation {
+                offer_asset: offer_asset.clone(),
+            },
+        )
+        .unwrap();
+    let return_amount = simulation.return_amount;
+    assert_eq!(
+        simulation.tax_amount,
+        return_amount - return_amount * (Decimal::one() / Decimal::percent(101))
+    );
+    assert!(!simulation.tax_amount.is_zero());
+
+    // the trader receives the return without the tax
+    let atom_balance = |app: &MockApp| {
+        app.query_balance(Addr::unchecked(MOCK_CONTRACT_ADDR), ATOM_DENOM.to_string())
+            .unwrap()
+    };
+    let balance_before = atom_balance(&app);
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::Swap {
+            offer_asset,
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        },
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    )
+    .unwrap();
+    assert_eq!(
+        atom_balance(&app) - balance_before,
+        return_amount - simulation.tax_amount
+    );
+
+    // orai is never taxed
+    let simulation: SimulationResponse = app
+        .query(
+            pair_addr,
+            &QueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(10000u128),
+                },
+            },
+        )
+        .unwrap();
+    assert_eq!(simulation.tax_amount, Uint128::zero());
+}
+
+#[test]
+fn test_withdraw_tax_stays_in_reserves() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000000u128),
+            },
+        ],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_tax(Decimal::zero(), &[(ORAI_DENOM, 0u128), (ATOM_DENOM, 0u128)]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: Some(Addr::unchecked("admin")),
+        // no operator fee, the whole return goes to the trader
+        operator_fee: Some("0".to_string()),
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("factory"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ATOM_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    app.set_tax(Decimal::percent(1), &[(ATOM_DENOM, 1000000u128)]);
+
+    let PairResponse { info: pair_info } =
+        app.query(pair_addr.clone(), &QueryMsg::Pair {}).unwrap();
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_info.liquidity_token.unwrap(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: pair_addr.to_string(),
+            amount: Uint128::from(100000u128),
+            msg: to_json_binary(&Cw20HookMsg::WithdrawLiquidity { deadline: None }).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // the taxed refund leaves no untracked surplus to skim
+    let pool: PoolResponse = app.query(pair_addr.clone(), &QueryMsg::Pool {}).unwrap();
+    for asset in pool.assets.iter() {
+        assert_eq!(
+            asset.amount,
+            app.query_balance(pair_addr.clone(), asset.info.to_string())
+                .unwrap()
+        );
+    }
+    assert!(pool.assets[1].amount > Uint128::from(900000u128));
+}
+
+mod fee_on_transfer_token {
+    use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
+    use cw20_base::msg::ExecuteMsg;
+    use cw20_base::ContractError;
+
+    pub use cw20_base::contract::{instantiate, query};
+
+    /// cw20 keeping 1% of every `TransferFrom` for a fee collector
+    pub fn execute(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> Result<Response, ContractError> {
+        match msg {
+            ExecuteMsg::TransferFrom {
+                owner,
+                recipient,
+                amount,
+            } => {
+                let fee = amount.multiply_ratio(1u128, 100u128);
+                cw20_base::contract::execute(
+                    deps.branch(),
+                    env.clone(),
+                    info.clone(),
+                    ExecuteMsg::TransferFrom {
+                        owner: owner.clone(),
+                        recipient: "fee_collector".to_string(),
+                        amount: fee,
+                    },
+                )?;
+                cw20_base::contract::execute(
+                    deps,
+                    env,
+                    info,
+                    ExecuteMsg::TransferFrom {
+                        owner,
+                        recipient,
+                        amount: amount - fee,
+                    },
+                )
+            }
+            msg => cw20_base::contract::execute(deps, env, info, msg),
+        }
+    }
+}
+
+#[test]
+fn test_provide_fee_on_transfer_token() {
+    let mut app = MockApp::new(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(100000u128),
+        }],
+    )]);
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_tax(Decimal::zero(), &[(ORAI_DENOM, 0u128)]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    let token_code_id = app.upload(Box::new(
+        oraiswap::cosmwasm_testing_util::ContractWrapper::new_with_empty(
+            fee_on_transfer_token::execute,
+            fee_on_transfer_token::instantiate,
+            fee_on_transfer_token::query,
+        ),
+    ));
+    let token_addr = app
+        .instantiate(
+            token_code_id,
+            Addr::unchecked("owner"),
+            &cw20_base::msg::InstantiateMsg {
+                name: "fee token".to_string(),
+                symbol: "FEE".to_string(),
+                decimals: 6,
+                initial_balances: vec![cw20::Cw20Coin {
+                    address: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(100000u128),
+                }],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "fee token",
+        )
+        .unwrap();
+
+    let msg = InstantiateMsg {
+        oracle_addr: app.oracle_addr.clone(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: token_addr.clone(),
+            },
+        ],
+        token_code_id: app.token_id(),
+        commission_rate: None,
+        admin: None,
+        operator_fee: None,
+        operator: None,
+        stable_swap: None,
+        pair_type: None,
+        native_lp: false,
+        factory: None,
+    };
+    let code_id = app.upload(Box::new(
+        create_entry_points_testing!(crate).with_reply_empty(crate::contract::reply),
+    ));
+    let pair_addr = app
+        .instantiate(code_id, Addr::unchecked("owner"), &msg, &[], "pair")
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        token_addr.clone(),
+        &cw20::Cw20ExecuteMsg::IncreaseAllowance {
+            spender: pair_addr.to_string(),
+            amount: Uint128::from(10000u128),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // only 9900 reaches the pair, so only 9900 is credited
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(10000u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: token_addr.clone(),
+                    },
+                    amount: Uint128::from(10000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            deadline: None,
+        },
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    )
+    .unwrap();
+
+    let PairResponse { info: pair_info } =
+        app.query(pair_addr.clone(), &QueryMsg::Pair {}).unwrap();
+    // sqrt(10000 * 9900) minus the locked minimum liquidity
+    assert_eq!(
+        query_token_balance(
+            &app.as_querier().into_empty(),
+            pair_info.liquidity_token.unwrap(),
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+        )
+        .unwrap(),
+        Uint128::from(9949u128) - MINIMUM_LIQUIDITY_AMOUNT
+    );
+    let pool: PoolResponse = app.query(pair_addr.clone(), &QueryMsg::Pool {}).unwrap();
+    assert_eq!(pool.assets[1].amount, Uint128::from(9900u128));
+
+    // nobody but the pair itself can mint for received deposits
+    app.execute(
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        pair_addr.clone(),
+        &ExecuteMsg::ProvideReceivedLiquidity {
+            receiver: Addr::unchecked(MOCK_CONTRACT_ADDR),
+            pools: pool.assets,
+            deposits: [Uint128::from(10000u128), Uint128::from(10000u128)],
+            balances_before: vec![],
+            slippage_tolerance: None,
+        },
+        &[],
+    )
+    .unwrap_err();
+}
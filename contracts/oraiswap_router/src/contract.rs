@@ -13,7 +13,7 @@ use crate::state::{Config, CONFIG};
 use cw20::Cw20ReceiveMsg;
 use oraiswap::asset::{Asset, AssetInfo, PairInfo};
 use oraiswap::oracle::OracleContract;
-use oraiswap::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
+use oraiswap::pair::{assert_deadline, QueryMsg as PairQueryMsg, SimulationResponse};
 use oraiswap::querier::{query_pair_config, query_pair_info};
 use oraiswap::router::{
     ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
@@ -56,7 +56,11 @@ pub fn execute(
             operations,
             minimum_receive,
             to,
-        } => execute_swap_operations(deps, env, info.sender, operations, minimum_receive, to),
+            deadline,
+        } => {
+            assert_deadline(env.block.time, deadline)?;
+            execute_swap_operations(deps, env, info.sender, operations, minimum_receive, to)
+        }
         ExecuteMsg::ExecuteSwapOperation {
             operation,
             to,
@@ -92,7 +96,9 @@ pub fn receive_cw20(
             operations,
             minimum_receive,
             to,
+            deadline,
         } => {
+            assert_deadline(env.block.time, deadline)?;
             let receiver = to.and_then(|addr| deps.api.addr_validate(addr.as_str()).ok());
             execute_swap_operations(deps, env, sender, operations, minimum_receive, receiver)
         }
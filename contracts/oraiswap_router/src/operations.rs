@@ -189,6 +189,7 @@ fn asset_into_swap_msg(
                     belief_price: None,
                     max_spread,
                     to,
+                    deadline: None,
                 })?,
             }))
         }
@@ -202,6 +203,7 @@ fn asset_into_swap_msg(
                     belief_price: None,
                     max_spread,
                     to,
+                    deadline: None,
                 })?,
             })?,
         })),
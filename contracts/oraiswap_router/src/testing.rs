@@ -14,11 +14,11 @@ fn simulate_swap_operations_test() {
         &[
             Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(1000u128),
+                amount: Uint128::from(100000u128),
             },
             Coin {
                 denom: ATOM_DENOM.to_string(),
-                amount: Uint128::from(1000u128),
+                amount: Uint128::from(100000u128),
             },
         ],
     )]);
@@ -67,17 +67,18 @@ fn simulate_swap_operations_test() {
                 info: AssetInfo::NativeToken {
                     denom: ATOM_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
@@ -88,11 +89,11 @@ fn simulate_swap_operations_test() {
             &[
                 Coin {
                     denom: ORAI_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
                 Coin {
                     denom: ATOM_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
             ],
         )
@@ -111,7 +112,7 @@ fn simulate_swap_operations_test() {
         .unwrap();
 
     let msg = QueryMsg::SimulateSwapOperations {
-        offer_amount: Uint128::from(100u128),
+        offer_amount: Uint128::from(10000u128),
         operations: vec![SwapOperation::OraiSwap {
             offer_asset_info: AssetInfo::NativeToken {
                 denom: ORAI_DENOM.to_string(),
@@ -133,11 +134,11 @@ fn execute_swap_operations() {
         &[
             Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(1000u128),
+                amount: Uint128::from(100000u128),
             },
             Coin {
                 denom: ATOM_DENOM.to_string(),
-                amount: Uint128::from(1000u128),
+                amount: Uint128::from(100000u128),
             },
         ],
     )]);
@@ -199,17 +200,18 @@ fn execute_swap_operations() {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::Token {
                     contract_addr: asset_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     // set allowance
@@ -218,7 +220,7 @@ fn execute_swap_operations() {
         asset_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: pair_addr1.to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(10000u128),
             expires: None,
         },
         &[],
@@ -232,7 +234,7 @@ fn execute_swap_operations() {
             &msg,
             &[Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             }],
         )
         .unwrap();
@@ -243,17 +245,18 @@ fn execute_swap_operations() {
                 info: AssetInfo::NativeToken {
                     denom: ATOM_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::Token {
                     contract_addr: asset_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     // set allowance
@@ -262,7 +265,7 @@ fn execute_swap_operations() {
         asset_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: pair_addr2.to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(10000u128),
             expires: None,
         },
         &[],
@@ -276,7 +279,7 @@ fn execute_swap_operations() {
             &msg,
             &[Coin {
                 denom: ATOM_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             }],
         )
         .unwrap();
@@ -297,6 +300,7 @@ fn execute_swap_operations() {
         operations: vec![],
         minimum_receive: None,
         to: None,
+        deadline: None,
     };
 
     let error = app
@@ -307,6 +311,21 @@ fn execute_swap_operations() {
         .to_string()
         .contains("must provide operations"));
 
+    // expired messages are rejected before the operations are checked
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: vec![],
+        minimum_receive: None,
+        to: None,
+        deadline: Some(1),
+    };
+    let error = app
+        .execute(Addr::unchecked("addr0000"), router_addr.clone(), &msg, &[])
+        .unwrap_err();
+    assert!(error
+        .root_cause()
+        .to_string()
+        .contains("Deadline 1 has passed"));
+
     let msg = ExecuteMsg::ExecuteSwapOperations {
         operations: vec![
             SwapOperation::OraiSwap {
@@ -328,6 +347,7 @@ fn execute_swap_operations() {
         ],
         minimum_receive: None,
         to: None,
+        deadline: None,
     };
 
     let res = app
@@ -338,11 +358,11 @@ fn execute_swap_operations() {
             &[
                 Coin {
                     denom: ORAI_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
                 Coin {
                     denom: ATOM_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
             ],
         )
@@ -400,11 +420,11 @@ fn execute_swap_operations() {
             &[
                 Coin {
                     denom: ORAI_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
                 Coin {
                     denom: ATOM_DENOM.to_string(),
-                    amount: Uint128::from(100u128),
+                    amount: Uint128::from(10000u128),
                 },
             ],
         )
@@ -443,11 +463,11 @@ fn execute_swap_operations() {
         &[
             Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Coin {
                 denom: ATOM_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
     )
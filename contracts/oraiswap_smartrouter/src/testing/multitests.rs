@@ -116,15 +116,16 @@ fn get_smart_router_test() {
         assets: [
             Asset {
                 info: oraix_info.clone(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(2000u128),
             },
             Asset {
                 info: usdc_info.clone(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(2000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
@@ -150,6 +151,7 @@ fn get_smart_router_test() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
@@ -178,6 +180,7 @@ fn get_smart_router_test() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
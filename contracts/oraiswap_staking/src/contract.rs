@@ -10,12 +10,12 @@ use crate::rewards::{
     deposit_reward, process_reward_assets, query_all_reward_infos, query_reward_info,
     withdraw_reward, withdraw_reward_others,
 };
-use crate::staking::{auto_stake, auto_stake_hook, bond, unbond};
+use crate::staking::{auto_stake, auto_stake_hook, bond, bond_native, unbond};
 use crate::state::{
     read_all_pool_infos, read_config, read_finish_migrate_store_status, read_pool_info,
-    read_rewards_per_sec, remove_pool_info, stakers_read, store_config,
-    store_finish_migrate_store_status, store_pool_info, store_rewards_per_sec, Config,
-    MigrationParams, PoolInfo,
+    read_rewards_per_sec, remove_pool_info, stakers_read, staking_token_from_key,
+    staking_token_key, store_config, store_finish_migrate_store_status, store_pool_info,
+    store_rewards_per_sec, Config, MigrationParams, PoolInfo,
 };
 
 use cosmwasm_std::{
@@ -60,6 +60,7 @@ pub fn instantiate(
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
+        ExecuteMsg::Bond {} => bond_native(deps, info),
         ExecuteMsg::UpdateConfig {
             rewarder,
             owner,
@@ -120,7 +121,8 @@ pub fn receive_cw20(
             // if user is trying to bond old token, return friendly error message
             if let Some(params) = pool_info.migration_params {
                 if params.deprecated_staking_token == token_raw {
-                    let staking_token_addr = deps.api.addr_humanize(&pool_info.staking_token)?;
+                    let staking_token_addr =
+                        staking_token_from_key(deps.api, &pool_info.staking_token)?;
                     return Err(StdError::generic_err(format!(
                         "The staking token for this asset has been migrated to {}",
                         staking_token_addr
@@ -183,7 +185,7 @@ fn update_rewards_per_sec(
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?.to_vec();
+    let asset_key = staking_token_key(deps.api, &staking_token)?.to_vec();
 
     // withdraw all rewards for all stakers from this pool
     let staker_addrs = stakers_read(deps.storage, &asset_key)
@@ -226,7 +228,7 @@ fn register_asset(deps: DepsMut, info: MessageInfo, staking_token: Addr) -> StdR
     }
 
     // query asset_key from AssetInfo
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?;
+    let asset_key = staking_token_key(deps.api, &staking_token)?;
     if read_pool_info(deps.storage, &asset_key).is_ok() {
         return Err(StdError::generic_err("Asset was already registered"));
     }
@@ -262,7 +264,7 @@ fn deprecate_staking_token(
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?.to_vec();
+    let asset_key = staking_token_key(deps.api, &staking_token)?.to_vec();
     let mut pool_info = read_pool_info(deps.storage, &asset_key)?;
 
     if pool_info.migration_params.is_some() {
@@ -271,19 +273,16 @@ fn deprecate_staking_token(
         ));
     }
     let deprecated_staking_token = pool_info.staking_token;
-    let deprecated_token_addr = deps.api.addr_humanize(&deprecated_staking_token)?;
+    let deprecated_token_addr = staking_token_from_key(deps.api, &deprecated_staking_token)?;
 
-    pool_info.staking_token = deps.api.addr_canonicalize(new_staking_token.as_str())?;
+    pool_info.staking_token = staking_token_key(deps.api, &new_staking_token)?;
 
     // mark old pool as migration
     pool_info.migration_params = Some(MigrationParams {
         index_snapshot: pool_info.reward_index,
         deprecated_staking_token,
     });
-    let new_asset_key = deps
-        .api
-        .addr_canonicalize(new_staking_token.as_str())?
-        .to_vec();
+    let new_asset_key = staking_token_key(deps.api, &new_staking_token)?.to_vec();
     // remove old pool
     remove_pool_info(deps.storage, &asset_key);
     store_pool_info(deps.storage, &new_asset_key, &pool_info)?;
@@ -341,18 +340,18 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
 }
 
 pub fn query_pool_info(deps: Deps, staking_token: Addr) -> StdResult<PoolInfoResponse> {
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?;
+    let asset_key = staking_token_key(deps.api, &staking_token)?;
     let pool_info = read_pool_info(deps.storage, &asset_key)?;
     Ok(PoolInfoResponse {
-        staking_token: deps.api.addr_humanize(&pool_info.staking_token)?,
+        staking_token: staking_token_from_key(deps.api, &pool_info.staking_token)?,
         total_bond_amount: pool_info.total_bond_amount,
         reward_index: pool_info.reward_index,
         pending_reward: pool_info.pending_reward,
-        migration_deprecated_staking_token: pool_info.migration_params.clone().map(|params| {
-            deps.api
-                .addr_humanize(&params.deprecated_staking_token)
-                .unwrap()
-        }),
+        migration_deprecated_staking_token: pool_info
+            .migration_params
+            .clone()
+            .map(|params| staking_token_from_key(deps.api, &params.deprecated_staking_token))
+            .transpose()?,
         migration_index_snapshot: pool_info
             .migration_params
             .map(|params| params.index_snapshot),
@@ -360,7 +359,7 @@ pub fn query_pool_info(deps: Deps, staking_token: Addr) -> StdResult<PoolInfoRes
 }
 
 pub fn query_rewards_per_sec(deps: Deps, staking_token: Addr) -> StdResult<RewardsPerSecResponse> {
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?.to_vec();
+    let asset_key = staking_token_key(deps.api, &staking_token)?.to_vec();
 
     let raw_assets = read_rewards_per_sec(deps.storage, &asset_key)?;
 
@@ -379,8 +378,7 @@ pub fn parse_read_all_pool_infos(
     pool_infos
         .into_iter()
         .map(|(key, pool_info)| {
-            let asset_key = CanonicalAddr::from(key);
-            let staking_token = api.addr_humanize(&asset_key)?;
+            let staking_token = staking_token_from_key(api, &CanonicalAddr::from(key))?;
             Ok(QueryPoolInfoResponse {
                 asset_key: staking_token.to_string(),
                 pool_info: PoolInfoResponse {
@@ -392,7 +390,7 @@ pub fn parse_read_all_pool_infos(
                         .migration_params
                         .clone()
                         .map(|params| -> StdResult<Addr> {
-                            api.addr_humanize(&params.deprecated_staking_token)
+                            staking_token_from_key(api, &params.deprecated_staking_token)
                         })
                         .transpose()?,
                     migration_index_snapshot: pool_info
@@ -3,7 +3,8 @@ use std::convert::TryFrom;
 use crate::contract::validate_migrate_store_status;
 use crate::state::{
     read_config, read_is_migrated, read_pool_info, read_rewards_per_sec, rewards_read,
-    rewards_store, stakers_read, store_pool_info, PoolInfo, RewardInfo,
+    rewards_store, stakers_read, staking_token_from_key, staking_token_key, store_pool_info,
+    PoolInfo, RewardInfo,
 };
 use cosmwasm_std::{
     Addr, Api, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response,
@@ -33,9 +34,7 @@ pub fn deposit_reward(
     let mut rewards_amount = Uint128::zero();
 
     for reward_msg in rewards.iter() {
-        let asset_key = deps
-            .api
-            .addr_canonicalize(reward_msg.staking_token.as_str())?;
+        let asset_key = staking_token_key(deps.api, &reward_msg.staking_token)?;
         let mut pool_info: PoolInfo = read_pool_info(deps.storage, &asset_key)?;
 
         let mut normal_reward = reward_msg.total_accumulation_amount;
@@ -71,12 +70,8 @@ pub fn withdraw_reward(
 ) -> StdResult<Response> {
     validate_migrate_store_status(deps.storage)?;
     let staker_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
-    let asset_key = staking_token.and_then(|a| {
-        deps.api
-            .addr_canonicalize(a.as_str())
-            .map(|a| a.to_vec())
-            .ok()
-    });
+    let asset_key =
+        staking_token.and_then(|a| staking_token_key(deps.api, &a).map(|a| a.to_vec()).ok());
 
     let reward_assets = process_reward_assets(deps.storage, &staker_addr, &asset_key, true)?;
 
@@ -108,12 +103,8 @@ pub fn withdraw_reward_others(
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    let asset_key = staker_addr.and_then(|a| {
-        deps.api
-            .addr_canonicalize(a.as_str())
-            .map(|a| a.to_vec())
-            .ok()
-    });
+    let asset_key =
+        staker_addr.and_then(|a| staking_token_key(deps.api, &a).map(|a| a.to_vec()).ok());
     // let mut messages: Vec<CosmosMsg> = vec![];
 
     // withdraw reward for each staker
@@ -257,7 +248,7 @@ pub fn query_all_reward_infos(
 ) -> StdResult<Vec<RewardInfoResponse>> {
     // default is Ascending
     let order_by = Order::try_from(order.unwrap_or(1))?;
-    let asset_key = deps.api.addr_canonicalize(staking_token.as_str())?;
+    let asset_key = staking_token_key(deps.api, &staking_token)?;
 
     let start_after = start_after
         .and_then(|a| deps.api.addr_canonicalize(a.as_str()).ok())
@@ -303,7 +294,7 @@ fn _read_reward_infos_response(
     let reward_infos: Vec<RewardInfoResponseItem> = results
         .into_iter()
         .map(|(staking_token, mut reward_info)| {
-            let asset_key = api.addr_canonicalize(staking_token.as_str())?.to_vec();
+            let asset_key = staking_token_key(api, &staking_token)?.to_vec();
             let pool_info = read_pool_info(storage, &asset_key)?;
 
             let (pool_index, should_migrate) = if pool_info.migration_params.is_some()
@@ -346,7 +337,7 @@ fn _read_reward_infos(
 ) -> StdResult<Vec<(Addr, RewardInfo)>> {
     let rewards_bucket = rewards_read(storage, staker_addr);
     let results = if let Some(staking_token) = staking_token {
-        let asset_key = api.addr_canonicalize(staking_token.as_str())?.to_vec();
+        let asset_key = staking_token_key(api, staking_token)?.to_vec();
 
         if let Some(reward_info) = rewards_bucket.may_load(&asset_key)? {
             vec![(staking_token.clone(), reward_info)]
@@ -360,7 +351,7 @@ fn _read_reward_infos(
                 let (asset_key, reward_info) = item?;
 
                 // try convert to AssetInfo based on reward info
-                let staking_token = api.addr_humanize(&CanonicalAddr::from(asset_key))?;
+                let staking_token = staking_token_from_key(api, &CanonicalAddr::from(asset_key))?;
                 api.debug(staking_token.as_str());
                 Ok((staking_token, reward_info))
             })
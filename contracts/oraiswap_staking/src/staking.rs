@@ -2,7 +2,8 @@ use crate::contract::validate_migrate_store_status;
 use crate::rewards::before_share_change;
 use crate::state::{
     read_config, read_is_migrated, read_pool_info, rewards_read, rewards_store, stakers_store,
-    store_is_migrated, store_pool_info, Config, PoolInfo, RewardInfo,
+    staking_token_from_key, staking_token_key, store_is_migrated, store_pool_info, Config,
+    PoolInfo, RewardInfo,
 };
 use cosmwasm_std::{
     attr, to_json_binary, Addr, Api, CanonicalAddr, Coin, CosmosMsg, Decimal, DepsMut, Env,
@@ -11,8 +12,22 @@ use cosmwasm_std::{
 use cw20::Cw20ExecuteMsg;
 use oraiswap::asset::{Asset, AssetInfo, PairInfo};
 use oraiswap::pair::ExecuteMsg as PairExecuteMsg;
-use oraiswap::querier::{query_pair_info, query_token_balance};
+use oraiswap::querier::query_pair_info;
 use oraiswap::staking::ExecuteMsg;
+use oraiswap::tokenfactory::is_token_factory_denom;
+
+/// Staking token as an asset, a token factory LP share is staked by its denom
+fn staking_token_info(staking_token: &Addr) -> AssetInfo {
+    if is_token_factory_denom(staking_token.as_str()) {
+        AssetInfo::NativeToken {
+            denom: staking_token.to_string(),
+        }
+    } else {
+        AssetInfo::Token {
+            contract_addr: staking_token.clone(),
+        }
+    }
+}
 
 pub fn bond(
     deps: DepsMut,
@@ -37,6 +52,21 @@ pub fn bond(
     ]))
 }
 
+/// Bonds a token factory LP share sent as funds
+pub fn bond_native(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    validate_migrate_store_status(deps.storage)?;
+    let (denom, amount) = match info.funds.as_slice() {
+        [coin] if is_token_factory_denom(&coin.denom) => (coin.denom.clone(), coin.amount),
+        _ => {
+            return Err(StdError::generic_err(
+                "Bond requires a single token factory LP share",
+            ))
+        }
+    };
+
+    bond(deps, info.sender, Addr::unchecked(denom), amount)
+}
+
 pub fn unbond(
     deps: DepsMut,
     _env: Env,
@@ -54,16 +84,12 @@ pub fn unbond(
         amount,
     )?;
 
-    let staking_token_addr = deps.api.addr_humanize(&staking_token)?;
-    let mut messages = vec![WasmMsg::Execute {
-        contract_addr: staking_token_addr.to_string(),
-        msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-            recipient: staker_addr.to_string(),
-            amount,
-        })?,
-        funds: vec![],
+    let staking_token_addr = staking_token_from_key(deps.api, &staking_token)?;
+    let mut messages = vec![Asset {
+        info: staking_token_info(&staking_token_addr),
+        amount,
     }
-    .into()];
+    .into_msg(None, &deps.querier, staker_addr.clone())?];
 
     // withdraw pending_withdraw assets (accumulated when changing reward_per_sec)
     messages.extend(
@@ -95,10 +121,13 @@ pub fn auto_stake(
     // query pair info to obtain pair contract address
     let asset_infos: [AssetInfo; 2] = [assets[0].info.clone(), assets[1].info.clone()];
     let oraiswap_pair: PairInfo = query_pair_info(&deps.querier, factory_addr, &asset_infos)?;
+    // a token factory LP share is staked by its denom
+    let liquidity_token = match oraiswap_pair.liquidity_token_info()? {
+        AssetInfo::Token { contract_addr } => contract_addr,
+        AssetInfo::NativeToken { denom } => Addr::unchecked(denom),
+    };
 
-    let staking_token = deps
-        .api
-        .addr_canonicalize(oraiswap_pair.liquidity_token.as_str())?;
+    let staking_token = staking_token_key(deps.api, &liquidity_token)?;
     let asset_key = staking_token.as_slice();
 
     // assert the token and lp token match with pool info
@@ -109,11 +138,8 @@ pub fn auto_stake(
     }
 
     // get current lp token amount to later compute the recived amount
-    let prev_staking_token_amount = query_token_balance(
-        &deps.querier,
-        oraiswap_pair.liquidity_token.clone(),
-        env.contract.address.clone(),
-    )?;
+    let prev_staking_token_amount = staking_token_info(&liquidity_token)
+        .query_pool(&deps.querier, env.contract.address.clone())?;
 
     let mut msgs = vec![];
     let mut funds = vec![];
@@ -161,6 +187,7 @@ pub fn auto_stake(
             assets: assets.clone(),
             slippage_tolerance,
             receiver: None,
+            deadline: None,
         })?,
         funds,
     });
@@ -170,7 +197,7 @@ pub fn auto_stake(
     msgs.push(WasmMsg::Execute {
         contract_addr: env.contract.address.to_string(),
         msg: to_json_binary(&ExecuteMsg::AutoStakeHook {
-            staking_token: oraiswap_pair.liquidity_token.clone(),
+            staking_token: liquidity_token.clone(),
             staker_addr: info.sender,
             prev_staking_token_amount,
         })?,
@@ -179,7 +206,7 @@ pub fn auto_stake(
 
     Ok(Response::new().add_messages(msgs).add_attributes([
         ("action", "auto_stake"),
-        ("staking_token", oraiswap_pair.liquidity_token.as_str()),
+        ("staking_token", liquidity_token.as_str()),
     ]))
 }
 
@@ -199,7 +226,7 @@ pub fn auto_stake_hook(
 
     // stake all lp tokens received, compare with staking token amount before liquidity provision was executed
     let current_staking_token_amount =
-        query_token_balance(&deps.querier, staking_token.clone(), env.contract.address)?;
+        staking_token_info(&staking_token).query_pool(&deps.querier, env.contract.address)?;
     let amount_to_stake = current_staking_token_amount.checked_sub(prev_staking_token_amount)?;
 
     bond(deps, staker_addr, staking_token, amount_to_stake)
@@ -212,7 +239,7 @@ fn _increase_bond_amount(
     staking_token: Addr,
     amount: Uint128,
 ) -> StdResult<()> {
-    let asset_key = api.addr_canonicalize(staking_token.as_str())?.to_vec();
+    let asset_key = staking_token_key(api, &staking_token)?.to_vec();
     let mut pool_info = read_pool_info(storage, &asset_key)?;
     let mut reward_info: RewardInfo = rewards_read(storage, staker_addr)
         .load(&asset_key)
@@ -263,7 +290,7 @@ fn _decrease_bond_amount(
     staking_token: &Addr,
     amount: Uint128,
 ) -> StdResult<(CanonicalAddr, Vec<Asset>)> {
-    let asset_key = api.addr_canonicalize(staking_token.as_str())?.to_vec();
+    let asset_key = staking_token_key(api, staking_token)?.to_vec();
     let mut pool_info: PoolInfo = read_pool_info(storage, &asset_key)?;
     let mut reward_info: RewardInfo = rewards_read(storage, staker_addr).load(&asset_key)?;
     let mut reward_assets = vec![];
@@ -1,7 +1,8 @@
 use cosmwasm_schema::cw_serde;
 use oraiswap::asset::AssetRaw;
+use oraiswap::tokenfactory::is_token_factory_denom;
 
-use cosmwasm_std::{CanonicalAddr, Decimal, StdResult, Storage, Uint128};
+use cosmwasm_std::{Addr, Api, CanonicalAddr, Decimal, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
 
 pub static KEY_CONFIG: &[u8] = b"config_v2";
@@ -41,8 +42,25 @@ pub fn read_finish_migrate_store_status(storage: &dyn Storage) -> StdResult<bool
     singleton_read(storage, KEY_MIGRATE_STORE_CHECK).load()
 }
 
+/// Key of a staking token, the canonical address of a cw20 LP token or the bytes of a
+/// token factory LP denom
+pub fn staking_token_key(api: &dyn Api, staking_token: &Addr) -> StdResult<CanonicalAddr> {
+    if is_token_factory_denom(staking_token.as_str()) {
+        return Ok(CanonicalAddr::from(staking_token.as_bytes()));
+    }
+    api.addr_canonicalize(staking_token.as_str())
+}
+
+pub fn staking_token_from_key(api: &dyn Api, key: &CanonicalAddr) -> StdResult<Addr> {
+    match std::str::from_utf8(key.as_slice()) {
+        Ok(denom) if is_token_factory_denom(denom) => Ok(Addr::unchecked(denom)),
+        _ => api.addr_humanize(key),
+    }
+}
+
 #[cw_serde]
 pub struct PoolInfo {
+    /// see `staking_token_key`
     pub staking_token: CanonicalAddr,
     pub pending_reward: Uint128, // not distributed amount due to zero bonding
     pub total_bond_amount: Uint128,
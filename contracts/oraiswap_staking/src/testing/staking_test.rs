@@ -172,6 +172,112 @@ fn test_bond_tokens() {
     );
 }
 
+#[test]
+fn test_bond_native_lp_shares() {
+    let mut deps = mock_dependencies();
+    let lp_denom = "factory/pair/lp";
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+
+    // the pool of a token factory LP share is registered by its denom
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::RegisterAsset {
+            staking_token: Addr::unchecked(lp_denom),
+        },
+    )
+    .unwrap();
+
+    // only a single token factory denom can be bonded
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[coin(100u128, ORAI_DENOM)]),
+        ExecuteMsg::Bond {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Bond requires a single token factory LP share")
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[coin(100u128, "factory/other/lp")]),
+        ExecuteMsg::Bond {},
+    )
+    .unwrap_err();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[coin(100u128, lp_denom)]),
+        ExecuteMsg::Bond {},
+    )
+    .unwrap();
+
+    let expected = RewardInfoResponse {
+        staker_addr: Addr::unchecked("addr"),
+        reward_infos: vec![RewardInfoResponseItem {
+            staking_token: Addr::unchecked(lp_denom),
+            pending_reward: Uint128::zero(),
+            pending_withdraw: vec![],
+            bond_amount: Uint128::from(100u128),
+            should_migrate: None,
+        }],
+    };
+    for staking_token in [Some(Addr::unchecked(lp_denom)), None] {
+        let res: RewardInfoResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::RewardInfo {
+                    staking_token,
+                    staker_addr: Addr::unchecked("addr"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res, expected);
+    }
+
+    let pools = query_get_pools_infomation(deps.as_ref()).unwrap();
+    assert_eq!(pools.len(), 1);
+    assert_eq!(pools[0].asset_key, lp_denom);
+    assert_eq!(pools[0].pool_info.staking_token, Addr::unchecked(lp_denom));
+    assert_eq!(pools[0].pool_info.total_bond_amount, Uint128::from(100u128));
+
+    // unbonding sends the shares back as funds
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[]),
+        ExecuteMsg::Unbond {
+            staking_token: Addr::unchecked(lp_denom),
+            amount: Uint128::from(100u128),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr".to_string(),
+            amount: vec![coin(100u128, lp_denom)],
+        }))]
+    );
+}
+
 #[test]
 fn test_unbond() {
     let mut deps = mock_dependencies_with_balance(&[
@@ -396,7 +502,7 @@ fn test_auto_stake() {
         asset_addr.clone(),
         &cw20::Cw20ExecuteMsg::IncreaseAllowance {
             spender: pair_addr.to_string(),
-            amount: Uint128::from(100u128),
+            amount: Uint128::from(10000u128),
             expires: None,
         },
         &[],
@@ -411,17 +517,18 @@ fn test_auto_stake() {
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
             Asset {
                 info: AssetInfo::Token {
                     contract_addr: asset_addr.clone(),
                 },
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             },
         ],
         slippage_tolerance: None,
         receiver: None,
+        deadline: None,
     };
 
     let _res = app
@@ -431,7 +538,7 @@ fn test_auto_stake() {
             &msg,
             &[Coin {
                 denom: ORAI_DENOM.to_string(),
-                amount: Uint128::from(100u128),
+                amount: Uint128::from(10000u128),
             }],
         )
         .unwrap();
@@ -465,7 +572,7 @@ fn test_auto_stake() {
     .unwrap();
 
     let msg = ExecuteMsg::RegisterAsset {
-        staking_token: pair_info.liquidity_token.clone(),
+        staking_token: pair_info.liquidity_token.clone().unwrap(),
     };
 
     let _res = app
@@ -565,7 +672,7 @@ fn test_auto_stake() {
 
     // wrong asset
     let msg = ExecuteMsg::AutoStakeHook {
-        staking_token: pair_info.liquidity_token.clone(),
+        staking_token: pair_info.liquidity_token.clone().unwrap(),
         staker_addr: Addr::unchecked("addr"),
         prev_staking_token_amount: Uint128::zero(),
     };
@@ -573,7 +680,7 @@ fn test_auto_stake() {
 
     // valid msg
     let msg = ExecuteMsg::AutoStakeHook {
-        staking_token: pair_info.liquidity_token.clone(),
+        staking_token: pair_info.liquidity_token.clone().unwrap(),
         staker_addr: Addr::unchecked("addr"),
         prev_staking_token_amount: Uint128::zero(),
     };
@@ -595,7 +702,10 @@ fn test_auto_stake() {
         vec![
             attr("action", "bond"),
             attr("staker_addr", "addr"),
-            attr("staking_token", pair_info.liquidity_token.as_str()),
+            attr(
+                "staking_token",
+                pair_info.liquidity_token.as_ref().unwrap().as_str()
+            ),
             attr("amount", "1"),
         ]
     );
@@ -604,7 +714,7 @@ fn test_auto_stake() {
         .query(
             staking_addr.clone(),
             &QueryMsg::PoolInfo {
-                staking_token: pair_info.liquidity_token.clone(),
+                staking_token: pair_info.liquidity_token.clone().unwrap(),
             },
         )
         .unwrap();
@@ -612,7 +722,7 @@ fn test_auto_stake() {
     assert_eq!(
         pool_info,
         PoolInfoResponse {
-            staking_token: pair_info.liquidity_token.clone(),
+            staking_token: pair_info.liquidity_token.clone().unwrap(),
             total_bond_amount: Uint128::from(3u128),
             reward_index: Decimal::zero(),
             pending_reward: Uint128::zero(),
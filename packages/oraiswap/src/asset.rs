@@ -307,17 +307,91 @@ impl AssetInfoRaw {
     }
 }
 
+/// Curve type of a pair, each type is registered in the factory with its own code id
+#[cw_serde]
+#[derive(Default)]
+pub enum PairType {
+    /// Constant product x*y=k
+    #[default]
+    Xyk {},
+    /// StableSwap invariant for pegged assets
+    Stable {},
+    Custom(String),
+}
+
+impl fmt::Display for PairType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PairType::Xyk {} => write!(f, "xyk"),
+            PairType::Stable {} => write!(f, "stable"),
+            PairType::Custom(pair_type) => write!(f, "custom-{}", pair_type),
+        }
+    }
+}
+
+/// Lifecycle of a pair registered in the factory
+#[cw_serde]
+#[derive(Default)]
+pub enum PairStatus {
+    #[default]
+    Active,
+    /// Still tradable but superseded, hidden from the pair listings
+    Deprecated,
+    /// Deregistered from the factory
+    Removed,
+}
+
 // We define a custom struct for each query response
 #[cw_serde]
 pub struct PairInfo {
     pub asset_infos: [AssetInfo; 2],
     pub contract_addr: Addr,
-    pub liquidity_token: Addr,
+    /// cw20 address of the LP share, none when the share is the `liquidity_denom`
+    pub liquidity_token: Option<Addr>,
+    /// token factory denom of the LP share
+    #[serde(default)]
+    pub liquidity_denom: Option<String>,
 
     pub oracle_addr: Addr,
     pub commission_rate: String, // commission rate as swap fee, and will ve devided for liquidity provider
     #[serde(default)]
     pub operator_fee: String, // operator fee send to system
+    #[serde(default)]
+    pub pair_type: PairType,
+    #[serde(default)]
+    pub status: PairStatus,
+    /// pair replacing this one once deprecated
+    #[serde(default)]
+    pub successor: Option<Addr>,
+}
+
+impl PairInfo {
+    pub fn has_native_lp(&self) -> bool {
+        self.liquidity_denom.is_some()
+    }
+
+    /// LP share as an asset, either a cw20 token or a native denom
+    pub fn liquidity_token_info(&self) -> StdResult<AssetInfo> {
+        match (&self.liquidity_denom, &self.liquidity_token) {
+            (Some(denom), _) => Ok(AssetInfo::NativeToken {
+                denom: denom.clone(),
+            }),
+            (None, Some(contract_addr)) => Ok(AssetInfo::Token {
+                contract_addr: contract_addr.clone(),
+            }),
+            (None, None) => Err(StdError::not_found("liquidity token")),
+        }
+    }
+
+    /// Raw form of the LP share, `(liquidity_token, liquidity_denom)`
+    pub fn liquidity_token_raw(&self, api: &dyn Api) -> StdResult<(CanonicalAddr, Option<String>)> {
+        match self.liquidity_token_info()? {
+            AssetInfo::NativeToken { denom } => Ok((CanonicalAddr::from(vec![]), Some(denom))),
+            AssetInfo::Token { contract_addr } => {
+                Ok((api.addr_canonicalize(contract_addr.as_str())?, None))
+            }
+        }
+    }
 }
 
 #[cw_serde]
@@ -325,18 +399,31 @@ pub struct PairInfoRaw {
     pub asset_infos: [AssetInfoRaw; 2],
     pub contract_addr: CanonicalAddr,
     pub liquidity_token: CanonicalAddr,
+    /// token factory denom of the LP share, `liquidity_token` is empty when set
+    #[serde(default)]
+    pub liquidity_denom: Option<String>,
 
     // oracle contract
     pub oracle_addr: CanonicalAddr,
     pub commission_rate: String,
     #[serde(default)]
     pub operator_fee: String,
+    #[serde(default)]
+    pub pair_type: PairType,
+    #[serde(default)]
+    pub status: PairStatus,
+    #[serde(default)]
+    pub successor: Option<CanonicalAddr>,
 }
 
 impl PairInfoRaw {
     pub fn to_normal(&self, api: &dyn Api) -> StdResult<PairInfo> {
         Ok(PairInfo {
-            liquidity_token: api.addr_humanize(&self.liquidity_token)?,
+            // empty for a native LP share, or while the pair is being created
+            liquidity_token: (!self.liquidity_token.is_empty())
+                .then(|| api.addr_humanize(&self.liquidity_token))
+                .transpose()?,
+            liquidity_denom: self.liquidity_denom.clone(),
             contract_addr: api.addr_humanize(&self.contract_addr)?,
             oracle_addr: api.addr_humanize(&self.oracle_addr)?,
             asset_infos: [
@@ -345,6 +432,13 @@ impl PairInfoRaw {
             ],
             commission_rate: self.commission_rate.clone(),
             operator_fee: self.operator_fee.clone(),
+            pair_type: self.pair_type.clone(),
+            status: self.status.clone(),
+            successor: self
+                .successor
+                .as_ref()
+                .map(|successor| api.addr_humanize(successor))
+                .transpose()?,
         })
     }
 
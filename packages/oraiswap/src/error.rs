@@ -43,6 +43,15 @@ pub enum ContractError {
     #[error("Pair was already registered")]
     PairRegistered {},
 
+    #[error("Pair is not active")]
+    PairNotActive {},
+
+    #[error("Creating a pair requires a fee of {fee}")]
+    CreationFeeRequired { fee: String },
+
+    #[error("Funds beyond the creation fee are only accepted with provide_liquidity")]
+    UnexpectedCreationFunds {},
+
     #[error(
         "Assertion failed; minimum receive amount: {minium_receive}, swap amount: {swap_amount}"
     )]
@@ -117,9 +126,96 @@ pub enum ContractError {
     #[error("Restricted prefix existed")]
     RestrictPrefixExisted {},
 
+    #[error("Restriction rule existed, remove it before restricting again")]
+    RestrictRuleExisted {},
+
+    #[error("Restriction rule not found")]
+    RestrictionNotFound {},
+
     #[error("Creator is whitelisted already")]
     CreatorAlreadyExists {},
 
     #[error("Not found this creator")]
-    CreatorNotFound {}
+    CreatorNotFound {},
+
+    #[error("Pair is not a StableSwap pool")]
+    NotStableSwapPair {},
+
+    #[error("Invalid amplification coefficient {amp}")]
+    InvalidAmp { amp: u64 },
+
+    #[error("Invalid amplification ramp")]
+    InvalidAmpRamp {},
+
+    #[error("Pair type {pair_type} is not registered")]
+    PairTypeNotFound { pair_type: String },
+
+    #[error("Pair type {pair_type} is disabled")]
+    PairTypeDisabled { pair_type: String },
+
+    #[error("StableSwap pairs require the amplification params")]
+    MissingStableSwapParams {},
+
+    #[error("Only StableSwap pairs take the amplification params")]
+    UnexpectedStableSwapParams {},
+
+    #[error("Twap window must be greater than zero")]
+    InvalidTwapWindow {},
+
+    #[error("Not enough price observations to cover {window_seconds} seconds")]
+    TwapWindowTooLong { window_seconds: u64 },
+
+    #[error("Offer amount {offer_amount} exceeds max offer amount {max_offer_amount}")]
+    MaxOfferAssertion {
+        max_offer_amount: Uint128,
+        offer_amount: Uint128,
+    },
+
+    #[error("A flash swap is in progress")]
+    FlashSwapInProgress {},
+
+    #[error("Flash swap was not repaid in the borrowed asset with the fee")]
+    FlashSwapNotRepaid {},
+
+    #[error("Minted share {share} is less than the minimum {min_lp_out}")]
+    MinLpOutAssertion { min_lp_out: Uint128, share: Uint128 },
+
+    #[error("Invalid dynamic fee params")]
+    InvalidDynamicFee {},
+
+    #[error("Swap hook {hook} is already registered")]
+    SwapHookAlreadyRegistered { hook: String },
+
+    #[error("Swap hook {hook} is not registered")]
+    SwapHookNotFound { hook: String },
+
+    #[error("Cannot register more than {max} swap hooks")]
+    TooManySwapHooks { max: u32 },
+
+    #[error("Swaps are paused")]
+    SwapPaused {},
+
+    #[error("Providing liquidity is paused")]
+    ProvidePaused {},
+
+    #[error("Withdrawing liquidity is paused")]
+    WithdrawPaused {},
+
+    #[error("Max price change must be greater than zero")]
+    InvalidMaxPriceChange {},
+
+    #[error("Initial liquidity must be more than {min_lp_amount} LP tokens")]
+    MinimumLiquidityAmountError { min_lp_amount: Uint128 },
+
+    #[error("Max price impact must be between zero and one")]
+    InvalidMaxPriceImpact {},
+
+    #[error("Price impact {price_impact} exceeds the pool limit {max_price_impact}")]
+    PriceImpactTooHigh {
+        price_impact: Decimal,
+        max_price_impact: Decimal,
+    },
+
+    #[error("Deadline {deadline} has passed, block time is {block_time}")]
+    DeadlineExceeded { deadline: u64, block_time: u64 },
 }
@@ -1,7 +1,8 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{Addr, Binary};
 
-use crate::asset::{Asset, AssetInfo, PairInfo};
+use crate::asset::{Asset, AssetInfo, PairInfo, PairType};
+use crate::pair::StableSwapParams;
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -22,13 +23,19 @@ pub enum ExecuteMsg {
         token_code_id: Option<u64>,
         pair_code_id: Option<u64>,
     },
-    /// CreatePair instantiates pair contract
+    /// CreatePair instantiates pair contract, an asset pair holds one pool per pair type
     CreatePair {
         /// Asset infos
         asset_infos: [AssetInfo; 2],
         pair_admin: Option<String>,
         operator: Option<String>,
         provide_liquidity: Option<ProvideLiquidityParams>,
+        /// Constant product pair when omitted
+        pair_type: Option<PairType>,
+        /// Required for StableSwap pairs
+        stable_swap: Option<StableSwapParams>,
+        /// Mint the LP share as a token factory denom instead of a cw20 token
+        native_lp: Option<bool>,
     },
     AddPair {
         pair_info: PairInfo,
@@ -41,16 +48,76 @@ pub enum ExecuteMsg {
     ProvideLiquidity {
         assets: [Asset; 2],
         receiver: Addr,
+        /// Constant product pair when omitted
+        pair_type: Option<PairType>,
     },
+    /// Restrict the native denoms under `prefix`, same as a `Prefix` rule without exemptions
     RestrictAsset {
         prefix: String,
     },
+    /// Add a restriction rule, an existing rule must be unrestricted first
+    RestrictAssetRule {
+        rule: AssetRestriction,
+        exempt_creators: Vec<Addr>,
+    },
+    UnrestrictAsset {
+        rule: AssetRestriction,
+    },
     AddCreator {
         address: Addr,
     },
     RemoveCreator {
         address: Addr,
     },
+    /// Register or update the code id and default fees of a pair type
+    UpdatePairTypeConfig {
+        config: PairTypeConfig,
+    },
+    /// Set the guardian allowed to pause pairs, `None` removes it
+    UpdateGuardian {
+        guardian: Option<String>,
+    },
+    /// Pause or resume a pair, callable by the owner and the guardian
+    PausePair {
+        pair_addr: String,
+        swap: Option<bool>,
+        provide: Option<bool>,
+        withdraw: Option<bool>,
+    },
+    /// Migrate a page of registered pairs to `new_code_id`, callable by the owner.
+    /// Without `start_after` the sweep resumes after the last pair of the previous page
+    MigratePairs {
+        new_code_id: u64,
+        msg: Binary,
+        start_after: Option<[AssetInfo; 2]>,
+        /// Pair type of `start_after`, every pool of its assets is skipped when omitted
+        start_after_pair_type: Option<PairType>,
+        limit: Option<u32>,
+    },
+    /// Mark a pair as deprecated, optionally pointing to the active pair replacing it,
+    /// which can be a pool of another type for the same assets. Pair types default
+    /// to constant product
+    DeprecatePair {
+        asset_infos: [AssetInfo; 2],
+        pair_type: Option<PairType>,
+        successor: Option<[AssetInfo; 2]>,
+        successor_pair_type: Option<PairType>,
+    },
+    /// Deregister a pair, a pool of the same assets and type can be created afterwards
+    RemovePair {
+        asset_infos: [AssetInfo; 2],
+        /// Constant product pair when omitted
+        pair_type: Option<PairType>,
+    },
+    /// Add a page of the pairs registered before the reverse lookups to them, callable
+    /// by the owner. Each call resumes after the last pair of the previous page
+    IndexPairs {
+        limit: Option<u32>,
+    },
+    /// Charge `CreatePair` to senders other than the owner and the creators, `None` makes it free
+    UpdateCreationFee {
+        creation_fee: Option<CreationFee>,
+    },
 }
 
 #[cw_serde]
@@ -58,17 +125,57 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(ConfigResponse)]
     Config {},
+    /// Pool of `pair_type` for the assets. Without it the first active pool, constant
+    /// product first, or else a deprecated one
     #[returns(PairInfo)]
-    Pair { asset_infos: [AssetInfo; 2] },
+    Pair {
+        asset_infos: [AssetInfo; 2],
+        pair_type: Option<PairType>,
+    },
     #[returns(PairsResponse)]
     Pairs {
         start_after: Option<[AssetInfo; 2]>,
+        /// Pair type of `start_after`, defaults to `pair_type`. Every pool of the
+        /// `start_after` assets is skipped when both are omitted
+        start_after_pair_type: Option<PairType>,
         limit: Option<u32>,
+        pair_type: Option<PairType>,
+        /// Also list deprecated and removed pairs
+        include_inactive: Option<bool>,
     },
     #[returns(RestrictedAssetResponse)]
-    RestrictedAssets {},
+    RestrictedAssets {
+        start_after: Option<AssetRestriction>,
+        limit: Option<u32>,
+    },
     #[returns(CreatorsResponse)]
     GetCreators {},
+    #[returns(PairTypesResponse)]
+    PairTypes {},
+    #[returns(Option<Addr>)]
+    Guardian {},
+    #[returns(Option<MigratePairsProgress>)]
+    MigratePairsProgress {},
+    /// Failed migrations of the current `MigratePairs` sweep
+    #[returns(FailedPairMigrationsResponse)]
+    FailedPairMigrations {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    /// Pair minting `liquidity_token`, a cw20 address or a token factory denom
+    #[returns(PairInfo)]
+    PairByLiquidityToken { liquidity_token: String },
+    /// Active pairs containing `asset_info`
+    #[returns(PairsResponse)]
+    PairsByAsset {
+        asset_info: AssetInfo,
+        start_after: Option<[AssetInfo; 2]>,
+        /// Pair type of `start_after`, every pool of its assets is skipped when omitted
+        start_after_pair_type: Option<PairType>,
+        limit: Option<u32>,
+    },
+    #[returns(Option<CreationFee>)]
+    CreationFee {},
 }
 
 // We define a custom struct for each query response
@@ -107,12 +214,118 @@ pub struct ProvideLiquidityParams {
     pub receiver: Option<Addr>,
 }
 
+/// Assets only the whitelisted creators can pair
+#[cw_serde]
+pub enum AssetRestriction {
+    /// Native denoms under this path, e.g. `factory/orai1...`
+    Prefix {
+        prefix: String,
+    },
+    Denom {
+        denom: String,
+    },
+    Token {
+        contract_addr: Addr,
+    },
+}
+
+impl AssetRestriction {
+    /// Storage key of the rule, also used as the pagination cursor
+    pub fn key(&self) -> String {
+        match self {
+            AssetRestriction::Prefix { prefix } => format!("prefix:{}", prefix),
+            AssetRestriction::Denom { denom } => format!("denom:{}", denom),
+            AssetRestriction::Token { contract_addr } => format!("token:{}", contract_addr),
+        }
+    }
+
+    pub fn matches(&self, asset_info: &AssetInfo) -> bool {
+        match (self, asset_info) {
+            (AssetRestriction::Prefix { prefix }, AssetInfo::NativeToken { denom }) => denom
+                .strip_prefix(prefix.trim_end_matches('/'))
+                .map_or(false, |rest| rest.is_empty() || rest.starts_with('/')),
+            (AssetRestriction::Denom { denom }, AssetInfo::NativeToken { denom: asset_denom }) => {
+                denom.eq(asset_denom)
+            }
+            (
+                AssetRestriction::Token { contract_addr },
+                AssetInfo::Token {
+                    contract_addr: asset_addr,
+                },
+            ) => contract_addr.eq(asset_addr),
+            _ => false,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct RestrictedAsset {
+    pub rule: AssetRestriction,
+    pub added_by: Addr,
+    /// Allowed to pair the matched assets besides the whitelisted creators
+    pub exempt_creators: Vec<Addr>,
+}
+
 #[cw_serde]
 pub struct RestrictedAssetResponse {
+    /// Prefixes of the `Prefix` rules in the page
     pub prefixes: Vec<String>,
+    pub rules: Vec<RestrictedAsset>,
 }
 
 #[cw_serde]
 pub struct CreatorsResponse {
     pub creators: Vec<Addr>,
 }
+
+#[cw_serde]
+pub struct PairTypeConfig {
+    pub pair_type: PairType,
+    /// Pair contract code ID used to instantiate pairs of this type
+    pub code_id: u64,
+    pub commission_rate: String,
+    pub operator_fee: String,
+    /// Disabled types cannot be used to create new pairs
+    pub is_disabled: bool,
+}
+
+/// Fee paid for creating a pair, a native fee is sent along with `CreatePair` and a cw20 fee
+/// is transferred from the sender's allowance
+#[cw_serde]
+pub struct CreationFee {
+    pub fee: Asset,
+    pub collector: Addr,
+}
+
+/// Progress of the current `MigratePairs` sweep
+#[cw_serde]
+pub struct MigratePairsProgress {
+    pub new_code_id: u64,
+    /// Last pair of the previous page, the next page starts after it
+    pub last_pair: Option<[AssetInfo; 2]>,
+    /// Pair type of `last_pair`
+    #[serde(default)]
+    pub last_pair_type: Option<PairType>,
+    /// Pairs migrated successfully
+    pub migrated: u32,
+    /// Pairs whose migration failed, listed by `FailedPairMigrations`
+    pub failed: u32,
+    /// Set once a page reaches the end of the registry
+    pub done: bool,
+}
+
+#[cw_serde]
+pub struct FailedPairMigration {
+    pub pair: Addr,
+    pub error: String,
+}
+
+#[cw_serde]
+pub struct FailedPairMigrationsResponse {
+    pub failed: Vec<FailedPairMigration>,
+}
+
+#[cw_serde]
+pub struct PairTypesResponse {
+    pub pair_types: Vec<PairTypeConfig>,
+}
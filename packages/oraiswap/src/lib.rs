@@ -12,7 +12,9 @@ pub mod response;
 pub mod rewarder;
 pub mod router;
 pub mod smartrouter;
+pub mod stableswap;
 pub mod staking;
+pub mod tokenfactory;
 pub mod universal_swap_memo;
 
 #[cfg(not(target_arch = "wasm32"))]
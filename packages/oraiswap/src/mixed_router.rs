@@ -52,6 +52,7 @@ pub enum ExecuteMsg {
         minimum_receive: Option<Uint128>,
         to: Option<Addr>,
         affiliates: Option<Vec<Affiliate>>,
+        deadline: Option<u64>,
     },
 
     /// Internal use
@@ -84,6 +85,7 @@ pub enum Cw20HookMsg {
         minimum_receive: Option<Uint128>,
         to: Option<String>,
         affiliates: Option<Vec<Affiliate>>,
+        deadline: Option<u64>,
     },
 }
 
@@ -144,6 +146,7 @@ impl MixedRouterController {
                         minimum_receive,
                         to: swap_to.map(|to| to.into_string()),
                         affiliates,
+                        deadline: None,
                     })?,
                 })?,
                 funds: vec![],
@@ -156,6 +159,7 @@ impl MixedRouterController {
                     minimum_receive,
                     to: swap_to,
                     affiliates,
+                    deadline: None,
                 })?,
                 funds: vec![coin(amount.u128(), denom)],
             }
@@ -1,13 +1,13 @@
 use std::convert::TryInto;
 
 use crate::{
-    asset::{Asset, AssetInfo, PairInfo},
+    asset::{Asset, AssetInfo, PairInfo, PairStatus, PairType},
     error::ContractError,
 };
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Decimal256, StdError, Uint256};
+use cosmwasm_std::{Decimal256, StdError, Timestamp, Uint256};
 
-use cosmwasm_std::{Addr, Decimal, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 
 /// Default commission rate == 0.3%
@@ -17,6 +17,12 @@ pub const DEFAULT_COMMISSION_RATE: &str = "0.003";
 /// Default operator fee == 0.1%
 pub const DEFAULT_OPERATOR_FEE: &str = "0.001";
 
+/// Default flash swap fee == 0.09%
+pub const DEFAULT_FLASH_FEE: &str = "0.0009";
+
+/// LP amount minted to the pair itself on the first provision, never withdrawable
+pub const MINIMUM_LIQUIDITY_AMOUNT: Uint128 = Uint128::new(1000);
+
 #[cw_serde]
 pub struct InstantiateMsg {
     /// Asset infos
@@ -34,6 +40,33 @@ pub struct InstantiateMsg {
     pub operator_fee: Option<String>,
     // operator
     pub operator: Option<Addr>,
+    /// StableSwap curve params, the pair uses the constant product curve when omitted
+    pub stable_swap: Option<StableSwapParams>,
+    /// Pair type registered in the factory, derived from the curve when omitted
+    pub pair_type: Option<PairType>,
+    /// Mint LP shares as a token factory denom instead of a cw20 token
+    #[serde(default)]
+    pub native_lp: bool,
+    /// Factory registering the pair, allowed to pause it and to update its status
+    pub factory: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct DynamicFeeParams {
+    pub min_commission_rate: Decimal,
+    pub max_commission_rate: Decimal,
+    /// TWAP window the spot price is compared against
+    pub window_seconds: u64,
+    /// Relative deviation from the TWAP at which the max rate applies
+    pub max_deviation: Decimal,
+}
+
+#[cw_serde]
+pub struct StableSwapParams {
+    /// Initial amplification coefficient
+    pub amp: u64,
+    /// Decimals of asset_infos, only needed when they differ
+    pub decimals: Option<[u8; 2]>,
 }
 
 #[cw_serde]
@@ -44,6 +77,8 @@ pub enum ExecuteMsg {
         assets: [Asset; 2],
         slippage_tolerance: Option<Decimal>,
         receiver: Option<Addr>,
+        /// Block time in seconds after which the message is rejected
+        deadline: Option<u64>,
     },
     /// Swap an offer asset to the other
     Swap {
@@ -51,6 +86,44 @@ pub enum ExecuteMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<Addr>,
+        deadline: Option<u64>,
+    },
+    /// Swap the sent native offer asset for exactly `ask_amount` of the other asset,
+    /// spending at most `max_offer_amount` and refunding the rest
+    SwapExactOut {
+        offer_info: AssetInfo,
+        max_offer_amount: Uint128,
+        ask_amount: Uint128,
+        to: Option<Addr>,
+        deadline: Option<u64>,
+    },
+    /// Provide liquidity with the sent native asset only, the optimal portion
+    /// is swapped to the other asset before minting
+    ZapIn {
+        offer_asset: Asset,
+        min_lp_out: Uint128,
+        receiver: Option<Addr>,
+        deadline: Option<u64>,
+    },
+    /// Withdraw liquidity with the LP shares sent as funds, only for pairs
+    /// minting a token factory denom
+    WithdrawLiquidity {
+        deadline: Option<u64>,
+    },
+    /// `ZapOut` with the LP shares sent as funds, only for pairs minting a
+    /// token factory denom
+    ZapOut {
+        target_asset: AssetInfo,
+        minimum_receive: Uint128,
+        to: Option<Addr>,
+        deadline: Option<u64>,
+    },
+    /// Lend `asset` to `receiver` and call it back with `FlashSwapReceiverMsg`, the loan
+    /// plus the flash fee must be returned in `asset` by the end of the callback
+    FlashSwap {
+        asset: Asset,
+        receiver: Addr,
+        msg: Binary,
     },
     /// Turn on/off only whitelisted address can interact with pool
     EnableWhitelist {
@@ -74,11 +147,104 @@ pub enum ExecuteMsg {
     UpdatePoolInfo {
         commission_rate: Option<String>,
         operator_fee: Option<String>,
+        flash_fee: Option<String>,
     },
     // update operator
     UpdateOperator {
         operator: Option<String>,
     },
+    /// Turn on/off accruing the operator fee inside the pair
+    UpdateProtocolFeeAccrual {
+        enabled: bool,
+    },
+    /// Send the accrued operator fees to the operator
+    CollectProtocolFees {},
+    /// Turn on the volatility based commission, or back to the static one with `None`
+    UpdateDynamicFee {
+        config: Option<DynamicFeeParams>,
+    },
+    /// Linearly change the StableSwap amplification until `future_time`
+    RampAmp {
+        future_amp: u64,
+        future_time: u64,
+    },
+    /// Freeze the StableSwap amplification at its current value
+    StopRampAmp {},
+    /// Register a contract notified with `SwapHookMsg` after every swap
+    AddSwapHook {
+        hook: Addr,
+    },
+    RemoveSwapHook {
+        hook: Addr,
+    },
+    /// Pause or resume swaps, provides and withdrawals, callable by the admin and the factory
+    UpdatePauseStatus {
+        swap: Option<bool>,
+        provide: Option<bool>,
+        withdraw: Option<bool>,
+    },
+    /// Mirror the status of the pair in the factory registry, callable by the factory
+    UpdatePairStatus {
+        status: PairStatus,
+        successor: Option<Addr>,
+    },
+    /// Pause swaps once a single swap moves the spot price by more than `max_price_change`,
+    /// turned off with `None`
+    UpdateCircuitBreaker {
+        max_price_change: Option<Decimal>,
+    },
+    /// Reject any swap whose price impact against the pre-trade spot price is above
+    /// `max_price_impact`, turned off with `None`
+    UpdateMaxPriceImpact {
+        max_price_impact: Option<Decimal>,
+    },
+    /// Give `trader` a discounted commission rate, callable by the admin.
+    /// The pool rate still applies when it is lower, `None` removes the tier
+    UpdateTraderFeeTier {
+        trader: Addr,
+        commission_rate: Option<String>,
+    },
+    /// Adopt the pair balances as the reserves, callable by the admin
+    Sync {},
+    /// Send the balances above the reserves to `to`, callable by the admin
+    Skim {
+        to: Addr,
+    },
+    /// Callback of the pair after pulling cw20 deposits, mints the share for the
+    /// balance each deposit added on top of `balances_before`
+    ProvideReceivedLiquidity {
+        receiver: Addr,
+        pools: [Asset; 2],
+        deposits: [Uint128; 2],
+        balances_before: Vec<Asset>,
+        slippage_tolerance: Option<Decimal>,
+    },
+}
+
+/// Callback executed on the flash swap receiver
+#[cw_serde]
+pub enum FlashSwapReceiverMsg {
+    FlashSwapCallback {
+        initiator: Addr,
+        asset: Asset,
+        fee_amount: Uint128,
+        msg: Binary,
+    },
+}
+
+/// Notification executed on the registered swap hooks, a failing hook is ignored
+#[cw_serde]
+pub enum SwapHookMsg {
+    SwapHook {
+        trader: Addr,
+        receiver: Addr,
+        offer_asset: Asset,
+        return_asset: Asset,
+        commission_amount: Uint128,
+        operator_fee_amount: Uint128,
+        /// Pair reserves after the swap
+        reserves: [Asset; 2],
+    },
 }
 
 #[cw_serde]
@@ -88,6 +254,7 @@ pub enum PairExecuteMsgCw20 {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<Addr>,
+        deadline: Option<u64>,
     },
 }
 
@@ -98,8 +265,30 @@ pub enum Cw20HookMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// Buy exactly `ask_amount` of the other asset, the sent amount is the max offer amount
+    SwapExactOut {
+        ask_amount: Uint128,
+        to: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// Provide liquidity with the sent token only
+    ZapIn {
+        min_lp_out: Uint128,
+        receiver: Option<String>,
+        deadline: Option<u64>,
+    },
+    WithdrawLiquidity {
+        deadline: Option<u64>,
+    },
+    /// Withdraw liquidity in `target_asset` only, the other side is swapped back into the pool
+    ZapOut {
+        target_asset: AssetInfo,
+        minimum_receive: Uint128,
+        to: Option<String>,
+        deadline: Option<u64>,
     },
-    WithdrawLiquidity {},
 }
 
 #[cw_serde]
@@ -109,16 +298,59 @@ pub enum QueryMsg {
     Pair {},
     #[returns(PoolResponse)]
     Pool {},
+    /// Pool after the last change at or before `height`, `None` before the first recorded change
+    #[returns(Option<PoolAtHeightResponse>)]
+    PoolAtHeight { height: u64 },
     #[returns(SimulationResponse)]
     Simulation { offer_asset: Asset },
+    /// Simulation with the fee tier of `trader` applied
+    #[returns(SimulationResponse)]
+    TraderSimulation { offer_asset: Asset, trader: Addr },
+    #[returns(Option<String>)]
+    TraderFeeTier { trader: Addr },
     #[returns(ReverseSimulationResponse)]
     ReverseSimulation { ask_asset: Asset },
     #[returns(bool)]
     TraderIsWhitelisted { trader: Addr },
+    #[returns(bool)]
+    WithdrawLpIsWhitelisted { provider: Addr },
+    /// Whether the pool is restricted to whitelisted addresses
+    #[returns(bool)]
+    WhitelistStatus {},
+    #[returns(Vec<Addr>)]
+    WhitelistedTraders {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    #[returns(Vec<Addr>)]
+    WhitelistedWithdrawLps {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
     #[returns(String)]
     Admin {},
     #[returns(String)]
     Operator {},
+    #[returns(String)]
+    FlashFee {},
+    #[returns(Option<DynamicFeeParams>)]
+    DynamicFee {},
+    #[returns(ProtocolFeesResponse)]
+    ProtocolFees {},
+    #[returns(Vec<Addr>)]
+    SwapHooks {},
+    #[returns(StatusResponse)]
+    Status {},
+    #[returns(Option<Decimal>)]
+    MaxPriceImpact {},
+    #[returns(StableSwapConfigResponse)]
+    StableSwapConfig {},
+    /// Cumulative prices accrued up to the current block
+    #[returns(PriceCumulativeResponse)]
+    PriceCumulative {},
+    /// Time-weighted average prices over the last `window_seconds`
+    #[returns(TwapResponse)]
+    Twap { window_seconds: u64 },
 }
 
 // We define a custom struct for each query response
@@ -126,6 +358,15 @@ pub enum QueryMsg {
 pub struct PoolResponse {
     pub assets: [Asset; 2],
     pub total_share: Uint128,
+    /// Part of `total_share` permanently held by the pair
+    #[serde(default)]
+    pub locked_share: Uint128,
+}
+
+#[cw_serde]
+pub struct PoolAtHeightResponse {
+    pub assets: [Asset; 2],
+    pub total_share: Uint128,
 }
 
 #[cw_serde]
@@ -141,6 +382,12 @@ pub struct SimulationResponse {
     pub commission_amount: Uint128,
     #[serde(default)]
     pub operator_fee_amount: Uint128,
+    /// Commission rate applied to this swap
+    #[serde(default)]
+    pub commission_rate: Decimal256,
+    /// Tax deducted from `return_amount` when it is sent
+    #[serde(default)]
+    pub tax_amount: Uint128,
 }
 
 /// ReverseSimulationResponse returns reverse swap simulation response
@@ -151,11 +398,72 @@ pub struct ReverseSimulationResponse {
     pub commission_amount: Uint128,
 }
 
+#[cw_serde]
+pub struct StableSwapConfigResponse {
+    /// Amplification coefficient at the current block time
+    pub amp: u64,
+    pub init_amp: u64,
+    pub init_amp_time: u64,
+    pub future_amp: u64,
+    pub future_amp_time: u64,
+}
+
+#[cw_serde]
+pub struct ProtocolFeesResponse {
+    pub accrual_enabled: bool,
+    /// Operator fees accrued and not yet collected
+    pub fees: [Asset; 2],
+}
+
+#[cw_serde]
+pub struct PriceCumulativeResponse {
+    /// Sum of the price of asset_infos[0] in asset_infos[1] weighted by seconds
+    pub price0_cumulative: Decimal256,
+    /// Sum of the price of asset_infos[1] in asset_infos[0] weighted by seconds
+    pub price1_cumulative: Decimal256,
+    pub block_time: u64,
+}
+
+#[cw_serde]
+pub struct TwapResponse {
+    /// Average price of asset_infos[0] in asset_infos[1]
+    pub price0_average: Decimal256,
+    /// Average price of asset_infos[1] in asset_infos[0]
+    pub price1_average: Decimal256,
+    /// Window actually covered by the stored observations, can be longer than requested
+    pub window_seconds: u64,
+}
+
+/// Pause flags and circuit breaker of the pair
+#[cw_serde]
+pub struct StatusResponse {
+    pub swap_paused: bool,
+    pub provide_paused: bool,
+    pub withdraw_paused: bool,
+    /// Spot price move that pauses swaps, if the circuit breaker is on
+    pub max_price_change: Option<Decimal>,
+    /// Factory allowed to pause the pair
+    pub factory: Option<Addr>,
+}
+
 /// We currently take no arguments for migrations
 #[cw_serde]
 pub struct MigrateMsg {
     pub admin: Option<String>,
     pub asset_infos: Option<[AssetInfo; 2]>,
+    /// Set the factory of pairs instantiated before it was stored
+    pub factory: Option<String>,
+}
+
+/// Reject messages executed after their `deadline`, given in block time seconds
+pub fn assert_deadline(block_time: Timestamp, deadline: Option<u64>) -> Result<(), ContractError> {
+    match deadline {
+        Some(deadline) if block_time.seconds() > deadline => Err(ContractError::DeadlineExceeded {
+            deadline,
+            block_time: block_time.seconds(),
+        }),
+        _ => Ok(()),
+    }
 }
 
 pub fn compute_swap(
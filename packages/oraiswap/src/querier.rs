@@ -45,6 +45,7 @@ pub fn query_pair_info(
         factory_addr,
         &FactoryQueryMsg::Pair {
             asset_infos: asset_infos.clone(),
+            pair_type: None,
         },
     )
 }
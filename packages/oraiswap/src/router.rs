@@ -43,6 +43,7 @@ pub enum ExecuteMsg {
         operations: Vec<SwapOperation>,
         minimum_receive: Option<Uint128>,
         to: Option<Addr>,
+        deadline: Option<u64>,
     },
 
     /// Internal use
@@ -68,6 +69,7 @@ pub enum Cw20HookMsg {
         operations: Vec<SwapOperation>,
         minimum_receive: Option<Uint128>,
         to: Option<String>,
+        deadline: Option<u64>,
     },
 }
 
@@ -125,6 +127,7 @@ impl RouterController {
                         operations,
                         minimum_receive,
                         to: swap_to.map(|to| to.into_string()),
+                        deadline: None,
                     })?,
                 })?,
                 funds: vec![],
@@ -136,6 +139,7 @@ impl RouterController {
                     operations,
                     minimum_receive,
                     to: swap_to,
+                    deadline: None,
                 })?,
                 funds: vec![coin(amount.u128(), denom)],
             }
@@ -0,0 +1,428 @@
+use std::convert::{TryFrom, TryInto};
+
+use cosmwasm_std::{Decimal256, StdError, StdResult, Uint128, Uint256, Uint512};
+
+use crate::error::ContractError;
+
+/// Number of assets in a pair pool
+const N_COINS: u128 = 2;
+
+/// Maximum number of Newton iterations when solving the invariant
+const MAX_ITERATIONS: u8 = 64;
+
+/// Bounds of the amplification coefficient
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Maximum factor the amplification may be changed by in a single ramp
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+/// Minimum duration of an amplification ramp, in seconds
+pub const MIN_AMP_RAMP_TIME: u64 = 86400;
+
+/// Largest decimals of a StableSwap asset
+pub const MAX_DECIMALS: u8 = 18;
+
+fn abs_diff(a: Uint512, b: Uint512) -> Uint512 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+/// Factors scaling the amounts of assets with `decimals` to their common precision
+fn precision_factors(decimals: [u8; 2]) -> StdResult<[Uint256; 2]> {
+    let precision = std::cmp::max(decimals[0], decimals[1]);
+    if precision > MAX_DECIMALS {
+        return Err(StdError::generic_err(format!(
+            "StableSwap decimals must be at most {}",
+            MAX_DECIMALS
+        )));
+    }
+
+    Ok(decimals.map(|decimals| Uint256::from(10u128.pow((precision - decimals) as u32))))
+}
+
+/// D^3 / (n^n * x * y), divided step by step to stay small
+fn compute_d_p(d: Uint512, amount_a: Uint512, amount_b: Uint512) -> StdResult<Uint512> {
+    let n_coins = Uint512::from(N_COINS);
+    Ok(d.checked_mul(d)?
+        .checked_div(amount_a.checked_mul(n_coins)?)?
+        .checked_mul(d)?
+        .checked_div(amount_b.checked_mul(n_coins)?)?)
+}
+
+/// Computes the StableSwap invariant D for the given balances
+/// Ann * (x + y) + D = Ann * D + D^3 / (4 * x * y), with Ann = amp * n
+pub fn compute_d(amp: u64, amount_a: Uint256, amount_b: Uint256) -> StdResult<Uint256> {
+    let n_coins = Uint512::from(N_COINS);
+    let amount_a = Uint512::from(amount_a);
+    let amount_b = Uint512::from(amount_b);
+    let sum = amount_a.checked_add(amount_b)?;
+    if sum.is_zero() {
+        return Ok(Uint256::zero());
+    }
+
+    let ann = Uint512::from(amp).checked_mul(n_coins)?;
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        let d_p = compute_d_p(d, amount_a, amount_b)?;
+        let d_prev = d;
+
+        // D = (Ann * S + D_p * n) * D / ((Ann - 1) * D + (n + 1) * D_p)
+        let numerator = ann
+            .checked_mul(sum)?
+            .checked_add(d_p.checked_mul(n_coins)?)?
+            .checked_mul(d)?;
+        let denominator = ann
+            .checked_sub(Uint512::one())?
+            .checked_mul(d)?
+            .checked_add(n_coins.checked_add(Uint512::one())?.checked_mul(d_p)?)?;
+        d = numerator.checked_div(denominator)?;
+
+        if abs_diff(d, d_prev) <= Uint512::one() {
+            return Ok(Uint256::try_from(d)?);
+        }
+    }
+
+    Err(StdError::generic_err(
+        "StableSwap invariant did not converge",
+    ))
+}
+
+/// Computes the balance of one asset that keeps the invariant D
+/// given the new balance of the other asset
+pub fn compute_y(amp: u64, new_amount: Uint256, d: Uint256) -> StdResult<Uint256> {
+    let n_coins = Uint512::from(N_COINS);
+    let ann = Uint512::from(amp).checked_mul(n_coins)?;
+    let new_amount = Uint512::from(new_amount);
+    let d = Uint512::from(d);
+
+    // c = D^3 / (n^n * x * Ann)
+    let c = d
+        .checked_mul(d)?
+        .checked_div(new_amount.checked_mul(n_coins)?)?
+        .checked_mul(d)?
+        .checked_div(ann.checked_mul(n_coins)?)?;
+    let b = new_amount.checked_add(d.checked_div(ann)?)?;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        // y = (y^2 + c) / (2y + b - D)
+        y = y
+            .checked_mul(y)?
+            .checked_add(c)?
+            .checked_div(y.checked_mul(n_coins)?.checked_add(b)?.checked_sub(d)?)?;
+
+        if abs_diff(y, y_prev) <= Uint512::one() {
+            return Ok(Uint256::try_from(y)?);
+        }
+    }
+
+    Err(StdError::generic_err(
+        "StableSwap invariant did not converge",
+    ))
+}
+
+/// Marginal price of asset a in asset b, the ratio of the invariant partial derivatives
+/// (4 * Ann * a * b + D^3 / a) / (4 * Ann * a * b + D^3 / b), with the balances
+/// scaled to the precision of `decimals`, which reduces to
+/// b * (Ann * a + D_p) / (a * (Ann * b + D_p)) in the unscaled amounts
+pub fn compute_stable_spot_price(
+    amp: u64,
+    amount_a: Uint256,
+    amount_b: Uint256,
+    decimals: [u8; 2],
+) -> StdResult<Decimal256> {
+    let factors = precision_factors(decimals)?;
+    let scaled_a = Uint512::from(amount_a.checked_mul(factors[0])?);
+    let scaled_b = Uint512::from(amount_b.checked_mul(factors[1])?);
+    let ann = Uint512::from(amp).checked_mul(Uint512::from(N_COINS))?;
+    let d = Uint512::from(compute_d(
+        amp,
+        amount_a.checked_mul(factors[0])?,
+        amount_b.checked_mul(factors[1])?,
+    )?);
+    let d_p = compute_d_p(d, scaled_a, scaled_b)?;
+
+    let numerator = Uint512::from(amount_b)
+        .checked_mul(ann.checked_mul(scaled_a)?.checked_add(d_p)?)?
+        .checked_mul(Uint512::from(Decimal256::one().atomics()))?;
+    let denominator =
+        Uint512::from(amount_a).checked_mul(ann.checked_mul(scaled_b)?.checked_add(d_p)?)?;
+
+    Ok(Decimal256::new(Uint256::try_from(
+        numerator.checked_div(denominator)?,
+    )?))
+}
+
+/// StableSwap counterpart of `pair::compute_swap`, spread is measured against the
+/// pre-trade spot price. `decimals` are the ones of the offer and the ask asset
+pub fn compute_stable_swap(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    offer_amount: Uint128,
+    amp: u64,
+    decimals: [u8; 2],
+    commission_rate: Decimal256,
+    operator_fee: Decimal256,
+) -> Result<(Uint128, Uint128, Uint128, Uint128), ContractError> {
+    if offer_pool.is_zero() {
+        return Err(ContractError::OfferPoolIsZero {});
+    }
+
+    let factors = precision_factors(decimals)?;
+    let offer_pool: Uint256 = offer_pool.into();
+    let ask_pool: Uint256 = ask_pool.into();
+    let offer_amount: Uint256 = offer_amount.into();
+
+    let scaled_ask_pool = ask_pool.checked_mul(factors[1])?;
+    let d = compute_d(amp, offer_pool.checked_mul(factors[0])?, scaled_ask_pool)?;
+    let new_ask_pool = compute_y(
+        amp,
+        offer_pool
+            .checked_add(offer_amount)?
+            .checked_mul(factors[0])?,
+        d,
+    )?;
+
+    // round down by one unit in favour of the pool
+    let return_amount = scaled_ask_pool
+        .checked_sub(new_ask_pool)?
+        .checked_div(factors[1])
+        .map_err(StdError::from)?
+        .saturating_sub(Uint256::one());
+
+    // calculate spread & commission
+    let spot_return =
+        offer_amount * compute_stable_spot_price(amp, offer_pool, ask_pool, decimals)?;
+    let spread_amount = spot_return.saturating_sub(return_amount);
+
+    let commission_amount = return_amount * commission_rate;
+    let operator_fee = return_amount * operator_fee;
+
+    // commission will be absorbed to pool
+    let return_amount = return_amount - commission_amount - operator_fee;
+    Ok((
+        return_amount.try_into().map_err(StdError::from)?,
+        spread_amount.try_into().map_err(StdError::from)?,
+        commission_amount.try_into().map_err(StdError::from)?,
+        operator_fee.try_into().map_err(StdError::from)?,
+    ))
+}
+
+/// StableSwap counterpart of `pair::compute_offer_amount`, `decimals` are the ones of
+/// the offer and the ask asset
+pub fn compute_stable_offer_amount(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    ask_amount: Uint128,
+    amp: u64,
+    decimals: [u8; 2],
+    commission_rate: Decimal256,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    let factors = precision_factors(decimals)?;
+    let offer_pool: Uint256 = offer_pool.into();
+    let ask_pool: Uint256 = ask_pool.into();
+    let ask_amount: Uint256 = ask_amount.into();
+
+    let before_commission_deduction = ask_amount
+        * (Decimal256::one()
+            .checked_div(Decimal256::one().checked_sub(commission_rate)?)
+            .map_err(|err| StdError::generic_err(err.to_string()))?);
+
+    let scaled_offer_pool = offer_pool.checked_mul(factors[0])?;
+    let d = compute_d(amp, scaled_offer_pool, ask_pool.checked_mul(factors[1])?)?;
+    let new_offer_pool = compute_y(
+        amp,
+        ask_pool
+            .checked_sub(before_commission_deduction)?
+            .checked_mul(factors[1])?,
+        d,
+    )?;
+
+    // round up by one unit in favour of the pool
+    let offer_amount = new_offer_pool
+        .checked_sub(scaled_offer_pool)?
+        .checked_add(factors[0] - Uint256::one())?
+        .checked_div(factors[0])
+        .map_err(StdError::from)?
+        .checked_add(Uint256::one())?;
+
+    let before_spread_deduction =
+        offer_amount * compute_stable_spot_price(amp, offer_pool, ask_pool, decimals)?;
+    let spread_amount = before_spread_deduction.saturating_sub(before_commission_deduction);
+    let commission_amount = before_commission_deduction * commission_rate;
+
+    Ok((
+        offer_amount.try_into().map_err(StdError::from)?,
+        spread_amount.try_into().map_err(StdError::from)?,
+        commission_amount.try_into().map_err(StdError::from)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pair::compute_swap;
+    use std::str::FromStr;
+
+    #[test]
+    fn stable_swap_better_than_constant_product() {
+        let pool = Uint128::from(1_000_000_000_000u128);
+        let offer_amount = Uint128::from(10_000_000_000u128);
+        let commission_rate = Decimal256::from_str("0.003").unwrap();
+        let operator_fee = Decimal256::zero();
+
+        let (stable_return, stable_spread, ..) = compute_stable_swap(
+            pool,
+            pool,
+            offer_amount,
+            100,
+            [6, 6],
+            commission_rate,
+            operator_fee,
+        )
+        .unwrap();
+        let (xyk_return, xyk_spread, ..) =
+            compute_swap(pool, pool, offer_amount, commission_rate, operator_fee).unwrap();
+
+        assert!(stable_return > xyk_return);
+        assert!(stable_spread < xyk_spread);
+        // balanced pool with high amp trades close to the peg
+        assert!(stable_spread < offer_amount.multiply_ratio(1u128, 1000u128));
+    }
+
+    #[test]
+    fn stable_spot_price_near_peg() {
+        let pool = Uint256::from(1_000_000_000_000u128);
+        let price = compute_stable_spot_price(100, pool, pool, [6, 6]).unwrap();
+        assert_eq!(price, Decimal256::one());
+
+        // the cheaper asset is the one with the larger balance
+        let price = compute_stable_spot_price(100, pool + pool, pool, [6, 6]).unwrap();
+        assert!(price < Decimal256::one());
+        assert!(price > Decimal256::from_str("0.9").unwrap());
+    }
+
+    #[test]
+    fn stable_spread_against_spot_price() {
+        // off-peg pool, the offered asset is already the cheaper one
+        let offer_pool = Uint128::from(3_000_000_000_000u128);
+        let ask_pool = Uint128::from(1_000_000_000_000u128);
+        let offer_amount = Uint128::from(1_000_000u128);
+
+        let (return_amount, spread_amount, ..) = compute_stable_swap(
+            offer_pool,
+            ask_pool,
+            offer_amount,
+            10,
+            [6, 6],
+            Decimal256::zero(),
+            Decimal256::zero(),
+        )
+        .unwrap();
+        let spot_return = Uint256::from(offer_amount)
+            * compute_stable_spot_price(10, offer_pool.into(), ask_pool.into(), [6, 6]).unwrap();
+
+        // a tiny trade has almost no spread, though it returns far less than 1:1
+        assert!(offer_amount - return_amount > Uint128::from(10_000u128));
+        assert!(spread_amount <= Uint128::from(2u128));
+        assert!(Uint256::from(return_amount + spread_amount + Uint128::from(2u128)) >= spot_return);
+    }
+
+    #[test]
+    fn stable_offer_amount_matches_swap() {
+        let offer_pool = Uint128::from(1_000_000_000_000u128);
+        let ask_pool = Uint128::from(800_000_000_000u128);
+        let ask_amount = Uint128::from(5_000_000_000u128);
+        let commission_rate = Decimal256::from_str("0.003").unwrap();
+
+        let (offer_amount, ..) = compute_stable_offer_amount(
+            offer_pool,
+            ask_pool,
+            ask_amount,
+            100,
+            [6, 6],
+            commission_rate,
+        )
+        .unwrap();
+        let (return_amount, ..) = compute_stable_swap(
+            offer_pool,
+            ask_pool,
+            offer_amount,
+            100,
+            [6, 6],
+            commission_rate,
+            Decimal256::zero(),
+        )
+        .unwrap();
+
+        // rounding may differ by a few units only
+        assert!(return_amount + Uint128::from(2u128) >= ask_amount);
+        assert!(return_amount <= ask_amount + Uint128::from(2u128));
+    }
+
+    #[test]
+    fn stable_swap_in_large_pool() {
+        // a billion tokens of 18 decimals on each side
+        let pool = Uint128::from(10u128.pow(27));
+        let price = compute_stable_spot_price(100, pool.into(), pool.into(), [18, 18]).unwrap();
+        assert_eq!(price, Decimal256::one());
+
+        let offer_amount = Uint128::from(10u128.pow(24));
+        let (return_amount, spread_amount, ..) = compute_stable_swap(
+            pool,
+            pool,
+            offer_amount,
+            100,
+            [18, 18],
+            Decimal256::zero(),
+            Decimal256::zero(),
+        )
+        .unwrap();
+        assert!(return_amount < offer_amount);
+        assert!(return_amount > offer_amount.multiply_ratio(999u128, 1000u128));
+        assert_eq!(
+            Uint256::from(return_amount + spread_amount),
+            Uint256::from(offer_amount) * price
+        );
+    }
+
+    #[test]
+    fn stable_swap_between_decimals() {
+        // a million tokens of 6 decimals against a million tokens of 18 decimals
+        let pool_6 = Uint128::from(10u128.pow(12));
+        let pool_18 = Uint128::from(10u128.pow(24));
+        let price = compute_stable_spot_price(100, pool_6.into(), pool_18.into(), [6, 18]).unwrap();
+        assert_eq!(price, Decimal256::from_ratio(10u128.pow(12), 1u128));
+
+        // one token of 6 decimals returns close to one token of 18 decimals
+        let (return_amount, ..) = compute_stable_swap(
+            pool_6,
+            pool_18,
+            Uint128::from(1_000_000u128),
+            100,
+            [6, 18],
+            Decimal256::zero(),
+            Decimal256::zero(),
+        )
+        .unwrap();
+        assert!(return_amount < Uint128::from(10u128.pow(18)));
+        assert!(return_amount > Uint128::from(999_990_000_000_000_000u128));
+
+        let (offer_amount, ..) = compute_stable_offer_amount(
+            pool_18,
+            pool_6,
+            Uint128::from(1_000_000u128),
+            100,
+            [18, 6],
+            Decimal256::zero(),
+        )
+        .unwrap();
+        assert!(offer_amount > Uint128::from(10u128.pow(18)));
+        assert!(offer_amount < Uint128::from(1_000_010_000_000_000_000u128));
+    }
+}
@@ -27,6 +27,7 @@ pub enum ExecuteMsg {
         owner: Option<Addr>,
         migrate_store_status: Option<bool>,
     },
+    /// `staking_token` is a cw20 LP token or the denom of a token factory LP share
     RegisterAsset {
         staking_token: Addr,
     },
@@ -48,6 +49,8 @@ pub enum ExecuteMsg {
     ////////////////////////
     /// User operations ///
     ////////////////////////
+    /// Bond a token factory LP share sent as funds, cw20 LP tokens bond through `Receive`
+    Bond {},
     Unbond {
         staking_token: Addr,
         amount: Uint128,
@@ -179,6 +179,9 @@ impl MockApp {
                         pair_admin: Some("admin".to_string()),
                         operator: Some("operator".to_string()),
                         provide_liquidity: None,
+                        pair_type: None,
+                        stable_swap: None,
+                        native_lp: None,
                     },
                     &[],
                 )
@@ -209,6 +212,9 @@ impl MockApp {
                 pair_admin: Some("admin".to_string()),
                 operator: Some("operator".to_string()),
                 provide_liquidity: None,
+                pair_type: None,
+                stable_swap: None,
+                native_lp: None,
             },
             &[],
         )
@@ -274,6 +280,9 @@ impl MockApp {
                             ],
                             receiver: None,
                         }),
+                        pair_type: None,
+                        stable_swap: None,
+                        native_lp: None,
                     },
                     &funds,
                 )
@@ -318,7 +327,10 @@ impl MockApp {
         if !self.factory_addr.as_str().is_empty() {
             return self.app.as_querier().query_wasm_smart(
                 self.factory_addr.clone(),
-                &crate::factory::QueryMsg::Pair { asset_infos },
+                &crate::factory::QueryMsg::Pair {
+                    asset_infos,
+                    pair_type: None,
+                },
             );
         }
         Err(cosmwasm_std::StdError::NotFound {
@@ -342,7 +354,10 @@ impl MockApp {
         if !self.factory_addr.as_str().is_empty() {
             return self.app.as_querier().query_wasm_smart(
                 self.factory_addr.clone(),
-                &crate::factory::QueryMsg::RestrictedAssets {},
+                &crate::factory::QueryMsg::RestrictedAssets {
+                    start_after: None,
+                    limit: None,
+                },
             );
         }
         Err(cosmwasm_std::StdError::NotFound {
@@ -0,0 +1,100 @@
+use cosmwasm_std::{Addr, Binary, CosmosMsg, Uint128};
+use prost::Message;
+
+/// Every token factory denom starts with this prefix
+pub const TOKEN_FACTORY_PREFIX: &str = "factory/";
+
+/// Full denom of a token created by `creator` with `subdenom`
+pub fn token_factory_denom(creator: &str, subdenom: &str) -> String {
+    format!("{}{}/{}", TOKEN_FACTORY_PREFIX, creator, subdenom)
+}
+
+pub fn is_token_factory_denom(denom: &str) -> bool {
+    denom.starts_with(TOKEN_FACTORY_PREFIX)
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Coin {
+    #[prost(string, tag = "1")]
+    pub denom: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub amount: ::prost::alloc::string::String,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgCreateDenom {
+    #[prost(string, tag = "1")]
+    pub sender: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub subdenom: ::prost::alloc::string::String,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgMint {
+    #[prost(string, tag = "1")]
+    pub sender: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub amount: ::core::option::Option<Coin>,
+    #[prost(string, tag = "3")]
+    pub mint_to_address: ::prost::alloc::string::String,
+}
+
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgBurn {
+    #[prost(string, tag = "1")]
+    pub sender: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub amount: ::core::option::Option<Coin>,
+    #[prost(string, tag = "3")]
+    pub burn_from_address: ::prost::alloc::string::String,
+}
+
+fn stargate_msg<M: Message>(type_url: &str, msg: M) -> CosmosMsg {
+    CosmosMsg::Stargate {
+        type_url: type_url.to_string(),
+        value: Binary::from(msg.encode_to_vec()),
+    }
+}
+
+pub fn create_denom_msg(sender: &Addr, subdenom: &str) -> CosmosMsg {
+    stargate_msg(
+        "/osmosis.tokenfactory.v1beta1.MsgCreateDenom",
+        MsgCreateDenom {
+            sender: sender.to_string(),
+            subdenom: subdenom.to_string(),
+        },
+    )
+}
+
+pub fn mint_msg(sender: &Addr, denom: &str, amount: Uint128, mint_to: &Addr) -> CosmosMsg {
+    stargate_msg(
+        "/osmosis.tokenfactory.v1beta1.MsgMint",
+        MsgMint {
+            sender: sender.to_string(),
+            amount: Some(Coin {
+                denom: denom.to_string(),
+                amount: amount.to_string(),
+            }),
+            mint_to_address: mint_to.to_string(),
+        },
+    )
+}
+
+/// Burns `amount` of `denom` held by the sender itself
+pub fn burn_msg(sender: &Addr, denom: &str, amount: Uint128) -> CosmosMsg {
+    stargate_msg(
+        "/osmosis.tokenfactory.v1beta1.MsgBurn",
+        MsgBurn {
+            sender: sender.to_string(),
+            amount: Some(Coin {
+                denom: denom.to_string(),
+                amount: amount.to_string(),
+            }),
+            burn_from_address: sender.to_string(),
+        },
+    )
+}